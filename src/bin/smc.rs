@@ -1,10 +1,24 @@
 /// smc — Search My Claude.
 ///
 /// Clap CLI harness. All business logic lives in smc::cmd::*.
-use clap::{Parser, Subcommand};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use clap::{CommandFactory, Parser, Subcommand};
 use smc::cmd;
 use smc::output::Emitter;
+use smc::session;
+use smc::util::cache::SummaryCache;
+use smc::util::config::Config;
 use smc::util::discover;
+use smc::util::fuzzy;
+use smc::util::progress::{Progress, Silent};
+use smc::util::query::Filter;
+use smc::util::redact::Redactor;
+use smc::util::state;
+use smc::util::time::TimeFormat;
 
 // ── Top-level ──────────────────────────────────────────────────────────────
 
@@ -13,8 +27,8 @@ use smc::util::discover;
     name = "smc",
     version,
     about = "Surgical search through Claude Code conversation logs",
-    long_about = "Structured JSONL output for search, sessions, show, tools, export, \
-                  context, stats, projects, freq, recent. Every record is machine-parseable \
+    long_about = "Structured JSONL output for search, sessions, show, tools, tools-schema, export, \
+                  context, split, stats, projects, growth, hours, freq, recent, validate, errors, outcomes, site, db, dataset, alerts, blame, resume, doctor, digest, backup, restore, watch. Every record is machine-parseable \
                   JSON Lines — zero ANSI, zero pagination.",
     after_help = "Exit codes: 0 = success/match, 1 = no results, 2 = error\n\n\
                   NOTE: All output is single-line JSONL. Pipe through `cat` or redirect \
@@ -24,13 +38,74 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Path to Claude projects directory (default: ~/.claude/projects)
+    /// Path to Claude projects directory (default: ~/.claude/projects); `-` reads stdin
     #[arg(long, global = true)]
     path: Option<String>,
 
+    /// Read JSONL records from stdin instead of discovered files
+    #[arg(long, global = true)]
+    stdin: bool,
+
     /// Hard cap on output tokens (0 = unlimited)
     #[arg(long, global = true, value_name = "N")]
     max_tokens: Option<usize>,
+
+    /// Report every unparseable JSONL line as an inline warning, then a parse_summary by kind.
+    /// Runs as a separate pass before the command, so the files it reads are read twice
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Render timestamps in the local timezone instead of UTC
+    #[arg(long, global = true)]
+    local: bool,
+
+    /// Add a relative `age` field ("2h ago") to sessions, recent, and search
+    #[arg(long, global = true)]
+    relative: bool,
+
+    /// Add a file:// `url` field pointing at the session JSONL
+    #[arg(long, global = true)]
+    links: bool,
+
+    /// Filter expression, e.g. 'role:assistant tool:Bash project:myapp after:2025-01-01 "timeout"'
+    #[arg(long, global = true, value_name = "EXPR")]
+    filter: Option<String>,
+
+    /// Worker threads for parallel scans (default: [limits] threads, else one per core)
+    #[arg(long, global = true, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Skip session files larger than this in multi-file scans, e.g. 500MB
+    #[arg(long, global = true, value_name = "SIZE")]
+    max_file_size: Option<String>,
+
+    /// Re-parse every file instead of using ~/.smc/cache (sessions, projects, freq)
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Show scan progress on stderr (search, freq): text (a status line, the
+    /// default for a bare --progress) or json (one event per line)
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "text"
+    )]
+    progress: Option<String>,
+
+    /// Skip the external filter commands configured under [plugin] in ~/.smc/config.toml
+    #[arg(long, global = true)]
+    no_plugins: bool,
+
+    /// Extra regex to redact, on top of the built-in secret patterns (repeatable)
+    #[arg(long, global = true, value_name = "REGEX")]
+    redact_pattern: Vec<String>,
+
+    /// Count smc's own <smc-cc-cli> output in search, watch, recent, and freq (default: [policy] include_smc)
+    #[arg(long, global = true)]
+    include_smc: bool,
 }
 
 // ── Commands ───────────────────────────────────────────────────────────────
@@ -45,15 +120,27 @@ enum Commands {
     #[command(visible_alias = "ls")]
     Sessions(SessionsArgs),
 
+    /// The most recently active session, optionally chained into another command
+    Last(LastArgs),
+
     /// Pretty-print a conversation as JSONL message records
     Show(ShowArgs),
 
+    /// Show a conversation across a session and the sessions resumed from it
+    Thread(ThreadArgs),
+
     /// List every tool call in a session with timestamps
     #[command(visible_alias = "t")]
     Tools(ToolsArgs),
 
-    /// Aggregate statistics: sessions, sizes, top projects
-    Stats,
+    /// Observed input keys and value types of each tool across all sessions
+    ToolsSchema(ToolsSchemaArgs),
+
+    /// API token usage of a session, in total and by model
+    Tokens(TokensArgs),
+
+    /// Aggregate statistics: sessions, sizes, top projects; or one project in depth
+    Stats(StatsArgs),
 
     /// Export a session as markdown (file or stdout)
     #[command(visible_alias = "e")]
@@ -63,17 +150,93 @@ enum Commands {
     #[command(visible_alias = "ctx")]
     Context(ContextArgs),
 
+    /// Print one JSONL record in full: `smc msg <session>:<line>`
+    Msg(MsgArgs),
+
+    /// Cut a session into head and tail JSONL files at a message boundary
+    Split(SplitArgs),
+
     /// List projects with session counts, sizes, and date ranges
     #[command(visible_alias = "p")]
-    Projects,
+    Projects(ProjectsArgs),
 
-    /// Frequency analysis: chars, words, tools, or roles
+    /// Sessions and bytes added per day, week, month, or year, by project
+    Growth(GrowthArgs),
+
+    /// Active hours by weekday and project: hourly histogram, earliest and latest message
+    Hours(HoursArgs),
+
+    /// Frequency analysis: chars, words, tools, roles, file churn, or record types
     #[command(visible_alias = "f")]
     Freq(FreqArgs),
 
     /// Most recent messages across all sessions
     #[command(visible_alias = "r")]
     Recent(RecentArgs),
+
+    /// Report JSONL lines that fail to parse, grouped by error kind
+    Validate(ValidateArgs),
+
+    /// Sample records of types smc doesn't model yet, grouped by type
+    Unknown(UnknownArgs),
+
+    /// List API error and retry events by type and time
+    Errors(ErrorsArgs),
+
+    /// Search tool results, showing the call that produced each hit
+    Results(ResultsArgs),
+
+    /// Classify sessions as completed, abandoned, interrupted- or error-heavy, per project
+    Outcomes(OutcomesArgs),
+
+    /// Integrity check: unreadable/truncated files, parse failures, duplicates, clock skew
+    Doctor(DoctorArgs),
+
+    /// Write a dated markdown digest of activity since the previous run
+    Digest(DigestArgs),
+
+    /// Time cold and warm full-corpus scans: discovery, parse, search, cached summaries
+    Bench(BenchArgs),
+
+    /// Generate a browsable static HTML site of all sessions
+    Site(SiteArgs),
+
+    /// Incrementally sync sessions into an Obsidian vault as linked markdown notes
+    SyncObsidian(SyncObsidianArgs),
+
+    /// Convert sessions into fine-tuning conversation datasets
+    Dataset(DatasetArgs),
+
+    /// List every session that read or changed a file
+    Blame(BlameArgs),
+
+    /// Find a session and continue it with `claude --resume`
+    Resume(ResumeArgs),
+
+    /// Tag a session so `smc resume <tag>` finds it, or list its tags
+    Tag(TagArgs),
+
+    /// Stream newly appended messages from every session as they are written
+    #[cfg(feature = "watch")]
+    #[command(visible_alias = "w")]
+    Watch(WatchArgs),
+
+    /// Keyword alerts on fresh transcript content
+    #[command(subcommand)]
+    Alerts(AlertsCommand),
+
+    /// Snapshot session files and smc state into a .tar.zst archive
+    #[cfg(feature = "backup")]
+    Backup(BackupArgs),
+
+    /// Restore sessions and smc state from a backup archive
+    #[cfg(feature = "backup")]
+    Restore(RestoreArgs),
+
+    /// Mirror sessions into SQLite and query them with SQL
+    #[cfg(feature = "sqlite")]
+    #[command(subcommand)]
+    Db(DbCommand),
 }
 
 // ── search ─────────────────────────────────────────────────────────────────
@@ -94,15 +257,29 @@ struct SearchArgs {
     #[arg(long, short = 'e')]
     regex: bool,
 
+    /// Match whole words after English stemming: "deploying" also finds
+    /// "deploy", "deployed", "deployment"
+    #[arg(long, conflicts_with = "regex")]
+    stem: bool,
+
     /// Require ALL terms to match (default is OR)
     #[arg(long, short = 'a')]
     and: bool,
 
-    /// Filter by role (user, assistant, system)
+    /// Match sessions where every term appears somewhere, not necessarily in one
+    /// message; shows each term's first hit, and --max counts sessions
+    #[arg(long, conflicts_with = "and")]
+    session_all: bool,
+
+    /// Also match each term's synonyms from [expand] in ~/.smc/config.toml
+    #[arg(long)]
+    expand: bool,
+
+    /// Filter by role: user, assistant (asst), system, any-human; comma-separated
     #[arg(long)]
     role: Option<String>,
 
-    /// Filter by tool name
+    /// Filter by tool name: exact (any case), `~` prefix for substring; comma-separated
     #[arg(long)]
     tool: Option<String>,
 
@@ -110,11 +287,11 @@ struct SearchArgs {
     #[arg(long, short)]
     project: Option<String>,
 
-    /// Only results after this date (YYYY-MM-DD)
-    #[arg(long)]
+    /// Only results after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long, visible_alias = "since", alias = "since-last")]
     after: Option<String>,
 
-    /// Only results before this date (YYYY-MM-DD)
+    /// Only results before this date (YYYY-MM-DD) or span back from now (48h, 7d)
     #[arg(long)]
     before: Option<String>,
 
@@ -122,13 +299,17 @@ struct SearchArgs {
     #[arg(long)]
     branch: Option<String>,
 
-    /// Maximum number of results
-    #[arg(long, short = 'n', default_value = "50")]
+    /// Maximum number of results (0 = unlimited)
+    #[arg(long, short = 'n', visible_alias = "limit", default_value = "50")]
     max: usize,
 
-    /// Include results from previous smc output (excluded by default)
-    #[arg(long, short = 'i')]
-    include_smc: bool,
+    /// Short for the global --include-smc
+    #[arg(short = 'i')]
+    include_smc_short: bool,
+
+    /// Only hits in previous smc output, e.g. to debug output fed back into sessions
+    #[arg(long)]
+    only_smc: bool,
 
     /// Exclude a specific session ID
     #[arg(long)]
@@ -149,6 +330,62 @@ struct SearchArgs {
     /// Exclude thinking blocks from search
     #[arg(long)]
     no_thinking: bool,
+
+    /// Max chars of message text per hit (default: [truncate] search, else 500)
+    #[arg(long, value_name = "N")]
+    truncate: Option<usize>,
+
+    /// Disable truncation entirely
+    #[arg(long)]
+    full: bool,
+
+    /// Compact records: session, line, role, timestamp, 100-char preview
+    #[arg(long)]
+    oneline: bool,
+
+    /// Add cwd, uuid, parent_uuid, model, and token usage to each hit
+    #[arg(long, conflicts_with = "oneline")]
+    json_rich: bool,
+
+    /// Add a `snippets` entry of N chars centred on the first match
+    #[arg(long, value_name = "N", conflicts_with = "oneline")]
+    snippet_chars: Option<usize>,
+
+    /// One snippet per match position in the message (150 chars unless --snippet-chars)
+    #[arg(long, conflicts_with = "oneline")]
+    all_occurrences: bool,
+
+    /// Replace API keys, tokens, and emails in hit text with [REDACTED:<kind>]
+    #[arg(long)]
+    redact: bool,
+
+    /// Also copy the hits to the clipboard as a markdown list, in <smc-cc-cli> tags
+    #[arg(long)]
+    copy: bool,
+
+    /// With --copy, leave out the <smc-cc-cli> tags (for pasting outside Claude)
+    #[arg(long, requires = "copy")]
+    no_tag: bool,
+
+    /// Also write the hits as a markdown list to FILE; strftime tokens allowed (smc-%Y-%m-%d.md)
+    #[arg(long, value_name = "FILE")]
+    md: Option<String>,
+
+    /// Append to the --md file instead of replacing it
+    #[arg(long, requires = "md")]
+    md_append: bool,
+
+    /// Also write one markdown file per matching session, hits with context, into DIR
+    #[arg(long, value_name = "DIR")]
+    md_split: Option<String>,
+
+    /// Messages shown either side of each hit in --md-split files
+    #[arg(long, value_name = "N", default_value = "2", requires = "md_split")]
+    md_context: usize,
+
+    /// Also write an HTML report to FILE: filters, hits with collapsible full messages, a filter box
+    #[arg(long, value_name = "FILE")]
+    html: Option<String>,
 }
 
 // ── sessions ───────────────────────────────────────────────────────────────
@@ -157,25 +394,89 @@ struct SearchArgs {
 #[command(
     about = "List sessions with previews, dates, and sizes",
     long_about = "List conversation sessions sorted by date. Each record includes the \
-                  session ID, project name, file size, first timestamp, first user \
-                  message preview, and message count."
+                  session ID, project name, file size, first and last timestamps, \
+                  duration, message count, and a preview: the first user message, or with \
+                  --preview last the final prompt and the assistant's reply."
 )]
 struct SessionsArgs {
-    /// Maximum sessions to show
-    #[arg(long, short = 'n', default_value = "20")]
+    /// Maximum sessions to show (0 = unlimited)
+    #[arg(long, short = 'n', visible_alias = "max", default_value = "20")]
     limit: usize,
 
     /// Filter by project name
     #[arg(long, short)]
     project: Option<String>,
 
-    /// Only sessions after this date (YYYY-MM-DD)
-    #[arg(long)]
+    /// Only sessions after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long, visible_alias = "since", alias = "since-last")]
     after: Option<String>,
 
-    /// Only sessions before this date (YYYY-MM-DD)
+    /// Only sessions before this date (YYYY-MM-DD) or span back from now (48h, 7d)
     #[arg(long)]
     before: Option<String>,
+
+    /// Preview the first user message or the final exchange: first, last
+    #[arg(long, value_name = "MODE", default_value = "first")]
+    preview: String,
+}
+
+// ── last ───────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "The most recently active session, optionally chained into another command",
+    long_about = "Resolve the session whose file was written most recently (within \
+                  --project if given). Alone, emits its sessions record; followed by a \
+                  single-session command — show, thread, tools, tokens, export, context, \
+                  split, tag — runs that command on it with the remaining arguments, e.g. \
+                  `smc last show --oneline` or `smc last export -o`.",
+    after_help = "Examples:\n  smc last\n  smc last -p myapp show --from 10\n  smc last export -o\n  smc last tokens"
+)]
+struct LastArgs {
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Command to run on the session, with its arguments
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+    action: Vec<String>,
+}
+
+/// Parse `smc last <command> [args]` as `smc <command> <id> [args]`, global
+/// flags included.
+fn parse_chained(action: &[String], id: &str) -> anyhow::Result<Cli> {
+    let Some((name, rest)) = action.split_first() else { anyhow::bail!("no command to chain") };
+    let takes_session = Cli::command()
+        .find_subcommand(name)
+        .is_some_and(|c| c.get_arguments().any(|a| a.get_id() == "session"));
+    anyhow::ensure!(
+        takes_session,
+        "smc last can only run single-session commands (show, thread, tools, tokens, export, context, split, tag), not '{}'",
+        name
+    );
+    let argv = ["smc last", name, id].into_iter().chain(rest.iter().map(String::as_str));
+    Ok(Cli::try_parse_from(argv).unwrap_or_else(|e| e.exit()))
+}
+
+impl Cli {
+    /// Apply the global flags of a chained command line on top of these.
+    fn merge_globals(&mut self, other: Cli) {
+        self.path = other.path.or(self.path.take());
+        self.stdin |= other.stdin;
+        self.max_tokens = other.max_tokens.or(self.max_tokens);
+        self.strict |= other.strict;
+        self.local |= other.local;
+        self.relative |= other.relative;
+        self.links |= other.links;
+        self.filter = other.filter.or(self.filter.take());
+        self.threads = other.threads.or(self.threads);
+        self.max_file_size = other.max_file_size.or(self.max_file_size.take());
+        self.no_cache |= other.no_cache;
+        self.progress = other.progress.or(self.progress.take());
+        self.no_plugins |= other.no_plugins;
+        self.redact_pattern.extend(other.redact_pattern);
+        self.include_smc |= other.include_smc;
+    }
 }
 
 // ── show ───────────────────────────────────────────────────────────────────
@@ -185,15 +486,19 @@ struct SessionsArgs {
     about = "Pretty-print a conversation as JSONL message records",
     long_about = "Emit every message in a session as structured JSONL. Each record \
                   includes role, timestamp, text content, and tool calls. Use --thinking \
-                  to include thinking blocks, --from/--to to slice by message index."
+                  to include thinking blocks, --from/--to to slice by message index. \
+                  Sub-agent messages carry sidechain: true and follow the message whose \
+                  Task call spawned them (spawned_by: its tool_use id); --no-sidechains \
+                  hides them."
 )]
 struct ShowArgs {
     /// Session ID (or prefix)
     session: String,
 
-    /// Include thinking blocks
-    #[arg(long)]
-    thinking: bool,
+    /// Include thinking blocks: collapse (own field, the default for a bare
+    /// --thinking), inline (quoted in the text), omit
+    #[arg(long, value_name = "MODE", num_args = 0..=1, require_equals = true, default_missing_value = "collapse")]
+    thinking: Option<String>,
 
     /// Start from this message number
     #[arg(long)]
@@ -202,6 +507,51 @@ struct ShowArgs {
     /// End at this message number
     #[arg(long)]
     to: Option<usize>,
+
+    /// Max chars of each tool input preview (default: [truncate] show, else 200)
+    #[arg(long, value_name = "N")]
+    truncate: Option<usize>,
+
+    /// Disable truncation entirely
+    #[arg(long)]
+    full: bool,
+
+    /// Compact records: index, role, timestamp, 100-char preview
+    #[arg(long)]
+    oneline: bool,
+
+    /// Only these roles: user, assistant (asst), system, any-human; comma-separated
+    #[arg(long)]
+    role: Option<String>,
+
+    /// Replace API keys, tokens, and emails with [REDACTED:<kind>]
+    #[arg(long)]
+    redact: bool,
+
+    /// Leave out sub-agent (sidechain) messages instead of nesting them
+    /// after the Task call that spawned them
+    #[arg(long)]
+    no_sidechains: bool,
+
+    /// Also list these non-message records in sequence: progress, file-history, summary
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    include: Vec<String>,
+}
+
+// ── thread ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Show a conversation across a session and the sessions resumed from it",
+    long_about = "Find every session in the same project that shares message uuids with \
+                  this one (claude --resume copies the earlier messages into the new \
+                  file), drop the repeated messages, and emit the rest in time order as \
+                  show records. A thread record listing the chain comes first, and a \
+                  boundary record marks each switch to another session file."
+)]
+struct ThreadArgs {
+    #[command(flatten)]
+    show: ShowArgs,
 }
 
 // ── tools ──────────────────────────────────────────────────────────────────
@@ -215,6 +565,86 @@ struct ShowArgs {
 struct ToolsArgs {
     /// Session ID (or prefix)
     session: String,
+
+    /// Only these tools: exact name (any case), `~` prefix for substring; comma-separated
+    #[arg(long)]
+    tool: Option<String>,
+}
+
+// ── tools-schema ───────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Observed input keys and value types of each tool across all sessions",
+    long_about = "Walk the input of every tool call in the corpus and report, per tool, \
+                  each key path (`a.b` for nested keys, `a[]` for array elements), \
+                  how many calls carry it, the JSON types seen there, and an example value."
+)]
+struct ToolsSchemaArgs {
+    /// Only these tools: exact name (any case), `~` prefix for substring; comma-separated
+    #[arg(long)]
+    tool: Option<String>,
+}
+
+// ── tokens ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "API token usage of a session, in total and by model",
+    long_about = "Sum the usage reported on every API response in a session: input, \
+                  output, cache creation and cache read tokens, the number of calls, \
+                  and totals per model."
+)]
+struct TokensArgs {
+    /// Session ID (or prefix)
+    session: String,
+}
+
+// ── stats ──────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Aggregate statistics: sessions, sizes, top projects; or one project in depth",
+    long_about = "Without a project: session count, total size, and the 15 largest projects. \
+                  With one: sessions and size growth by month, token totals by model, \
+                  tool mix, the files most often touched, and the busiest days."
+)]
+struct StatsArgs {
+    /// Project name, or a substring matching exactly one project
+    project: Option<String>,
+}
+
+// ── projects ───────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "List projects with session counts, sizes, and date ranges",
+    long_about = "One record per project, most recently active first. With --match, only \
+                  projects near the given name, closest first, each with its edit distance \
+                  (0 = the name contains it): the same resolution --project uses in search, \
+                  sessions, and the bulk exports, where a typo of exactly one project is \
+                  corrected and an ambiguous one lists suggestions."
+)]
+struct ProjectsArgs {
+    /// Only projects near this name, closest first
+    #[arg(long = "match", value_name = "NAME")]
+    matches: Option<String>,
+}
+
+// ── growth ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Sessions and bytes added per day, week, month, or year, by project",
+    long_about = "Bucket every session by the period of its first message and emit one \
+                  growth record per period: sessions and bytes added, running totals, \
+                  and each project's share. The summary ranks projects by total bytes, \
+                  so you can see when usage ramped up and what is filling the disk."
+)]
+struct GrowthArgs {
+    /// Bucket size: day, week, month, year
+    #[arg(long, short, default_value = "month")]
+    interval: String,
 }
 
 // ── export ─────────────────────────────────────────────────────────────────
@@ -234,9 +664,54 @@ struct ExportArgs {
     #[arg(long, short)]
     output: bool,
 
-    /// Output file path (default: <session-id>.md)
+    /// Output file path (default: <session-id>.md); may use {session}, {project}, {date},
+    /// {branch}, {model}, {title}
     #[arg(long, value_name = "FILE")]
     md: Option<String>,
+
+    /// Copy the markdown to the clipboard, in <smc-cc-cli> tags (instead of the default file)
+    #[arg(long)]
+    copy: bool,
+
+    /// With --copy, leave out the <smc-cc-cli> tags (for pasting outside Claude)
+    #[arg(long, requires = "copy")]
+    no_tag: bool,
+
+    /// Skip secret redaction (on by default)
+    #[arg(long)]
+    no_redact: bool,
+
+    /// Start with YAML front matter: id, project, dates, branch, model, tokens, tags
+    #[arg(long)]
+    front_matter: bool,
+
+    /// End with a fenced JSON block of message counts, tokens, tools, and duration
+    #[arg(long)]
+    stats_footer: bool,
+
+    /// Markdown template with {{placeholders}} (see README)
+    #[arg(long, value_name = "FILE")]
+    template: Option<String>,
+
+    /// Tag for the front matter (repeatable; adds to config tags)
+    #[arg(long, value_name = "TAG")]
+    tag: Vec<String>,
+
+    /// Thinking blocks: collapse (<details>), inline (blockquote), omit
+    #[arg(long, value_name = "MODE", default_value = "collapse")]
+    thinking: String,
+
+    /// Rewrap prose lines at N columns, leaving code blocks untouched (0 = off)
+    #[arg(long, value_name = "N", default_value = "0")]
+    wrap: usize,
+
+    /// Max chars of each tool result, cut at a line end (0 = no limit)
+    #[arg(long, value_name = "N", default_value = "2000")]
+    tool_result_limit: usize,
+
+    /// Write each truncated tool result in full to DIR/<tool_use_id>.txt
+    #[arg(long, value_name = "DIR")]
+    tool_result_files: Option<String>,
 }
 
 // ── context ────────────────────────────────────────────────────────────────
@@ -258,30 +733,140 @@ struct ContextArgs {
     /// Number of messages to show before and after
     #[arg(long, short = 'C', default_value = "3")]
     context: usize,
+
+    /// Max chars of message text per record (default: [truncate] context, else 500)
+    #[arg(long, value_name = "N")]
+    truncate: Option<usize>,
+
+    /// Disable truncation entirely
+    #[arg(long)]
+    full: bool,
+}
+
+// ── msg ────────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Print one JSONL record in full: `smc msg <session>:<line>`",
+    long_about = "Print the message at a JSONL line, as search hits and `smc context` address \
+                  it, with nothing truncated: every text, thinking, tool call, and tool result \
+                  block. --json prints the stored line as is; --field prints one part as plain \
+                  text.",
+    after_help = "Examples:\n  smc msg 394afc:50\n  smc msg 394afc:50 --json | jq .message.usage\n  \
+                  smc msg 394afc:51 --field tool-input"
+)]
+struct MsgArgs {
+    /// Session ID (or prefix) and JSONL line, e.g. 394afc:50
+    #[arg(value_name = "SESSION:LINE")]
+    target: String,
+
+    /// Print the raw JSONL line instead of a message record
+    #[arg(long, conflicts_with = "field")]
+    json: bool,
+
+    /// Print only this part as plain text: text, tool-input, thinking
+    #[arg(long)]
+    field: Option<String>,
+}
+
+// ── split ──────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Cut a session into head and tail JSONL files at a message boundary",
+    long_about = "Write <session>.head.jsonl and <session>.tail.jsonl, copying every line \
+                  byte for byte. The cut moves forward past tool results so each tool call \
+                  stays with its result. The original file is not touched."
+)]
+struct SplitArgs {
+    /// Session ID (or prefix)
+    session: String,
+
+    /// Where the tail starts: line:N (JSONL line, also a bare N) or msg:N (message index)
+    #[arg(long)]
+    at: String,
+
+    /// Directory to write the two parts to
+    #[arg(long, default_value = ".")]
+    out: String,
+
+    /// Overwrite existing part files
+    #[arg(long)]
+    force: bool,
+}
+
+// ── hours ──────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Active hours by weekday and project: hourly histogram, earliest and latest message",
+    long_about = "Bucket every message by local hour of day. Emits one record per weekday \
+                  (active days, messages per hour, earliest and latest message, and the \
+                  median first and last message of an active day), one per project, and \
+                  a corpus summary."
+)]
+struct HoursArgs {
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Only messages after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only messages before this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    before: Option<String>,
+
+    /// Bucket by UTC instead of the local timezone
+    #[arg(long)]
+    utc: bool,
 }
 
 // ── freq ───────────────────────────────────────────────────────────────────
 
 #[derive(Parser)]
 #[command(
-    about = "Frequency analysis: chars, words, tools, or roles",
+    about = "Frequency analysis: chars, words, tools, roles, file churn, or record types",
     long_about = "Count character distributions, word frequencies, tool usage, \
-                  or message role breakdowns across all conversation logs. \
-                  Modes: chars (c), words (w), tools (t), roles (r). \
-                  Use --raw with chars mode to count raw JSONL bytes."
+                  message role breakdowns, per-file read/edit/write churn, or records \
+                  per JSONL type across all conversation logs. Modes: chars (c), words (w), \
+                  tools (t), roles (r), churn, types. \
+                  Use --raw with chars mode to count raw JSONL bytes, and --class \
+                  to count digits, punctuation, scripts, or a custom set instead of a–z."
 )]
 struct FreqArgs {
-    /// What to count: chars, words, tools, roles
+    /// What to count: chars, words, tools, roles, churn, types
     #[arg(default_value = "chars")]
     mode: String,
 
-    /// Max items to show (for words mode)
+    /// Max items to show (words, tools, and churn modes; types per project)
     #[arg(long, short = 'n', default_value = "30")]
     limit: usize,
 
     /// Count raw file bytes instead of parsed message content
     #[arg(long)]
     raw: bool,
+
+    /// Chars mode only: letters, digits, punct, unicode-script, or custom:<chars> (e.g. custom:"{}[]()")
+    #[arg(long, default_value = "letters")]
+    class: String,
+
+    /// Tools mode only: count just these tools (exact, `~` prefix for substring; comma-separated)
+    #[arg(long)]
+    tool: Option<String>,
+
+    /// Only sessions of projects matching this (substring)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Words mode only: count just these roles (user, assistant (asst), system, any-human; comma-separated)
+    #[arg(long)]
+    role: Option<String>,
+
+    /// Words mode only: top terms of these projects side by side (comma-separated)
+    #[arg(long, value_name = "A,B", value_delimiter = ',', conflicts_with = "project")]
+    compare: Vec<String>,
 }
 
 // ── recent ─────────────────────────────────────────────────────────────────
@@ -293,11 +878,19 @@ struct FreqArgs {
                   Filter by role or project. Useful for picking up where you left off."
 )]
 struct RecentArgs {
-    /// Number of recent messages to show
-    #[arg(long, short = 'n', default_value = "10")]
+    /// Number of recent messages to show (0 = all in range)
+    #[arg(long, short = 'n', visible_alias = "max", default_value = "10")]
     limit: usize,
 
-    /// Filter by role
+    /// Only messages after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long, visible_alias = "since", alias = "since-last")]
+    after: Option<String>,
+
+    /// Only messages before this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    before: Option<String>,
+
+    /// Filter by role: user, assistant (asst), system, any-human; comma-separated
     #[arg(long)]
     role: Option<String>,
 
@@ -306,146 +899,1344 @@ struct RecentArgs {
     project: Option<String>,
 }
 
-// ── main ───────────────────────────────────────────────────────────────────
-
-fn main() {
-    let cli = Cli::parse();
-    let max_tokens = cli.max_tokens.unwrap_or(0);
+// ── validate ───────────────────────────────────────────────────────────────
 
-    let result = run(cli, max_tokens);
-
-    match result {
-        Ok(true) => std::process::exit(0),
-        Ok(false) => std::process::exit(1),
-        Err(e) => {
-            eprintln!("{:#}", e);
-            std::process::exit(2);
-        }
-    }
+#[derive(Parser)]
+#[command(
+    about = "Report JSONL lines that fail to parse, grouped by error kind",
+    long_about = "Parse every line of every session and emit one parse_error record \
+                  (file, line, serde error) per failure, followed by a summary with \
+                  counts per error kind. Use this to spot Claude Code schema drift."
+)]
+struct ValidateArgs {
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
 }
 
-/// Returns Ok(true) for success/matches, Ok(false) for no results.
-fn run(cli: Cli, max_tokens: usize) -> anyhow::Result<bool> {
-    let claude_dir = discover::claude_dir(cli.path.as_deref())?;
-    let files = discover::discover_jsonl_files(&claude_dir)?;
+// ── unknown ────────────────────────────────────────────────────────────────
 
-    match cli.command {
-        Commands::Search(args) => {
-            let opts = cmd::search::SearchOpts {
-                queries: args.query,
-                is_regex: args.regex,
-                and_mode: args.and,
-                role: args.role,
-                tool: args.tool,
-                project: args.project,
-                after: args.after,
-                before: args.before,
-                branch: args.branch,
-                file: args.file,
-                tool_input: args.tool_input,
-                thinking_only: args.thinking,
-                no_thinking: args.no_thinking,
-                max_results: args.max,
-                include_smc: args.include_smc,
-                exclude_session: args.exclude_session,
-                max_tokens,
-            };
-            let mut em = Emitter::stdout(max_tokens);
-            cmd::search::run(&opts, &files, &mut em)?;
-        }
+#[derive(Parser)]
+#[command(
+    about = "Sample records of types smc doesn't model yet, grouped by type",
+    long_about = "Find every line whose type smc reads as an unknown record (skipped by \
+                  all other commands) and emit one unknown_type record per type value: \
+                  how many lines and sessions have it, plus the first few lines in full. \
+                  Use this to spot new Claude Code record types."
+)]
+struct UnknownArgs {
+    /// Sample lines per type
+    #[arg(long, short = 'n', default_value = "20")]
+    limit: usize,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+}
+
+// ── errors ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "List API error and retry events by type and time",
+    long_about = "Emit one api_error record per logged API failure — system retry events \
+                  (overloaded, rate limit, connection reset) and synthetic \"API Error\" \
+                  assistant messages — newest first, with a summary of counts per kind."
+)]
+struct ErrorsArgs {
+    /// Maximum events to show (0 = all)
+    #[arg(long, short = 'n', default_value = "50")]
+    limit: usize,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Only events after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only events before this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    before: Option<String>,
+}
+
+// ── results ────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Search tool results, showing the call that produced each hit",
+    long_about = "Search only tool output: the result blocks and the structured toolUseResult \
+                  copy (stdout, stderr, file content) stored beside them. Each result_hit \
+                  carries the tool name and input of the call that produced it and snippets \
+                  around the matches instead of a message preview; newest first."
+)]
+struct ResultsArgs {
+    /// Search queries (multiple terms are OR'd together)
+    query: Vec<String>,
+
+    /// Treat query as regex
+    #[arg(long, short = 'e')]
+    regex: bool,
+
+    /// Require ALL terms to match (default is OR)
+    #[arg(long, short = 'a')]
+    and: bool,
+
+    /// Only results of these tools: exact (any case), `~` prefix for substring; comma-separated
+    #[arg(long)]
+    tool: Option<String>,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Only results after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only results before this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    before: Option<String>,
+
+    /// Maximum hits to show (0 = all)
+    #[arg(long, short = 'n', default_value = "50")]
+    limit: usize,
+
+    /// Characters of result text per snippet
+    #[arg(long, default_value = "200")]
+    context: usize,
+}
+
+// ── outcomes ───────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Classify sessions as completed, abandoned, interrupted- or error-heavy, per project",
+    long_about = "Give every session a heuristic outcome: completed (ran `git commit`), \
+                  interrupted_heavy (many \"[Request interrupted by user]\" markers), \
+                  error_heavy (API errors and failed tool calls), abandoned (ends on an \
+                  unanswered prompt or interruption), or other. Emits counts per project \
+                  and a corpus summary."
+)]
+struct OutcomesArgs {
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Also emit one outcome record per session, with the signals behind it
+    #[arg(long)]
+    sessions: bool,
+}
+
+// ── doctor ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Integrity check: unreadable/truncated files, parse failures, duplicates, clock skew",
+    long_about = "Check every session file for unreadable files, truncated last lines, JSON \
+                  parse failures, sessions with no messages, duplicate session IDs across \
+                  projects, and future or backwards-jumping timestamps. Emits a summary of \
+                  issue counts; --verbose adds one doctor_file record per unhealthy file."
+)]
+struct DoctorArgs {
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Emit per-file details for every file with issues
+    #[arg(long, short)]
+    verbose: bool,
+}
+
+// ── site ───────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Generate a browsable static HTML site of all sessions",
+    long_about = "Write an index page grouped by project and date, one HTML page per \
+                  session, and a client-side search index into --out. The result opens \
+                  from disk with no server, for sharing or long-term archiving."
+)]
+struct SiteArgs {
+    /// Output directory (created if missing)
+    #[arg(long, value_name = "DIR")]
+    out: String,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Skip secret redaction (on by default)
+    #[arg(long)]
+    no_redact: bool,
+}
+
+// ── sync-obsidian ──────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Incrementally sync sessions into an Obsidian vault as linked markdown notes",
+    long_about = "Write one note per session (front matter plus the conversation) under \
+                  Sessions/, one per project under Projects/, and one per changed file \
+                  under Files/, joined by wikilinks. Only new or changed sessions are \
+                  re-rendered, and only notes whose text changed are rewritten, so it is \
+                  cheap to run from cron or a SessionEnd hook."
+)]
+struct SyncObsidianArgs {
+    /// Vault directory (created if missing)
+    #[arg(long, value_name = "DIR")]
+    vault: String,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Tag for the session notes' front matter (repeatable; adds to config tags)
+    #[arg(long, value_name = "TAG")]
+    tag: Vec<String>,
+
+    /// Skip secret redaction (on by default)
+    #[arg(long)]
+    no_redact: bool,
+}
+
+// ── dataset ────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Convert sessions into fine-tuning conversation datasets",
+    long_about = "Write one conversation per session to --out as JSONL in openai-chat \
+                  ({\"messages\": [...]}) or sharegpt ({\"conversations\": [...]}) format. \
+                  Slash commands and hook-injected reminders are dropped; consecutive \
+                  same-role messages are merged. Tool calls are dropped unless --inline-tools."
+)]
+struct DatasetArgs {
+    /// Output file (JSONL)
+    #[arg(long, value_name = "FILE")]
+    out: String,
+
+    /// Output format: openai-chat, sharegpt
+    #[arg(long, default_value = "openai-chat")]
+    format: String,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Only sessions starting after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only sessions starting before this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    before: Option<String>,
+
+    /// Skip conversations with fewer user turns
+    #[arg(long, default_value = "1")]
+    min_turns: usize,
+
+    /// Inline tool calls and results into assistant turns
+    #[arg(long)]
+    inline_tools: bool,
+
+    /// System prompt prepended to every conversation
+    #[arg(long)]
+    system: Option<String>,
+
+    /// Skip secret redaction (on by default)
+    #[arg(long)]
+    no_redact: bool,
+}
+
+// ── blame ──────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "List every session that read or changed a file",
+    long_about = "A git blame for Claude activity: scans Read/Edit/MultiEdit/Write/NotebookEdit \
+                  tool calls and file-history snapshots, and emits one blame record per session \
+                  with first/last touch times, counts, and diff snippets of each change. \
+                  Relative paths match as a suffix (src/main.rs matches /repo/src/main.rs)."
+)]
+struct BlameArgs {
+    /// File path (absolute, or a relative suffix)
+    #[arg(value_name = "PATH")]
+    file: String,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Max changes listed per session (0 = all)
+    #[arg(long, default_value = "10")]
+    changes: usize,
+}
+
+// ── resume ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Find a session and continue it with `claude --resume`",
+    long_about = "Resolve a session by ID prefix, then by tag (see `smc tag`; the newest \
+                  tagged session wins), or — when neither matches — by search terms (AND; \
+                  the session with the most hits wins, the newest hit breaking ties). Emits a resume record with the \
+                  session's summary, then execs `claude --resume <id>` in the session's last \
+                  working directory."
+)]
+struct ResumeArgs {
+    /// Session ID prefix, tag, or search terms
+    #[arg(required = true)]
+    query: Vec<String>,
+
+    /// Print the resolved session and command without running it
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+
+    /// Claude Code executable
+    #[arg(long, value_name = "BIN", default_value = "claude")]
+    claude: String,
+}
+
+// ── tag ────────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Tag a session so `smc resume <tag>` finds it, or list its tags",
+    long_about = "Attach tags to a session, stored in ~/.smc/tags.json. Tags are \
+                  case-insensitive; without any, emits the session's current tags.",
+    after_help = "Examples:\n  smc tag 394af auth-refactor\n  smc last tag wip\n  smc tag 394af wip --remove"
+)]
+struct TagArgs {
+    /// Session ID (or prefix)
+    session: String,
+
+    /// Tags to add
+    tags: Vec<String>,
+
+    /// Remove the given tags instead
+    #[arg(long, requires = "tags")]
+    remove: bool,
+}
+
+// ── digest ─────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Write a dated markdown digest of activity since the previous run",
+    long_about = "Summarize new sessions, message counts, changed files, and API errors \
+                  since the previous digest into <out>/<YYYY-MM-DD>.md (runs on the same \
+                  day append). The end of each window is remembered in ~/.smc/digest.json, \
+                  so it is safe to run from cron or a SessionEnd hook; runs narrowed by \
+                  --project, --filter or an explicit --since leave it where it is."
+)]
+struct DigestArgs {
+    /// `last-run` (default; 24h on the first run) or a date (YYYY-MM-DD / RFC 3339)
+    #[arg(long, default_value = "last-run")]
+    since: String,
+
+    /// Directory for the dated markdown files
+    #[arg(long, value_name = "DIR")]
+    out: String,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Report what would be summarized without writing or moving the marker
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+}
+
+// ── bench ──────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(
+    about = "Time cold and warm full-corpus scans: discovery, parse, search, cached summaries",
+    long_about = "Run each stage once cold and --runs times warm over every session file, \
+                  emitting elapsed time, MB/s, and lines/s per stage and pass (warm is the \
+                  median). The summaries stage goes through a scratch cache, so its warm \
+                  passes show what the summary cache saves over a full parse. Cold means \
+                  first in this process; drop the OS page cache beforehand for a cold disk."
+)]
+struct BenchArgs {
+    /// Query for the search stage
+    #[arg(long, short, default_value = "error")]
+    query: String,
+
+    /// Warm passes per stage
+    #[arg(long, default_value = "3")]
+    runs: usize,
+}
+
+// ── watch ──────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "watch")]
+#[derive(Parser)]
+#[command(
+    about = "Stream newly appended messages from every session as they are written",
+    long_about = "Like `tail -f` over all of ~/.claude/projects: filesystem notifications \
+                  trigger a read of whatever each session appended, and every new message \
+                  that passes the search filters is emitted as a search hit. With no query \
+                  every message is streamed. History is skipped; runs until interrupted."
+)]
+struct WatchArgs {
+    /// Only messages containing these terms (OR'd; empty = everything)
+    query: Vec<String>,
+
+    /// Treat query as regex
+    #[arg(long, short = 'e')]
+    regex: bool,
+
+    /// Require ALL terms to match (default is OR)
+    #[arg(long, short = 'a')]
+    and: bool,
+
+    /// Filter by role: user, assistant (asst), system, any-human; comma-separated
+    #[arg(long)]
+    role: Option<String>,
+
+    /// Filter by tool name: exact (any case), `~` prefix for substring; comma-separated
+    #[arg(long)]
+    tool: Option<String>,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Filter by git branch
+    #[arg(long)]
+    branch: Option<String>,
+
+    /// Filter to messages that touch a file path
+    #[arg(long)]
+    file: Option<String>,
+
+    /// Stop after this many messages (0 = run until interrupted)
+    #[arg(long, short = 'n', default_value = "0")]
+    max: usize,
+
+    /// Short for the global --include-smc
+    #[arg(short = 'i')]
+    include_smc_short: bool,
+
+    /// Exclude a specific session ID
+    #[arg(long)]
+    exclude_session: Option<String>,
+
+    /// Match only within tool input content
+    #[arg(long)]
+    tool_input: bool,
+
+    /// Match only within thinking blocks
+    #[arg(long)]
+    thinking: bool,
+
+    /// Exclude thinking blocks
+    #[arg(long)]
+    no_thinking: bool,
+
+    /// Max chars of message text per record (default: [truncate] watch, else 500)
+    #[arg(long, value_name = "N")]
+    truncate: Option<usize>,
+
+    /// Disable truncation entirely
+    #[arg(long)]
+    full: bool,
+
+    /// Compact records: session, line, role, timestamp, 100-char preview
+    #[arg(long)]
+    oneline: bool,
+
+    /// Replace API keys, tokens, and emails in message text with [REDACTED:<kind>]
+    #[arg(long)]
+    redact: bool,
+}
+
+// ── alerts ─────────────────────────────────────────────────────────────────
+
+#[derive(Subcommand)]
+enum AlertsCommand {
+    /// Watch for a keyword (case-insensitive substring unless --regex)
+    Add {
+        /// Keyword or regex
+        pattern: String,
+
+        /// Treat the pattern as a regex
+        #[arg(long, short = 'e')]
+        regex: bool,
+
+        /// Raise a desktop notification when it fires
+        #[arg(long)]
+        notify: bool,
+    },
+
+    /// Stop watching a keyword
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// Pattern exactly as added
+        pattern: String,
+    },
+
+    /// List alert rules
+    #[command(visible_alias = "ls")]
+    List,
+
+    /// Scan content appended since the last check and report matches
+    #[command(long_about = "Reads only lines appended since the previous check (byte cursors \
+                            live in ~/.smc/alerts.json). The first check baselines existing \
+                            history without alerting. Suitable for a Claude Code hook or cron.")]
+    Check {
+        /// Don't raise desktop notifications
+        #[arg(long, short)]
+        quiet: bool,
+    },
+}
+
+// ── backup ─────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "backup")]
+#[derive(Parser)]
+#[command(
+    about = "Snapshot session files and smc state into a .tar.zst archive",
+    long_about = "Archive the selected session JSONL files plus smc's own state (~/.smc, minus \
+                  the rebuildable SQLite mirror). --incremental skips files whose size and \
+                  mtime are unchanged since the previous backup."
+)]
+struct BackupArgs {
+    /// Archive path (.tar.zst)
+    #[arg(long, value_name = "FILE")]
+    out: String,
+
+    /// Filter by project name (substring match)
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Only sessions modified on or after this date (YYYY-MM-DD) or span back from now (48h, 7d)
+    #[arg(long)]
+    after: Option<String>,
+
+    /// Only include files changed since the previous backup
+    #[arg(long)]
+    incremental: bool,
+}
+
+#[cfg(feature = "backup")]
+#[derive(Parser)]
+#[command(
+    about = "Restore sessions and smc state from a backup archive",
+    long_about = "Sessions go back under the Claude projects directory (--path or \
+                  ~/.claude/projects, created if missing) and state under ~/.smc. Existing \
+                  files are left alone unless --force."
+)]
+struct RestoreArgs {
+    /// Archive created by `smc backup`
+    archive: String,
+
+    /// Overwrite files that already exist
+    #[arg(long)]
+    force: bool,
+
+    /// Show what would be restored without writing
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+}
+
+// ── db ─────────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "sqlite")]
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Incrementally mirror all sessions into a normalized SQLite schema
+    #[command(long_about = "Import sessions, messages, tool_calls, and usage tables. Only \
+                            sessions whose file size or mtime changed since the last sync \
+                            are re-imported; sessions whose file is gone are removed.")]
+    Sync {
+        /// Database path (default: ~/.smc/smc.db)
+        #[arg(long, value_name = "FILE")]
+        db: Option<String>,
+    },
+
+    /// Run SQL against the mirror; each row is emitted as a record
+    #[command(long_about = "Run one SQL statement against the mirror and emit each row as a \
+                            record. The database is opened read-only, so statements that \
+                            write fail.")]
+    Query {
+        /// SQL statement
+        sql: String,
+
+        /// Database path (default: ~/.smc/smc.db)
+        #[arg(long, value_name = "FILE")]
+        db: Option<String>,
+    },
+}
+
+// ── main ───────────────────────────────────────────────────────────────────
+
+fn main() {
+    let result = run(Cli::parse());
+
+    match result {
+        Ok(true) => std::process::exit(0),
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Returns Ok(true) for success/matches, Ok(false) for no results.
+fn run(mut cli: Cli) -> anyhow::Result<bool> {
+    // `smc last <command> [args]` is `smc <command> <latest id> [args]`. Its
+    // global flags are taken now, before anything reads them; the id is only
+    // known after discovery.
+    let chain = match &cli.command {
+        Commands::Last(args) if !args.action.is_empty() => Some((args.project.clone(), args.action.clone())),
+        _ => None,
+    };
+    if let Some((_, action)) = &chain {
+        cli.merge_globals(parse_chained(action, "-")?);
+    }
+    let max_tokens = cli.max_tokens.unwrap_or(0);
+
+    let mut em = Emitter::stdout(max_tokens);
+    // Everything in config.toml tunes session-reading commands. A broken file
+    // falls back to defaults with a warning instead of failing them.
+    let config = match needs_sessions(&cli.command).then(Config::load).transpose() {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            let msg = format!("{:#}", e);
+            em.warn(None, &format!("{} — using defaults", msg.trim_end()));
+            Config::default()
+        }
+    };
+    if let Some(n) = cli.threads.or(config.limits.threads) {
+        anyhow::ensure!(n > 0, "--threads must be at least 1");
+        rayon::ThreadPoolBuilder::new().num_threads(n).build_global()?;
+    }
+    let max_file_size = match cli.max_file_size.as_ref().or(config.limits.max_file_size.as_ref()) {
+        Some(s) => Some(discover::parse_size(s)?),
+        None => None,
+    };
+
+    let from_stdin = cli.stdin || cli.path.as_deref() == Some("-");
+    #[cfg(feature = "watch")]
+    anyhow::ensure!(
+        !(from_stdin && matches!(cli.command, Commands::Watch(_))),
+        "smc watch needs a projects directory to watch, not stdin"
+    );
+    anyhow::ensure!(
+        !(from_stdin && matches!(cli.command, Commands::Bench(_))),
+        "smc bench scans a projects directory, not stdin"
+    );
+    let spool;
+    let files = if !needs_sessions(&cli.command) {
+        Vec::new()
+    } else if from_stdin {
+        spool = discover::spool_stdin(std::io::stdin().lock())?;
+        spool.files.clone()
+    } else {
+        let claude_dir = discover::claude_dir(cli.path.as_deref())?;
+        discover::discover_jsonl_files(&claude_dir)?
+    };
+    if let Some((project, action)) = &chain {
+        let file = session::last_active(&files, project.as_deref()).ok_or_else(|| anyhow::anyhow!("no sessions found"))?;
+        cli.command = parse_chained(action, &file.session_id)?.command;
+    }
+    let mut filter = match cli.filter.as_deref() {
+        Some(expr) => Filter::parse(expr)?,
+        None => Filter::default(),
+    };
+    let (name, supported) = filter_support(&cli.command);
+    filter.ensure_supported(name, supported)?;
+    let mut files: Vec<_> = files.into_iter().filter(|f| filter.matches_file(f)).collect();
+
+    // Single-session commands name their file explicitly, so only scans skip.
+    if let (Some(limit), None) = (max_file_size, session_arg(&cli.command)) {
+        let (kept, skipped): (Vec<_>, Vec<_>) = files.into_iter().partition(|f| f.size_bytes <= limit);
+        for f in &skipped {
+            let msg = format!("skipped: {} exceeds --max-file-size", f.size_human());
+            em.warn(Some(&f.path.display().to_string()), &msg);
+        }
+        files = kept;
+    }
+    let time_format = TimeFormat { local: cli.local, relative: cli.relative };
+    let include_smc = cli.include_smc || config.policy.include_smc;
+    let redactor = Redactor::new(&cli.redact_pattern)?;
+    let plugins = if cli.no_plugins { Default::default() } else { config.plugin };
+    let redact_if = |on: bool| on.then(|| redactor.clone());
+    // Spooled stdin files are temporary; caching them would only leave garbage.
+    let cache = (!cli.no_cache && !from_stdin).then(SummaryCache::default_path);
+    let progress: Box<dyn Progress> = match cli.progress.as_deref() {
+        None => Box::new(Silent),
+        Some("text") => Box::<StderrProgress>::default(),
+        Some("json") => Box::new(StderrProgress { json: true, ..Default::default() }),
+        Some(other) => anyhow::bail!("unknown progress format '{}' — use: text, json", other),
+    };
+
+    if cli.strict {
+        let targets = match session_arg(&cli.command) {
+            Some(query) => vec![discover::find_session(&files, query)?],
+            None => files.iter().collect(),
+        };
+        cmd::validate::warn_issues(&targets, &mut em)?;
+    }
+
+    match cli.command {
+        Commands::Search(args) => {
+            let mut queries = args.query;
+            queries.append(&mut filter.terms);
+            let opts = cmd::search::SearchOpts::builder()
+                .queries(queries)
+                .is_regex(args.regex)
+                .stem(args.stem)
+                .and_mode(args.and)
+                .session_all(args.session_all)
+                .synonyms(if args.expand { config.expand.clone() } else { Default::default() })
+                .role(args.role.or(filter.role))
+                .tool(args.tool.or(filter.tool))
+                .project(resolve_project(&files, args.project, &mut em)?)
+                .after(args.after.or(filter.after))
+                .before(args.before.or(filter.before))
+                .branch(args.branch.or(filter.branch))
+                .file(args.file.or(filter.file))
+                .tool_input(args.tool_input)
+                .thinking_only(args.thinking)
+                .no_thinking(args.no_thinking)
+                .max_results(args.max)
+                .include_smc(include_smc || args.include_smc_short)
+                .only_smc(args.only_smc)
+                .exclude_session(args.exclude_session)
+                .truncate(truncation(args.full, args.truncate, config.truncate.search, 500))
+                .oneline(args.oneline)
+                .snippet_chars(args.snippet_chars.unwrap_or(0))
+                .all_occurrences(args.all_occurrences)
+                .rich(args.json_rich)
+                .time_format(time_format)
+                .links(cli.links)
+                .redact(redact_if(args.redact))
+                .copy(args.copy)
+                .no_tag(args.no_tag)
+                .md(args.md.map(Into::into))
+                .md_append(args.md_append)
+                .md_split(args.md_split.map(Into::into))
+                .html(args.html.map(Into::into))
+                .md_context(args.md_context)
+                .post_filter(plugins.post_search.clone())
+                .max_tokens(max_tokens)
+                .build();
+            cmd::search::run_with_progress(&opts, &files, &mut em, progress.as_ref())?;
+        }
 
         Commands::Sessions(args) => {
-            let opts = cmd::sessions::SessionsOpts {
-                limit: args.limit,
-                project: args.project,
-                after: args.after,
-                before: args.before,
-            };
-            let mut em = Emitter::stdout(max_tokens);
+            let mut opts = cmd::sessions::SessionsOpts::default();
+            opts.limit = args.limit;
+            opts.project = resolve_project(&files, args.project, &mut em)?;
+            opts.after = args.after.or(filter.after);
+            opts.before = args.before.or(filter.before);
+            opts.time_format = time_format;
+            opts.links = cli.links;
+            opts.cache = cache.clone();
+            opts.preview = cmd::sessions::PreviewMode::parse(&args.preview)?;
             cmd::sessions::run(&opts, &files, &mut em)?;
         }
 
         Commands::Show(args) => {
             let file = discover::find_session(&files, &args.session)?;
-            let opts = cmd::show::ShowOpts {
-                session: args.session,
-                thinking: args.thinking,
-                from: args.from,
-                to: args.to,
-                max_tokens,
-            };
-            let mut em = Emitter::stdout(max_tokens);
+            let mut opts = cmd::show::ShowOpts::default();
+            opts.session = args.session;
+            opts.thinking = cmd::show::ThinkingMode::parse(args.thinking.as_deref().unwrap_or("omit"))?;
+            opts.from = args.from;
+            opts.to = args.to;
+            opts.truncate = truncation(args.full, args.truncate, config.truncate.show, 200);
+            opts.oneline = args.oneline;
+            opts.role = args.role.or(filter.role);
+            opts.redact = redact_if(args.redact);
+            opts.hide_sidechains = args.no_sidechains;
+            opts.include = args.include.iter().map(|k| cmd::show::ExtraKind::parse(k)).collect::<Result<_, _>>()?;
+            opts.max_tokens = max_tokens;
             cmd::show::run(&opts, file, &mut em)?;
         }
 
+        Commands::Last(args) => {
+            let file = session::last_active(&files, args.project.as_deref())
+                .ok_or_else(|| anyhow::anyhow!("no sessions found"))?;
+            let mut opts = cmd::sessions::SessionsOpts::default();
+            opts.limit = 1;
+            opts.time_format = time_format;
+            opts.links = cli.links;
+            opts.cache = cache.clone();
+            opts.preview = cmd::sessions::PreviewMode::Last;
+            cmd::sessions::run(&opts, std::slice::from_ref(file), &mut em)?;
+        }
+
+        Commands::Tokens(args) => {
+            let file = discover::find_session(&files, &args.session)?;
+            let mut opts = cmd::tokens::TokensOpts::default();
+            opts.session = args.session;
+            opts.max_tokens = max_tokens;
+            cmd::tokens::run(&opts, file, &mut em)?;
+        }
+
+        Commands::Thread(ThreadArgs { show: args }) => {
+            let file = discover::find_session(&files, &args.session)?;
+            let mut opts = cmd::thread::ThreadOpts::default();
+            opts.show.session = args.session;
+            opts.show.thinking = cmd::show::ThinkingMode::parse(args.thinking.as_deref().unwrap_or("omit"))?;
+            opts.show.from = args.from;
+            opts.show.to = args.to;
+            opts.show.truncate = truncation(args.full, args.truncate, config.truncate.show, 200);
+            opts.show.oneline = args.oneline;
+            opts.show.role = args.role.or(filter.role);
+            opts.show.redact = redact_if(args.redact);
+            opts.show.hide_sidechains = args.no_sidechains;
+            opts.show.include = args.include.iter().map(|k| cmd::show::ExtraKind::parse(k)).collect::<Result<_, _>>()?;
+            opts.show.max_tokens = max_tokens;
+            cmd::thread::run(&opts, &files, file, &mut em)?;
+        }
+
         Commands::Tools(args) => {
             let file = discover::find_session(&files, &args.session)?;
-            let opts = cmd::tools::ToolsOpts {
-                session: args.session,
-                max_tokens,
-            };
-            let mut em = Emitter::stdout(max_tokens);
+            let mut opts = cmd::tools::ToolsOpts::default();
+            opts.session = args.session;
+            opts.tool = args.tool.or(filter.tool);
+            opts.max_tokens = max_tokens;
             cmd::tools::run(&opts, file, &mut em)?;
         }
 
-        Commands::Stats => {
-            let opts = cmd::stats::StatsOpts { max_tokens };
-            let mut em = Emitter::stdout(max_tokens);
+        Commands::ToolsSchema(args) => {
+            let mut opts = cmd::tools_schema::ToolsSchemaOpts::default();
+            opts.tool = args.tool.or(filter.tool);
+            opts.max_tokens = max_tokens;
+            cmd::tools_schema::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Stats(args) => {
+            let mut opts = cmd::stats::StatsOpts::default();
+            opts.project = args.project;
+            opts.max_tokens = max_tokens;
             cmd::stats::run(&opts, &files, &mut em)?;
         }
 
         Commands::Export(args) => {
             let file = discover::find_session(&files, &args.session)?;
-            let opts = cmd::export::ExportOpts {
-                session: args.session,
-                to_stdout: args.output,
-                md_path: args.md,
+            let mut opts = cmd::export::ExportOpts::default();
+            opts.session = args.session;
+            opts.to_stdout = args.output;
+            opts.md_path = args.md;
+            opts.redact = redact_if(!args.no_redact);
+            opts.copy = args.copy;
+            opts.no_tag = args.no_tag;
+            opts.post_filter = plugins.post_export.clone();
+            opts.front_matter = args.front_matter || config.export.front_matter;
+            opts.stats_footer = args.stats_footer || config.export.stats_footer;
+            opts.template = match args.template {
+                Some(t) => Some(t.into()),
+                None => config.export.template.as_ref().map(|t| state::smc_home_path().join(t)),
             };
-            let mut em = Emitter::stdout(max_tokens);
+            opts.tags = config.export.tags.iter().cloned().chain(args.tag).collect();
+            opts.thinking = cmd::show::ThinkingMode::parse(&args.thinking)?;
+            opts.wrap = args.wrap;
+            opts.tool_results.limit = args.tool_result_limit;
+            opts.tool_results.files = args.tool_result_files.map(Into::into);
             cmd::export::run(&opts, file, &mut em)?;
         }
 
         Commands::Context(args) => {
             let file = discover::find_session(&files, &args.session)?;
-            let opts = cmd::context::ContextOpts {
-                session: args.session,
-                line: args.line,
-                context: args.context,
-                max_tokens,
-            };
-            let mut em = Emitter::stdout(max_tokens);
+            let mut opts = cmd::context::ContextOpts::default();
+            opts.session = args.session;
+            opts.line = args.line;
+            opts.context = args.context;
+            opts.truncate = truncation(args.full, args.truncate, config.truncate.context, 500);
+            opts.max_tokens = max_tokens;
             cmd::context::run(&opts, file, &mut em)?;
         }
 
-        Commands::Projects => {
-            let opts = cmd::projects::ProjectsOpts { max_tokens };
-            let mut em = Emitter::stdout(max_tokens);
+        Commands::Msg(args) => {
+            let (session, line) = cmd::msg::parse_target(&args.target)?;
+            let file = discover::find_session(&files, &session)?;
+            let mut opts = cmd::msg::MsgOpts::default();
+            opts.session = session;
+            opts.line = line;
+            opts.json = args.json;
+            opts.field = args.field.as_deref().map(cmd::msg::MsgField::parse).transpose()?;
+            opts.max_tokens = max_tokens;
+            cmd::msg::run(&opts, file, &mut em)?;
+        }
+
+        Commands::Split(args) => {
+            let file = discover::find_session(&files, &args.session)?;
+            let mut opts = cmd::split::SplitOpts::default();
+            opts.session = args.session;
+            opts.at = cmd::split::SplitAt::parse(&args.at)?;
+            opts.out = args.out.into();
+            opts.force = args.force;
+            opts.max_tokens = max_tokens;
+            cmd::split::run(&opts, file, &mut em)?;
+        }
+
+        Commands::Projects(args) => {
+            let mut opts = cmd::projects::ProjectsOpts::default();
+            opts.matches = args.matches;
+            opts.cache = cache.clone();
+            opts.max_tokens = max_tokens;
             cmd::projects::run(&opts, &files, &mut em)?;
         }
 
+        Commands::Growth(args) => {
+            let mut opts = cmd::growth::GrowthOpts::default();
+            opts.interval = cmd::growth::Interval::parse(&args.interval)?;
+            opts.cache = cache.clone();
+            opts.max_tokens = max_tokens;
+            cmd::growth::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Hours(args) => {
+            let mut opts = cmd::hours::HoursOpts::default();
+            opts.project = args.project;
+            opts.after = args.after.or(filter.after);
+            opts.before = args.before.or(filter.before);
+            opts.utc = args.utc;
+            opts.max_tokens = max_tokens;
+            cmd::hours::run(&opts, &files, &mut em)?;
+        }
+
         Commands::Freq(args) => {
             let mode = cmd::freq::FreqMode::parse(&args.mode)?;
-            let opts = cmd::freq::FreqOpts {
-                mode,
-                limit: args.limit,
-                raw: args.raw,
-                max_tokens,
-            };
-            let mut em = Emitter::stdout(max_tokens);
-            cmd::freq::run(&opts, &files, &mut em)?;
+            let mut opts = cmd::freq::FreqOpts::default();
+            opts.mode = mode;
+            opts.class = cmd::freq::CharClass::parse(&args.class)?;
+            opts.limit = args.limit;
+            opts.raw = args.raw;
+            opts.cache = cache.clone();
+            opts.tool = args.tool.or(filter.tool);
+            opts.project = args.project;
+            opts.role = args.role.or(filter.role);
+            opts.compare = args.compare;
+            opts.include_smc = include_smc;
+            opts.max_tokens = max_tokens;
+            cmd::freq::run_with_progress(&opts, &files, &mut em, progress.as_ref())?;
         }
 
         Commands::Recent(args) => {
-            let opts = cmd::recent::RecentOpts {
-                limit: args.limit,
-                role: args.role,
-                project: args.project,
-                max_tokens,
-            };
-            let mut em = Emitter::stdout(max_tokens);
+            let mut opts = cmd::recent::RecentOpts::default();
+            opts.limit = args.limit;
+            opts.after = args.after.or(filter.after);
+            opts.before = args.before.or(filter.before);
+            opts.role = args.role.or(filter.role);
+            opts.project = args.project;
+            opts.time_format = time_format;
+            opts.links = cli.links;
+            opts.include_smc = include_smc;
+            opts.max_tokens = max_tokens;
             cmd::recent::run(&opts, &files, &mut em)?;
         }
+
+        Commands::Validate(args) => {
+            let mut opts = cmd::validate::ValidateOpts::default();
+            opts.project = args.project;
+            opts.max_tokens = max_tokens;
+            cmd::validate::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Unknown(args) => {
+            let mut opts = cmd::unknown::UnknownOpts::default();
+            opts.project = args.project;
+            opts.samples = args.limit;
+            opts.max_tokens = max_tokens;
+            cmd::unknown::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Errors(args) => {
+            let mut opts = cmd::errors::ErrorsOpts::default();
+            opts.limit = args.limit;
+            opts.project = args.project;
+            opts.after = args.after.or(filter.after);
+            opts.before = args.before.or(filter.before);
+            opts.max_tokens = max_tokens;
+            cmd::errors::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Results(args) => {
+            let mut queries = args.query;
+            queries.append(&mut filter.terms);
+            let mut opts = cmd::results::ResultsOpts::default();
+            opts.queries = queries;
+            opts.is_regex = args.regex;
+            opts.and_mode = args.and;
+            opts.tool = args.tool.or(filter.tool);
+            opts.project = args.project.or(filter.project);
+            opts.after = args.after.or(filter.after);
+            opts.before = args.before.or(filter.before);
+            opts.limit = args.limit;
+            opts.context = args.context;
+            opts.max_tokens = max_tokens;
+            cmd::results::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Outcomes(args) => {
+            let mut opts = cmd::outcomes::OutcomesOpts::default();
+            opts.project = args.project;
+            opts.sessions = args.sessions;
+            opts.max_tokens = max_tokens;
+            cmd::outcomes::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Doctor(args) => {
+            let mut opts = cmd::doctor::DoctorOpts::default();
+            opts.project = args.project;
+            opts.verbose = args.verbose;
+            opts.max_tokens = max_tokens;
+            cmd::doctor::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Digest(args) => {
+            let mut opts = cmd::digest::DigestOpts::default();
+            opts.since = args.since;
+            opts.out = args.out.into();
+            opts.project = args.project;
+            opts.narrowed = cli.filter.is_some() || max_file_size.is_some() || from_stdin;
+            opts.dry_run = args.dry_run;
+            cmd::digest::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Bench(args) => {
+            let mut opts = cmd::bench::BenchOpts::default();
+            opts.dir = discover::claude_dir(cli.path.as_deref())?;
+            opts.query = args.query;
+            opts.runs = args.runs;
+            cmd::bench::run(&opts, &mut em)?;
+        }
+
+        Commands::SyncObsidian(args) => {
+            let mut opts = cmd::obsidian::ObsidianOpts::default();
+            opts.vault = args.vault.into();
+            opts.project = resolve_project(&files, args.project, &mut em)?;
+            opts.redact = redact_if(!args.no_redact);
+            opts.tags = config.export.tags.iter().cloned().chain(args.tag).collect();
+            opts.max_tokens = max_tokens;
+            cmd::obsidian::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Site(args) => {
+            let mut opts = cmd::site::SiteOpts::default();
+            opts.out_dir = args.out;
+            opts.project = resolve_project(&files, args.project, &mut em)?;
+            opts.redact = redact_if(!args.no_redact);
+            opts.max_tokens = max_tokens;
+            cmd::site::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Dataset(args) => {
+            let mut opts = cmd::dataset::DatasetOpts::default();
+            opts.format = cmd::dataset::DatasetFormat::parse(&args.format)?;
+            opts.out = args.out;
+            opts.project = resolve_project(&files, args.project, &mut em)?;
+            opts.after = args.after.or(filter.after);
+            opts.before = args.before.or(filter.before);
+            opts.min_turns = args.min_turns;
+            opts.inline_tools = args.inline_tools;
+            opts.system = args.system;
+            opts.redact = redact_if(!args.no_redact);
+            opts.max_tokens = max_tokens;
+            cmd::dataset::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Blame(args) => {
+            let mut opts = cmd::blame::BlameOpts::default();
+            opts.path = args.file;
+            opts.project = args.project;
+            opts.changes = args.changes;
+            opts.max_tokens = max_tokens;
+            cmd::blame::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Resume(args) => {
+            let mut opts = cmd::resume::ResumeOpts::default();
+            opts.query = args.query;
+            opts.dry_run = args.dry_run;
+            opts.claude_bin = args.claude;
+            cmd::resume::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Tag(args) => {
+            let file = discover::find_session(&files, &args.session)?;
+            let mut opts = cmd::tag::TagOpts::default();
+            opts.tags = args.tags;
+            opts.remove = args.remove;
+            cmd::tag::run(&opts, file, &mut em)?;
+        }
+
+        #[cfg(feature = "watch")]
+        Commands::Watch(args) => {
+            let mut queries = args.query;
+            queries.append(&mut filter.terms);
+            let mut opts = cmd::watch::WatchOpts::default();
+            opts.search = cmd::search::SearchOpts::builder()
+                .queries(queries)
+                .is_regex(args.regex)
+                .and_mode(args.and)
+                .role(args.role.or(filter.role))
+                .tool(args.tool.or(filter.tool))
+                .project(args.project.or(filter.project))
+                .branch(args.branch.or(filter.branch))
+                .file(args.file.or(filter.file))
+                .tool_input(args.tool_input)
+                .thinking_only(args.thinking)
+                .no_thinking(args.no_thinking)
+                .max_results(args.max)
+                .include_smc(include_smc || args.include_smc_short)
+                .exclude_session(args.exclude_session)
+                .truncate(truncation(args.full, args.truncate, config.truncate.watch, 500))
+                .oneline(args.oneline)
+                .time_format(time_format)
+                .links(cli.links)
+                .redact(redact_if(args.redact))
+                .max_tokens(max_tokens)
+                .build();
+            opts.dir = discover::claude_dir(cli.path.as_deref())?;
+            opts.session = filter.session;
+            cmd::watch::run(&opts, &files, &mut em)?;
+        }
+
+        Commands::Alerts(AlertsCommand::Add { pattern, regex, notify }) => {
+            let mut opts = cmd::alerts::AlertAddOpts::default();
+            opts.pattern = pattern;
+            opts.regex = regex;
+            opts.notify = notify;
+            cmd::alerts::add(&opts, &mut em)?;
+        }
+
+        Commands::Alerts(AlertsCommand::Remove { pattern }) => {
+            let mut opts = cmd::alerts::AlertRemoveOpts::default();
+            opts.pattern = pattern;
+            cmd::alerts::remove(&opts, &mut em)?;
+        }
+
+        Commands::Alerts(AlertsCommand::List) => cmd::alerts::list(&mut em)?,
+
+        Commands::Alerts(AlertsCommand::Check { quiet }) => {
+            let mut opts = cmd::alerts::AlertCheckOpts::default();
+            opts.quiet = quiet;
+            opts.max_tokens = max_tokens;
+            cmd::alerts::check(&opts, &files, &mut em)?;
+        }
+
+        #[cfg(feature = "backup")]
+        Commands::Backup(args) => {
+            let mut opts = cmd::backup::BackupOpts::default();
+            opts.out = args.out;
+            opts.project = args.project;
+            opts.after = args.after.or(filter.after);
+            opts.incremental = args.incremental;
+            opts.max_tokens = max_tokens;
+            cmd::backup::backup(&opts, &files, &mut em)?;
+        }
+
+        #[cfg(feature = "backup")]
+        Commands::Restore(args) => {
+            let mut opts = cmd::backup::RestoreOpts::default();
+            opts.archive = args.archive;
+            opts.projects_dir = discover::claude_dir_path(cli.path.as_deref());
+            opts.force = args.force;
+            opts.dry_run = args.dry_run;
+            opts.max_tokens = max_tokens;
+            cmd::backup::restore(&opts, &mut em)?;
+        }
+
+        #[cfg(feature = "sqlite")]
+        Commands::Db(DbCommand::Sync { db }) => {
+            let mut opts = cmd::db::DbSyncOpts::default();
+            opts.db = db;
+            opts.max_tokens = max_tokens;
+            cmd::db::sync(&opts, &files, &mut em)?;
+        }
+
+        #[cfg(feature = "sqlite")]
+        Commands::Db(DbCommand::Query { sql, db }) => {
+            let mut opts = cmd::db::DbQueryOpts::default();
+            opts.db = db;
+            opts.sql = sql;
+            opts.max_tokens = max_tokens;
+            cmd::db::query(&opts, &mut em)?;
+        }
     }
 
     Ok(true)
 }
+
+/// Subcommand name and the non-file-level `--filter` keys it understands.
+fn filter_support(command: &Commands) -> (&'static str, &'static [&'static str]) {
+    match command {
+        Commands::Search(_) => ("smc search", &["role", "tool", "branch", "file", "after", "before", "terms"]),
+        #[cfg(feature = "watch")]
+        Commands::Watch(_) => ("smc watch", &["role", "tool", "branch", "file", "terms"]),
+        Commands::Sessions(_) => ("smc sessions", &["after", "before"]),
+        Commands::Recent(_) => ("smc recent", &["role", "after", "before"]),
+        Commands::Show(_) => ("smc show", &["role"]),
+        Commands::Tools(_) => ("smc tools", &["tool"]),
+        Commands::ToolsSchema(_) => ("smc tools-schema", &["tool"]),
+        Commands::Freq(_) => ("smc freq", &["tool", "role"]),
+        Commands::Thread(_) => ("smc thread", &["role"]),
+        Commands::Errors(_) => ("smc errors", &["after", "before"]),
+        Commands::Results(_) => ("smc results", &["tool", "after", "before", "terms"]),
+        Commands::Hours(_) => ("smc hours", &["after", "before"]),
+        Commands::Dataset(_) => ("smc dataset", &["after", "before"]),
+        #[cfg(feature = "backup")]
+        Commands::Backup(_) => ("smc backup", &["after"]),
+        _ => ("this subcommand", &[]),
+    }
+}
+
+/// The session query for single-session subcommands.
+/// `--project` as typed when some project name contains it, else the one
+/// project it is a typo of, with a warning. See [`fuzzy::resolve_project`].
+fn resolve_project<W: Write>(
+    files: &[discover::SessionFile],
+    project: Option<String>,
+    em: &mut Emitter<W>,
+) -> anyhow::Result<Option<String>> {
+    let Some(query) = project else { return Ok(None) };
+    match fuzzy::resolve_project(files.iter().map(|f| f.project_name.as_str()), &query)? {
+        None => Ok(Some(query)),
+        Some(name) => {
+            em.warn(None, &format!("no project matches '{}' — using '{}'", query, name));
+            Ok(Some(name))
+        }
+    }
+}
+
+/// Whether `command` reads sessions. The others run without a projects
+/// directory, which `restore` may be about to create.
+fn needs_sessions(command: &Commands) -> bool {
+    match command {
+        Commands::Alerts(AlertsCommand::Add { .. } | AlertsCommand::Remove { .. } | AlertsCommand::List) => false,
+        #[cfg(feature = "sqlite")]
+        Commands::Db(DbCommand::Query { .. }) => false,
+        #[cfg(feature = "backup")]
+        Commands::Restore(_) => false,
+        // Discovers its own files, once per run.
+        Commands::Bench(_) => false,
+        _ => true,
+    }
+}
+
+/// `--full`, else `--truncate`, else the `[truncate]` config, else `default`.
+fn truncation(full: bool, flag: Option<usize>, config: Option<usize>, default: usize) -> usize {
+    if full {
+        0
+    } else {
+        flag.or(config).unwrap_or(default)
+    }
+}
+
+fn session_arg(command: &Commands) -> Option<&str> {
+    match command {
+        Commands::Show(a) => Some(&a.session),
+        Commands::Thread(a) => Some(&a.show.session),
+        Commands::Tools(a) => Some(&a.session),
+        Commands::Tokens(a) => Some(&a.session),
+        Commands::Export(a) => Some(&a.session),
+        Commands::Context(a) => Some(&a.session),
+        Commands::Msg(a) => Some(a.target.rsplit_once(':').map_or(a.target.as_str(), |(s, _)| s)),
+        Commands::Split(a) => Some(&a.session),
+        Commands::Tag(a) => Some(&a.session),
+        _ => None,
+    }
+}
+
+// ── Progress ───────────────────────────────────────────────────────────────
+
+/// `--progress`: a self-overwriting "files, bytes, MB/s, ETA" line on
+/// stderr, or with `--progress=json` one `progress` event per line for
+/// wrappers to draw their own UI. Redrawn at most every 100ms; stdout stays
+/// pure JSONL.
+#[derive(Default)]
+struct StderrProgress {
+    json: bool,
+    total: AtomicUsize,
+    done: AtomicUsize,
+    bytes_total: AtomicU64,
+    bytes_done: AtomicU64,
+    started: Mutex<Option<Instant>>,
+    last_draw: Mutex<Option<Instant>>,
+}
+
+impl StderrProgress {
+    fn draw(&self, event: &str, force: bool) {
+        let mut last = self.last_draw.lock().unwrap();
+        if !force && last.is_some_and(|t| t.elapsed() < Duration::from_millis(100)) {
+            return;
+        }
+        *last = Some(Instant::now());
+        let (done, total) = (self.done.load(Ordering::Relaxed), self.total.load(Ordering::Relaxed));
+        let (bytes, bytes_total) = (self.bytes_done.load(Ordering::Relaxed), self.bytes_total.load(Ordering::Relaxed));
+        let elapsed = self.started.lock().unwrap().map_or(Duration::ZERO, |t| t.elapsed());
+        let rate = if elapsed.as_secs_f64() > 0.0 { bytes as f64 / elapsed.as_secs_f64() } else { 0.0 };
+        let eta = (rate > 0.0 && bytes > 0)
+            .then(|| Duration::from_secs_f64(bytes_total.saturating_sub(bytes) as f64 / rate));
+
+        let mut err = std::io::stderr();
+        if self.json {
+            let event = serde_json::json!({
+                "type": "progress",
+                "event": event,
+                "files_done": done,
+                "files_total": total,
+                "bytes_done": bytes,
+                "bytes_total": bytes_total,
+                "bytes_per_sec": rate.round() as u64,
+                "elapsed_ms": elapsed.as_millis() as u64,
+                "eta_ms": eta.map(|d| d.as_millis() as u64),
+            });
+            let _ = writeln!(err, "{}", event);
+            return;
+        }
+        let mut line = format!(
+            "smc: {}/{} files, {}/{}, {:.1}MB/s",
+            done,
+            total,
+            cmd::stats::format_bytes(bytes),
+            cmd::stats::format_bytes(bytes_total),
+            rate / (1024.0 * 1024.0)
+        );
+        if let Some(eta) = eta.filter(|_| event != "finish") {
+            line.push_str(&format!(", ETA {}", clock(eta)));
+        }
+        // Clear to end of line: the status can get shorter.
+        let _ = write!(err, "\r{}\x1b[K", line);
+    }
+}
+
+impl Progress for StderrProgress {
+    fn start(&self, files: usize, bytes: u64) {
+        self.total.store(files, Ordering::Relaxed);
+        self.bytes_total.store(bytes, Ordering::Relaxed);
+        *self.started.lock().unwrap() = Some(Instant::now());
+        self.draw("start", true);
+    }
+
+    fn inc(&self, bytes: u64) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        self.bytes_done.fetch_add(bytes, Ordering::Relaxed);
+        self.draw("progress", false);
+    }
+
+    fn finish(&self) {
+        self.draw("finish", true);
+        if !self.json {
+            let _ = writeln!(std::io::stderr());
+        }
+    }
+}
+
+/// `M:SS`, or `H:MM:SS` from an hour up.
+fn clock(d: Duration) -> String {
+    let secs = d.as_secs();
+    match secs / 3600 {
+        0 => format!("{}:{:02}", secs / 60, secs % 60),
+        h => format!("{}:{:02}:{:02}", h, secs / 60 % 60, secs % 60),
+    }
+}
@@ -0,0 +1,397 @@
+/// smc alerts — watch fresh transcript content for keywords.
+///
+/// `check` is incremental: each session keeps a byte cursor in
+/// `~/.smc/alerts.json`, so only lines appended since the last run are read.
+/// It is cheap enough to run from a Claude Code hook or cron.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{RecordRef, Timestamp};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::tail::{self, Cursor};
+use crate::util::{policy, state, text};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct AlertAddOpts {
+    pub pattern: String,
+    /// Treat the pattern as a regex (default: case-insensitive substring).
+    pub regex: bool,
+    /// Raise a desktop notification when it fires.
+    pub notify: bool,
+}
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct AlertRemoveOpts {
+    pub pattern: String,
+}
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct AlertCheckOpts {
+    /// Suppress desktop notifications even for rules with `notify`.
+    pub quiet: bool,
+    pub max_tokens: usize,
+}
+
+const STATE_FILE: &str = "alerts.json";
+
+// ── State ──────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Rule {
+    pattern: String,
+    #[serde(default)]
+    regex: bool,
+    #[serde(default)]
+    notify: bool,
+}
+
+impl Rule {
+    fn compile(&self) -> Result<Regex> {
+        let re = if self.regex {
+            self.pattern.clone()
+        } else {
+            format!("(?i){}", regex::escape(&self.pattern))
+        };
+        Regex::new(&re).with_context(|| format!("invalid alert pattern '{}'", self.pattern))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct AlertState {
+    #[serde(default)]
+    rules: Vec<Rule>,
+    /// Unix seconds of the last `check`; `None` until the first run.
+    #[serde(default)]
+    last_check: Option<u64>,
+    #[serde(default)]
+    cursors: BTreeMap<String, Cursor>,
+}
+
+impl AlertState {
+    fn path() -> Result<PathBuf> {
+        Ok(state::smc_home()?.join(STATE_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).with_context(|| format!("cannot parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        // Write-then-rename: hooks and cron may run `check` concurrently.
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("cannot write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("cannot write {}", path.display()))
+    }
+
+    /// Read what was appended to `files` since their cursors and advance
+    /// them. Cursors of files outside `files` are kept, so a run over a
+    /// subset doesn't replay the others next time; those of deleted files
+    /// are dropped. Returns the hits, oldest first, and the bytes read.
+    fn advance(&mut self, files: &[SessionFile]) -> Result<(Vec<AlertHit>, u64)> {
+        let rules: Vec<(&Rule, Regex)> = self
+            .rules
+            .iter()
+            .map(|r| Ok((r, r.compile()?)))
+            .collect::<Result<_>>()?;
+        let first_run = self.last_check.is_none();
+
+        let scanned: Vec<(String, Cursor, Vec<AlertHit>, u64)> = files
+            .par_iter()
+            .map(|file| {
+                let key = file.path.display().to_string();
+                let mut cursor = match self.cursors.get(&key) {
+                    Some(c) => *c,
+                    // First run ever: baseline everything instead of alerting on history.
+                    None if first_run => Cursor::at_end(&file.path),
+                    None => Cursor::default(),
+                };
+                let mut hits = Vec::new();
+                let bytes = tail::read_appended(&file.path, &mut cursor, |line, raw| {
+                    scan_line(file, line, raw, &rules, &mut hits)
+                })
+                .unwrap_or(0);
+                (key, cursor, hits, bytes)
+            })
+            .collect();
+
+        let mut bytes_scanned = 0u64;
+        let mut hits_out = Vec::new();
+        let mut cursors = BTreeMap::new();
+        for (key, cursor, hits, bytes) in scanned {
+            bytes_scanned += bytes;
+            cursors.insert(key, cursor);
+            hits_out.extend(hits);
+        }
+        hits_out.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        self.cursors.retain(|path, _| std::path::Path::new(path).exists());
+        self.cursors.extend(cursors);
+        self.last_check = Some(now_secs());
+        Ok((hits_out, bytes_scanned))
+    }
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct RuleRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    #[serde(flatten)]
+    rule: &'a Rule,
+}
+
+#[derive(Serialize, Debug)]
+struct RuleRemoved<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    pattern: &'a str,
+}
+
+#[derive(Serialize, Debug)]
+struct AlertHit {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    pattern: String,
+    project: String,
+    session_id: String,
+    line: usize,
+    role: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    text: String,
+}
+
+#[derive(Serialize, Debug)]
+struct CheckSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    matches: usize,
+    files_scanned: usize,
+    bytes_scanned: u64,
+    first_run: bool,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn add<W: Write>(opts: &AlertAddOpts, em: &mut Emitter<W>) -> Result<()> {
+    let rule = Rule { pattern: opts.pattern.clone(), regex: opts.regex, notify: opts.notify };
+    rule.compile()?;
+
+    let mut st = AlertState::load()?;
+    st.rules.retain(|r| r.pattern != rule.pattern);
+    st.rules.push(rule.clone());
+    st.save()?;
+
+    em.emit(&RuleRecord { record_type: "alert_rule", rule: &rule })?;
+    em.flush()?;
+    Ok(())
+}
+
+pub fn remove<W: Write>(opts: &AlertRemoveOpts, em: &mut Emitter<W>) -> Result<()> {
+    let mut st = AlertState::load()?;
+    let before = st.rules.len();
+    st.rules.retain(|r| r.pattern != opts.pattern);
+    if st.rules.len() == before {
+        anyhow::bail!("no alert rule '{}'", opts.pattern);
+    }
+    st.save()?;
+
+    em.emit(&RuleRemoved { record_type: "alert_removed", pattern: &opts.pattern })?;
+    em.flush()?;
+    Ok(())
+}
+
+pub fn list<W: Write>(em: &mut Emitter<W>) -> Result<()> {
+    let st = AlertState::load()?;
+    for rule in &st.rules {
+        if !em.emit(&RuleRecord { record_type: "alert_rule", rule })? {
+            break;
+        }
+    }
+    em.flush()?;
+    Ok(())
+}
+
+pub fn check<W: Write>(opts: &AlertCheckOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let mut st = AlertState::load()?;
+    let first_run = st.last_check.is_none();
+    let (hits_out, bytes_scanned) = st.advance(files)?;
+    let matches = hits_out.len();
+
+    let mut fired: BTreeMap<&str, usize> = BTreeMap::new();
+    for hit in &hits_out {
+        if let Some(rule) = st.rules.iter().find(|r| r.pattern == hit.pattern) {
+            if rule.notify {
+                *fired.entry(rule.pattern.as_str()).or_default() += 1;
+            }
+        }
+    }
+    if !opts.quiet {
+        for (pattern, count) in &fired {
+            if let Err(e) = notify(pattern, *count) {
+                em.warn(None, &format!("notification failed: {:#}", e));
+            }
+        }
+    }
+
+    for hit in &hits_out {
+        if !em.emit(hit)? {
+            break;
+        }
+    }
+
+    st.save()?;
+
+    em.emit(&CheckSummary {
+        record_type: "summary",
+        matches,
+        files_scanned: files.len(),
+        bytes_scanned,
+        first_run,
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn scan_line(file: &SessionFile, line: usize, raw: &str, rules: &[(&Rule, Regex)], hits: &mut Vec<AlertHit>) {
+    if rules.is_empty() {
+        return;
+    }
+    let Ok(rec) = serde_json::from_str::<RecordRef>(raw) else { return };
+    let Some(role) = rec.role() else { return };
+    let content = rec.full_content();
+    if policy::is_smc_output(&content) {
+        return;
+    }
+    for (rule, re) in rules {
+        let Some(m) = re.find(&content) else { continue };
+        hits.push(AlertHit {
+            record_type: "alert",
+            pattern: rule.pattern.clone(),
+            project: file.project_name.clone(),
+            session_id: file.session_id.clone(),
+            line,
+            role,
+            timestamp: rec.timestamp.as_deref().map(Timestamp::new),
+            text: text::oneline(snippet(&content, m.start()), 200),
+        });
+    }
+}
+
+/// Text starting a little before `at`, on a char boundary.
+fn snippet(s: &str, at: usize) -> &str {
+    let mut start = at.saturating_sub(60);
+    while !s.is_char_boundary(start) {
+        start -= 1;
+    }
+    &s[start..]
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Best-effort desktop notification via the platform's stock tool.
+fn notify(pattern: &str, count: usize) -> Result<()> {
+    let title = "smc alert";
+    let body = format!("'{}' matched {} new message{}", pattern, count, if count == 1 { "" } else { "s" });
+    let status = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_quote(&body),
+            applescript_quote(title)
+        );
+        std::process::Command::new("osascript").args(["-e", &script]).status()
+    } else {
+        std::process::Command::new("notify-send").args([title, &body]).status()
+    }
+    .context("cannot run notifier")?;
+    anyhow::ensure!(status.success(), "notifier exited with {}", status);
+    Ok(())
+}
+
+fn applescript_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn plain_rules_are_case_insensitive_literals() {
+        let rule = Rule { pattern: "panicked at (x)".into(), regex: false, notify: false };
+        let re = rule.compile().unwrap();
+        assert!(re.is_match("thread 'main' PANICKED AT (x)"));
+        assert!(!re.is_match("panicked at x"));
+    }
+
+    #[test]
+    fn regex_rules_compile_or_error() {
+        assert!(Rule { pattern: "err(or)?".into(), regex: true, notify: false }.compile().is_ok());
+        assert!(Rule { pattern: "(".into(), regex: true, notify: false }.compile().is_err());
+    }
+
+    #[test]
+    fn snippet_respects_char_boundaries() {
+        let s = format!("{}é tail", "a".repeat(59));
+        assert!(snippet(&s, s.find("tail").unwrap()).ends_with("tail"));
+    }
+
+    #[test]
+    fn subset_runs_keep_other_cursors() {
+        let dir = TempDir::new("alerts");
+        let msg = |text: &str| format!(r#"{{"type":"user","message":{{"role":"user","content":"{}"}}}}"#, text) + "\n";
+        let files = [dir.session("a", "p", &msg("old boom")), dir.session("b", "p", &msg("old boom"))];
+        let append = |f: &SessionFile, text: &str| {
+            let mut out = std::fs::OpenOptions::new().append(true).open(&f.path).unwrap();
+            out.write_all(msg(text).as_bytes()).unwrap();
+        };
+        let mut st = AlertState { rules: vec![Rule { pattern: "boom".into(), regex: false, notify: false }], ..Default::default() };
+        let texts = |hits: Vec<AlertHit>| hits.into_iter().map(|h| format!("{}:{}", h.session_id, h.line)).collect::<Vec<_>>();
+
+        // The first run only baselines.
+        assert!(st.advance(&files).unwrap().0.is_empty());
+
+        append(&files[0], "new boom");
+        append(&files[1], "new boom");
+        assert_eq!(texts(st.advance(&files[..1]).unwrap().0), ["a:2"]);
+        assert_eq!(texts(st.advance(&files).unwrap().0), ["b:2"]);
+        assert!(st.advance(&files).unwrap().0.is_empty());
+
+        std::fs::remove_file(&files[1].path).unwrap();
+        st.advance(&files[..1]).unwrap();
+        assert_eq!(st.cursors.len(), 1);
+    }
+}
@@ -0,0 +1,407 @@
+/// smc backup / smc restore — snapshot sessions and smc state as `.tar.zst`.
+///
+/// Archive layout: `manifest.json`, `projects/<project-dir>/<session>.jsonl`,
+/// and `smc/<file>` for everything in `~/.smc` except the rebuildable SQLite
+/// mirror. Incremental backups only include files whose size or mtime
+/// changed since the previous backup.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::{state, time};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct BackupOpts {
+    pub out: String,
+    pub project: Option<String>,
+    /// Only sessions modified on or after this date.
+    pub after: Option<String>,
+    /// Skip files unchanged since the previous backup.
+    pub incremental: bool,
+    pub max_tokens: usize,
+}
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct RestoreOpts {
+    pub archive: String,
+    /// Claude projects directory to restore sessions into.
+    pub projects_dir: PathBuf,
+    /// Overwrite files that already exist.
+    pub force: bool,
+    /// List what would be restored without writing anything.
+    pub dry_run: bool,
+    pub max_tokens: usize,
+}
+
+/// Last backup's view of each archived file, in `~/.smc`.
+const MANIFEST_FILE: &str = "backup-manifest.json";
+const ZSTD_LEVEL: i32 = 3;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Entry {
+    path: String,
+    size: u64,
+    mtime: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Manifest {
+    version: u32,
+    created: String,
+    incremental: bool,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Debug)]
+struct BackupDone {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    out: String,
+    sessions: usize,
+    state_files: usize,
+    unchanged: usize,
+    bytes: u64,
+    incremental: bool,
+    elapsed_ms: u128,
+}
+
+#[derive(Serialize, Debug)]
+struct RestoreEntry<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    path: &'a str,
+    target: String,
+    action: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+struct RestoreDone {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    archive: String,
+    restored: usize,
+    skipped: usize,
+    /// Links, devices, and entries that would land outside their root.
+    rejected: usize,
+    dry_run: bool,
+    elapsed_ms: u128,
+}
+
+// ── backup ─────────────────────────────────────────────────────────────────
+
+pub fn backup<W: Write>(opts: &BackupOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let (after, _) = time::parse_range(opts.after.as_deref(), None)?;
+    let home = state::smc_home()?;
+
+    // (archive path, source path)
+    let mut sources: Vec<(String, PathBuf)> = Vec::new();
+    for f in files {
+        if let Some(proj) = &opts.project {
+            if !f.project_name.to_lowercase().contains(&proj.to_lowercase()) {
+                continue;
+            }
+        }
+        let mtime = state::mtime_secs(&f.path);
+        if let Some(after) = after {
+            if Utc.timestamp_opt(mtime as i64, 0).single().map_or(true, |m| m < after) {
+                continue;
+            }
+        }
+        let Some(dir) = f.path.parent().and_then(Path::file_name).and_then(|d| d.to_str()) else { continue };
+        let Some(name) = f.path.file_name().and_then(|n| n.to_str()) else { continue };
+        sources.push((format!("projects/{}/{}", dir, name), f.path.clone()));
+    }
+    for entry in std::fs::read_dir(&home)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+        if !path.is_file() || name == MANIFEST_FILE || name.ends_with(".db") {
+            continue;
+        }
+        sources.push((format!("smc/{}", name), path));
+    }
+
+    let manifest_path = home.join(MANIFEST_FILE);
+    let mut previous: BTreeMap<String, Entry> = if opts.incremental {
+        load_manifest(&manifest_path)?
+            .map(|m| m.entries.into_iter().map(|e| (e.path.clone(), e)).collect())
+            .unwrap_or_default()
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut included: Vec<(Entry, PathBuf)> = Vec::new();
+    let mut unchanged = 0usize;
+    for (name, src) in sources {
+        let size = std::fs::metadata(&src).map(|m| m.len()).unwrap_or(0);
+        let entry = Entry { path: name, size, mtime: state::mtime_secs(&src) };
+        if previous.get(&entry.path) == Some(&entry) {
+            unchanged += 1;
+            continue;
+        }
+        included.push((entry, src));
+    }
+
+    let manifest = Manifest {
+        version: 1,
+        created: Utc::now().to_rfc3339(),
+        incremental: opts.incremental,
+        entries: included.iter().map(|(e, _)| e.clone()).collect(),
+    };
+
+    let out = std::fs::File::create(&opts.out).with_context(|| format!("cannot create {}", opts.out))?;
+    let encoder = zstd::Encoder::new(out, ZSTD_LEVEL)?.auto_finish();
+    let mut tar = tar::Builder::new(encoder);
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, "manifest.json", manifest_json.as_slice())?;
+
+    let mut bytes = 0u64;
+    let mut archived_sessions = 0usize;
+    let mut archived_state = 0usize;
+    for (entry, src) in &included {
+        if let Err(e) = tar.append_path_with_name(src, &entry.path) {
+            em.warn(Some(&src.display().to_string()), &format!("{:#}", e));
+            continue;
+        }
+        bytes += entry.size;
+        if entry.path.starts_with("projects/") {
+            archived_sessions += 1;
+        } else {
+            archived_state += 1;
+        }
+        previous.insert(entry.path.clone(), entry.clone());
+    }
+    tar.into_inner()?.flush()?;
+
+    let next = Manifest { entries: previous.into_values().collect(), ..manifest };
+    std::fs::write(&manifest_path, serde_json::to_vec_pretty(&next)?)
+        .with_context(|| format!("cannot write {}", manifest_path.display()))?;
+
+    em.emit(&BackupDone {
+        record_type: "backup",
+        out: opts.out.clone(),
+        sessions: archived_sessions,
+        state_files: archived_state,
+        unchanged,
+        bytes,
+        incremental: opts.incremental,
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── restore ────────────────────────────────────────────────────────────────
+
+pub fn restore<W: Write>(opts: &RestoreOpts, em: &mut Emitter<W>) -> Result<()> {
+    restore_into(opts, &state::smc_home()?, em)
+}
+
+/// [`restore`] with `smc/` entries going to `home`. Only regular files and
+/// directories are unpacked, and only where their resolved parent stays
+/// inside the root they belong to: a symlink — in the archive or already on
+/// disk — can't redirect a write out of it.
+fn restore_into<W: Write>(opts: &RestoreOpts, home: &Path, em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+
+    let f = std::fs::File::open(&opts.archive).with_context(|| format!("cannot open {}", opts.archive))?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(f)?);
+    archive.set_preserve_mtime(true);
+
+    let mut restored = 0usize;
+    let mut skipped = 0usize;
+    let mut rejected = 0usize;
+    let mut budget_left = true;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let Some((root, target)) = target_for(&name, &opts.projects_dir, home) else { continue };
+        let kind = entry.header().entry_type();
+
+        let action = if !matches!(kind, tar::EntryType::Regular | tar::EntryType::Directory)
+            || !stays_inside(&target, root)?
+            || std::fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink())
+        {
+            "rejected"
+        } else if kind.is_dir() {
+            if !opts.dry_run {
+                std::fs::create_dir_all(&target).with_context(|| format!("cannot create {}", target.display()))?;
+            }
+            continue;
+        } else if target.exists() && !opts.force {
+            "skipped"
+        } else {
+            "restored"
+        };
+        if action == "restored" && !opts.dry_run {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+                anyhow::ensure!(stays_inside(parent, root)?, "{} resolves outside {}", parent.display(), root.display());
+            }
+            entry.unpack(&target).with_context(|| format!("cannot write {}", target.display()))?;
+        }
+        match action {
+            "restored" => restored += 1,
+            "skipped" => skipped += 1,
+            _ => rejected += 1,
+        }
+
+        if budget_left {
+            budget_left = em.emit(&RestoreEntry {
+                record_type: "restore_file",
+                path: &name,
+                target: target.display().to_string(),
+                action,
+            })?;
+        }
+    }
+
+    em.emit(&RestoreDone {
+        record_type: "restore",
+        archive: opts.archive.clone(),
+        restored,
+        skipped,
+        rejected,
+        dry_run: opts.dry_run,
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn load_manifest(path: &Path) -> Result<Option<Manifest>> {
+    match std::fs::read(path) {
+        Ok(b) => Ok(Some(serde_json::from_slice(&b).with_context(|| format!("cannot parse {}", path.display()))?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+    }
+}
+
+/// The root an archive entry belongs to and where it lands on disk; `None`
+/// for the manifest, unknown prefixes, and any path that could escape its
+/// root.
+fn target_for<'a>(name: &str, projects_dir: &'a Path, home: &'a Path) -> Option<(&'a Path, PathBuf)> {
+    let (root, rest) = match name.split_once('/')? {
+        ("projects", rest) => (projects_dir, rest),
+        ("smc", rest) => (home, rest),
+        _ => return None,
+    };
+    let rel = Path::new(rest);
+    if rest.is_empty() || !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    Some((root, root.join(rel)))
+}
+
+/// Whether `path` resolves inside `root`, judged by its deepest existing
+/// ancestor (symlinks followed). A root that doesn't exist yet can't be
+/// redirected, so anything under it is inside.
+fn stays_inside(path: &Path, root: &Path) -> Result<bool> {
+    let Ok(root) = root.canonicalize() else { return Ok(true) };
+    let mut existing = path;
+    while std::fs::symlink_metadata(existing).is_err() {
+        match existing.parent() {
+            Some(p) => existing = p,
+            None => return Ok(false),
+        }
+    }
+    // A dangling link has no canonical form; treat it as outside.
+    Ok(existing.canonicalize().is_ok_and(|p| p.starts_with(&root)))
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn targets_stay_inside_roots() {
+        let (p, h) = (Path::new("/claude"), Path::new("/smc"));
+        assert_eq!(target_for("projects/-a/x.jsonl", p, h), Some((p, PathBuf::from("/claude/-a/x.jsonl"))));
+        assert_eq!(target_for("smc/alerts.json", p, h), Some((h, PathBuf::from("/smc/alerts.json"))));
+        assert_eq!(target_for("manifest.json", p, h), None);
+        assert_eq!(target_for("projects/../etc/passwd", p, h), None);
+        assert_eq!(target_for("other/x", p, h), None);
+    }
+
+    #[test]
+    fn restore_rejects_links_out_of_root() {
+        let dir = TempDir::new("restore");
+        let (projects, home, outside) = (dir.join("projects"), dir.join("smc"), dir.join("outside"));
+        for d in [&projects, &home, &outside] {
+            std::fs::create_dir_all(d).unwrap();
+        }
+
+        let archive = dir.join("hostile.tar.zst");
+        let mut tar = tar::Builder::new(zstd::Encoder::new(std::fs::File::create(&archive).unwrap(), 0).unwrap());
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        tar.append_link(&mut link, "projects/x", &outside).unwrap();
+        let file = |tar: &mut tar::Builder<_>, path: &str, body: &[u8]| {
+            let mut h = tar::Header::new_gnu();
+            h.set_size(body.len() as u64);
+            h.set_mode(0o644);
+            tar.append_data(&mut h, path, body).unwrap();
+        };
+        file(&mut tar, "projects/x/passwd", b"pwned");
+        file(&mut tar, "projects/-a/s1.jsonl", b"{}\n");
+        tar.into_inner().unwrap().finish().unwrap();
+
+        // A link already on disk is no way out either.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside, projects.join("y")).unwrap();
+        let mut tar = tar::Builder::new(zstd::Encoder::new(std::fs::File::create(dir.join("b.tar.zst")).unwrap(), 0).unwrap());
+        file(&mut tar, "projects/y/passwd", b"pwned");
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let opts = |archive: &Path| RestoreOpts {
+            archive: archive.display().to_string(),
+            projects_dir: projects.clone(),
+            force: false,
+            dry_run: false,
+            max_tokens: 0,
+        };
+        let mut em = Emitter::capturing(0);
+        restore_into(&opts(&archive), &home, &mut em).unwrap();
+        let records = em.into_records();
+        let actions: Vec<&str> = records.iter().filter_map(|r| r["action"].as_str()).collect();
+        // With the link refused, x/passwd lands in a real directory inside the root.
+        assert_eq!(actions, ["rejected", "restored", "restored"]);
+        assert_eq!((records[3]["restored"].as_u64(), records[3]["rejected"].as_u64()), (Some(2), Some(1)));
+        assert!(std::fs::symlink_metadata(projects.join("x")).unwrap().is_dir());
+        assert_eq!(std::fs::read(projects.join("-a/s1.jsonl")).unwrap(), b"{}\n");
+
+        #[cfg(unix)]
+        {
+            let mut em = Emitter::capturing(0);
+            restore_into(&opts(&dir.join("b.tar.zst")), &home, &mut em).unwrap();
+            assert_eq!(em.into_records()[0]["action"], "rejected");
+        }
+        assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+    }
+}
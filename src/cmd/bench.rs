@@ -0,0 +1,201 @@
+/// smc bench — time full-corpus scans stage by stage.
+///
+/// Each stage (discovery, parse-only, search for a fixed query, cached
+/// summaries) runs once cold and `runs` more times warm. "Cold" is the first
+/// pass in this process: the OS page cache may already hold the files, so
+/// for a truly cold disk drop it first. The `summaries` stage uses a scratch
+/// cache, so its cold pass parses and its warm passes are cache hits — the
+/// gap between `parse` and warm `summaries` is what the cache buys.
+use std::cell::Cell;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::cmd::search::{self, SearchOpts};
+use crate::models::Record;
+use crate::output::Emitter;
+use crate::util::cache::{self, SummaryCache};
+use crate::util::discover::{self, SessionFile};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct BenchOpts {
+    /// Claude projects directory to scan.
+    pub dir: PathBuf,
+    /// Query for the search stage.
+    pub query: String,
+    /// Warm passes per stage (the reported time is their median).
+    pub runs: usize,
+}
+
+/// One timed pass of a stage.
+type Stage<'a> = dyn Fn() -> Result<()> + 'a;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct StageRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    stage: &'static str,
+    pass: &'static str,
+    elapsed_ms: f64,
+    mb_per_sec: f64,
+    lines_per_sec: f64,
+}
+
+#[derive(Serialize, Debug)]
+struct BenchSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    files: usize,
+    bytes: u64,
+    lines: u64,
+    query: String,
+    warm_runs: usize,
+    threads: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &BenchOpts, em: &mut Emitter<W>) -> Result<()> {
+    anyhow::ensure!(opts.runs > 0, "--runs must be at least 1");
+    let start = Instant::now();
+
+    let t = Instant::now();
+    let files = discover::discover_jsonl_files(&opts.dir)?;
+    let mut cold = vec![t.elapsed()];
+    let bytes: u64 = files.iter().map(|f| f.size_bytes).sum();
+    // Set by the parse stage, so counting lines doesn't warm the cold pass.
+    let lines = Cell::new(0);
+
+    let search_opts = SearchOpts::builder().query(opts.query.as_str()).max_results(0).truncate(1).build();
+    let scratch = std::env::temp_dir().join(format!("smc-bench-{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&scratch);
+
+    let stages: [(&'static str, Box<Stage>); 4] = [
+        ("discover", Box::new(|| discover::discover_jsonl_files(&opts.dir).map(drop))),
+        ("parse", Box::new(|| {
+            lines.set(parse_all(&files));
+            Ok(())
+        })),
+        ("search", Box::new(|| search::run(&search_opts, &files, &mut Emitter::capturing(0)))),
+        ("summaries", Box::new(|| {
+            let c = SummaryCache::open(&scratch);
+            cache::summaries(&files.iter().collect::<Vec<_>>(), Some(&c));
+            c.save()
+        })),
+    ];
+
+    // Cold pass of every stage first, so no stage's cold pass rides on
+    // another's warm-up.
+    for (_, stage) in &stages[1..] {
+        cold.push(time(stage)?);
+    }
+    let lines = lines.get();
+    let rate = |d: Duration| {
+        let secs = d.as_secs_f64().max(1e-9);
+        (round2(bytes as f64 / 1e6 / secs), round2(lines as f64 / secs))
+    };
+    for ((name, stage), cold) in stages.iter().zip(cold) {
+        let mut warm = Vec::with_capacity(opts.runs);
+        for _ in 0..opts.runs {
+            warm.push(time(stage)?);
+        }
+        warm.sort();
+        for (pass, d) in [("cold", cold), ("warm", warm[warm.len() / 2])] {
+            let (mb_per_sec, lines_per_sec) = rate(d);
+            em.emit(&StageRecord {
+                record_type: "bench",
+                stage: name,
+                pass,
+                elapsed_ms: round2(d.as_secs_f64() * 1e3),
+                mb_per_sec,
+                lines_per_sec,
+            })?;
+        }
+    }
+    let _ = std::fs::remove_file(&scratch);
+
+    em.emit(&BenchSummary {
+        record_type: "summary",
+        files: files.len(),
+        bytes,
+        lines,
+        query: opts.query.clone(),
+        warm_runs: opts.runs,
+        threads: rayon::current_num_threads(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn time(stage: &Stage) -> Result<Duration> {
+    let t = Instant::now();
+    stage()?;
+    Ok(t.elapsed())
+}
+
+/// Parse every non-blank line of every file; returns how many there were.
+fn parse_all(files: &[SessionFile]) -> u64 {
+    files
+        .par_iter()
+        .map(|f| {
+            let Ok(file) = std::fs::File::open(&f.path) else { return 0 };
+            let mut n = 0;
+            for line in std::io::BufReader::with_capacity(256 * 1024, file).lines() {
+                let Ok(line) = line else { continue };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                n += 1;
+                let _ = std::hint::black_box(serde_json::from_str::<Record>(&line));
+            }
+            n
+        })
+        .sum()
+}
+
+fn round2(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn reports_every_stage_cold_and_warm() {
+        let dir = TempDir::new("bench");
+        let proj = dir.join("-Users-me-GitHub-alpha");
+        std::fs::create_dir_all(&proj).unwrap();
+        let line = r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"an error here"}}"#;
+        std::fs::write(proj.join("s1.jsonl"), format!("{}\n\n{}\n", line, line)).unwrap();
+
+        let opts = BenchOpts { dir: dir.to_path_buf(), query: "error".into(), runs: 2 };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &mut em).unwrap();
+        let records = em.into_records();
+
+        let stages: Vec<(&str, &str)> =
+            records.iter().filter_map(|r| Some((r["stage"].as_str()?, r["pass"].as_str()?))).collect();
+        assert_eq!(stages.len(), 8);
+        assert_eq!(stages[0], ("discover", "cold"));
+        assert_eq!(stages[7], ("summaries", "warm"));
+        let summary = records.last().unwrap();
+        assert_eq!((summary["files"].as_u64(), summary["lines"].as_u64()), (Some(1), Some(2)));
+    }
+}
@@ -0,0 +1,291 @@
+/// smc blame — which sessions touched a file, and what they changed.
+///
+/// Looks at Read/Edit/MultiEdit/Write/NotebookEdit tool calls and at
+/// `file-history-snapshot` records, so a file counts as touched even when
+/// only Claude Code's own backup tracking saw it. Other tools that take a
+/// path (NotebookRead, MCP tools) are ignored, as in `freq churn`.
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{ContentBlock, MessageContent, Record, Timestamp};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::cmd::digest::WRITE_TOOLS;
+use crate::util::{diff, text};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct BlameOpts {
+    /// Absolute path, or a relative suffix like `src/main.rs`.
+    pub path: String,
+    pub project: Option<String>,
+    /// Max change entries listed per session.
+    pub changes: usize,
+    pub max_tokens: usize,
+}
+
+/// Max chars of each change snippet.
+const SNIPPET_CHARS: usize = 300;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct BlameRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_touch: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_touch: Option<Timestamp>,
+    reads: usize,
+    edits: usize,
+    writes: usize,
+    snapshots: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    changes: Vec<Change>,
+}
+
+#[derive(Serialize, Debug)]
+struct Change {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    tool: String,
+    snippet: String,
+}
+
+#[derive(Serialize, Debug)]
+struct BlameSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    path: String,
+    sessions: usize,
+    edits: usize,
+    files_scanned: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &BlameOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let mut sessions: Vec<BlameRecord> = filtered
+        .par_iter()
+        .filter_map(|file| blame_file(file, opts))
+        .collect();
+    sessions.sort_by(|a, b| b.last_touch.cmp(&a.last_touch));
+
+    let count = sessions.len();
+    let edits: usize = sessions.iter().map(|s| s.edits + s.writes).sum();
+    for rec in &sessions {
+        if !em.emit(rec)? {
+            break;
+        }
+    }
+
+    em.emit(&BlameSummary {
+        record_type: "summary",
+        path: opts.path.clone(),
+        sessions: count,
+        edits,
+        files_scanned: filtered.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Per-file scan ──────────────────────────────────────────────────────────
+
+fn blame_file(file: &SessionFile, opts: &BlameOpts) -> Option<BlameRecord> {
+    let f = std::fs::File::open(&file.path).ok()?;
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+    let needle = basename(&opts.path);
+
+    let mut rec = BlameRecord {
+        record_type: "blame",
+        project: file.project_name.clone(),
+        session_id: file.session_id.clone(),
+        first_touch: None,
+        last_touch: None,
+        reads: 0,
+        edits: 0,
+        writes: 0,
+        snapshots: 0,
+        changes: Vec::new(),
+    };
+
+    for (line_num, line) in reader.lines().enumerate() {
+        let Ok(line) = line else { continue };
+        // Cheap pre-filter before a full parse.
+        if !line.contains(needle) {
+            continue;
+        }
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+
+        let (timestamp, touched) = match &record {
+            Record::FileHistorySnapshot(v) => {
+                if !snapshot_tracks(v, &opts.path) {
+                    continue;
+                }
+                rec.snapshots += 1;
+                let ts = v.pointer("/snapshot/timestamp").and_then(Value::as_str).map(Timestamp::new);
+                (ts, true)
+            }
+            _ => {
+                let Some(msg) = record.as_message() else { continue };
+                let MessageContent::Blocks(blocks) = &msg.message.content else { continue };
+                let mut touched = false;
+                for block in blocks {
+                    let ContentBlock::ToolUse { name, input, .. } = block else { continue };
+                    if name != "Read" && !WRITE_TOOLS.contains(&name.as_str()) {
+                        continue;
+                    }
+                    let Some(target) = target_path(input) else { continue };
+                    if !path_matches(target, &opts.path) {
+                        continue;
+                    }
+                    touched = true;
+                    match name.as_str() {
+                        "Read" => rec.reads += 1,
+                        "Write" => rec.writes += 1,
+                        _ => rec.edits += 1,
+                    }
+                    if name != "Read" && (opts.changes == 0 || rec.changes.len() < opts.changes) {
+                        rec.changes.push(Change {
+                            line: line_num + 1,
+                            timestamp: msg.timestamp.clone(),
+                            tool: name.clone(),
+                            snippet: text::preview(&change_snippet(name, input), SNIPPET_CHARS),
+                        });
+                    }
+                }
+                (msg.timestamp.clone(), touched)
+            }
+        };
+
+        if touched {
+            if let Some(ts) = timestamp {
+                if rec.first_touch.as_ref().map_or(true, |f| &ts < f) {
+                    rec.first_touch = Some(ts.clone());
+                }
+                if rec.last_touch.as_ref().map_or(true, |l| &ts > l) {
+                    rec.last_touch = Some(ts);
+                }
+            }
+        }
+    }
+
+    let touched = rec.reads + rec.edits + rec.writes + rec.snapshots > 0;
+    touched.then_some(rec)
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+pub(crate) fn target_path(input: &Value) -> Option<&str> {
+    input
+        .get("file_path")
+        .or_else(|| input.get("notebook_path"))
+        .and_then(Value::as_str)
+}
+
+/// Exact match, or `query` is a suffix of `path` on a component boundary.
+/// Also accepts the reverse, since snapshots may record cwd-relative paths.
+fn path_matches(path: &str, query: &str) -> bool {
+    let suffix = |long: &str, short: &str| {
+        long == short || (long.ends_with(short) && long[..long.len() - short.len()].ends_with('/'))
+    };
+    suffix(path, query) || suffix(query, path)
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn snapshot_tracks(v: &Value, query: &str) -> bool {
+    v.pointer("/snapshot/trackedFileBackups")
+        .and_then(Value::as_object)
+        .is_some_and(|m| m.keys().any(|k| path_matches(k, query)))
+}
+
+fn change_snippet(tool: &str, input: &Value) -> String {
+    if let Some(d) = diff::edit_diff(tool, input) {
+        // Drop the ---/+++ header; the path is already known.
+        return d.lines().skip(2).collect::<Vec<_>>().join("\n");
+    }
+    match input.get("content").and_then(Value::as_str) {
+        Some(content) => format!("wrote {} lines:\n{}", content.lines().count(), content),
+        None => input.get("new_source").and_then(Value::as_str).unwrap_or("").to_string(),
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+    use serde_json::json;
+
+    #[test]
+    fn suffix_matching_respects_components() {
+        assert!(path_matches("/repo/src/main.rs", "src/main.rs"));
+        assert!(path_matches("/repo/src/main.rs", "/repo/src/main.rs"));
+        assert!(path_matches("src/main.rs", "/repo/src/main.rs"));
+        assert!(!path_matches("/repo/src/xmain.rs", "main.rs"));
+    }
+
+    #[test]
+    fn snapshot_paths() {
+        let v = json!({"snapshot": {"trackedFileBackups": {"src/lib.rs": {}}, "timestamp": "2026-01-01T00:00:00Z"}});
+        assert!(snapshot_tracks(&v, "/repo/src/lib.rs"));
+        assert!(!snapshot_tracks(&v, "src/main.rs"));
+    }
+
+    #[test]
+    fn write_snippet_counts_lines() {
+        let s = change_snippet("Write", &json!({"file_path": "a", "content": "x\ny\n"}));
+        assert!(s.starts_with("wrote 2 lines:"));
+    }
+
+    #[test]
+    fn counts_only_file_tools() {
+        let dir = TempDir::new("blame");
+        let call = |name: &str, key: &str| {
+            format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"t","name":"{}","input":{{"{}":"/r/a.ipynb"}}}}]}}}}"#,
+                name, key
+            ) + "\n"
+        };
+        let body = [
+            call("Read", "file_path"),
+            call("NotebookEdit", "notebook_path"),
+            call("NotebookRead", "notebook_path"),
+            call("mcp__fs__stat", "file_path"),
+        ]
+        .concat();
+        let file = dir.session("s1", "p", &body);
+        let opts = BlameOpts { path: "a.ipynb".into(), project: None, changes: 0, max_tokens: 0 };
+        let rec = blame_file(&file, &opts).unwrap();
+        assert_eq!((rec.reads, rec.edits, rec.writes), (1, 1, 0));
+        assert_eq!(rec.changes.iter().map(|c| c.tool.as_str()).collect::<Vec<_>>(), ["NotebookEdit"]);
+    }
+}
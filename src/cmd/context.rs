@@ -4,16 +4,21 @@ use std::io::Write;
 use anyhow::Result;
 use serde::Serialize;
 
-use crate::models::Record;
+use crate::models::{Record, Timestamp};
 use crate::output::Emitter;
 use crate::util::discover::SessionFile;
+use crate::util::text;
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct ContextOpts {
     pub session: String,
     pub line: usize,
     pub context: usize,
+    /// Max chars of message text per record (0 = no truncation).
+    pub truncate: usize,
     pub max_tokens: usize,
 }
 
@@ -26,7 +31,7 @@ struct ContextRecord {
     line: usize,
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    timestamp: Option<String>,
+    timestamp: Option<Timestamp>,
     text: String,
     is_target: bool,
 }
@@ -62,7 +67,7 @@ pub fn run<W: Write>(opts: &ContextOpts, file: &SessionFile, em: &mut Emitter<W>
     for (i, (line_num, record)) in messages[start..end].iter().enumerate() {
         let msg = record.as_message().unwrap();
         let text = msg.text_content();
-        let preview: String = text.chars().take(500).collect();
+        let preview = text::preview(&text, opts.truncate);
 
         let rec = ContextRecord {
             record_type: "context",
@@ -0,0 +1,295 @@
+/// smc dataset — convert sessions into fine-tuning conversation datasets.
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::models::{ContentBlock, MessageContent, Record, UserKind};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::redact::{self, Redactor};
+use crate::util::text;
+use crate::util::time::{self, DateRange};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DatasetOpts {
+    pub format: DatasetFormat,
+    pub out: String,
+    pub project: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+    /// Skip conversations with fewer user turns than this.
+    pub min_turns: usize,
+    /// Inline tool calls and results into assistant turns instead of dropping them.
+    pub inline_tools: bool,
+    /// Optional system prompt prepended to every conversation.
+    pub system: Option<String>,
+    /// Scrub secrets from every turn.
+    pub redact: Option<Redactor>,
+    pub max_tokens: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DatasetFormat {
+    #[default]
+    OpenAiChat,
+    ShareGpt,
+}
+
+impl DatasetFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "openai-chat" | "openai" => Ok(Self::OpenAiChat),
+            "sharegpt" => Ok(Self::ShareGpt),
+            _ => anyhow::bail!("unknown dataset format '{}' — use: openai-chat, sharegpt", s),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::OpenAiChat => "openai-chat",
+            Self::ShareGpt => "sharegpt",
+        }
+    }
+}
+
+/// Tool results inlined into assistant turns are capped at this many chars.
+const TOOL_RESULT_CHARS: usize = 2000;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct DatasetDone {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    out: String,
+    format: &'static str,
+    conversations: usize,
+    turns: usize,
+    skipped: usize,
+    files_scanned: usize,
+    elapsed_ms: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug)]
+struct Turn {
+    role: Role,
+    content: String,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &DatasetOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let range = time::parse_range(opts.after.as_deref(), opts.before.as_deref())?;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let conversations: Vec<Option<Vec<Turn>>> = filtered
+        .par_iter()
+        .map(|file| build_turns(file, opts, range))
+        .collect();
+
+    let f = std::fs::File::create(&opts.out).with_context(|| format!("cannot create {}", opts.out))?;
+    let mut out = std::io::BufWriter::new(f);
+
+    let mut written = 0usize;
+    let mut turns = 0usize;
+    let mut skipped = 0usize;
+    for conv in conversations {
+        let Some(conv) = conv else {
+            skipped += 1;
+            continue;
+        };
+        let user_turns = conv.iter().filter(|t| t.role == Role::User).count();
+        if conv.is_empty() || user_turns < opts.min_turns.max(1) {
+            skipped += 1;
+            continue;
+        }
+        serde_json::to_writer(&mut out, &render(&conv, opts))?;
+        out.write_all(b"\n")?;
+        written += 1;
+        turns += conv.len();
+    }
+    out.flush()?;
+
+    em.emit(&DatasetDone {
+        record_type: "dataset",
+        out: opts.out.clone(),
+        format: opts.format.name(),
+        conversations: written,
+        turns,
+        skipped,
+        files_scanned: filtered.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Conversation building ──────────────────────────────────────────────────
+
+/// Clean alternating turns for one session, or `None` if it can't be read
+/// or falls entirely outside the date range.
+fn build_turns(file: &SessionFile, opts: &DatasetOpts, (after, before): DateRange) -> Option<Vec<Turn>> {
+    let records = crate::cmd::parse_records(file).ok()?;
+
+    let first = records.iter().find_map(|r| r.as_message()?.time());
+    if !time::in_range(first, after, before) {
+        return None;
+    }
+
+    let mut turns: Vec<Turn> = Vec::new();
+    for record in &records {
+        let Some(msg) = record.as_message() else { continue };
+        let (role, content) = match record {
+            Record::User(_) => match msg.user_kind() {
+                Some(UserKind::Human) => (Role::User, msg.text_no_thinking()),
+                Some(UserKind::ToolResult) if opts.inline_tools => (Role::Assistant, tool_results(&msg.message.content)),
+                _ => continue,
+            },
+            Record::Assistant(_) if msg.is_api_error_message => continue,
+            Record::Assistant(_) => {
+                let mut content = msg.text_no_thinking();
+                if opts.inline_tools {
+                    let calls = msg.tool_input_content();
+                    if !calls.is_empty() {
+                        if !content.is_empty() {
+                            content.push_str("\n\n");
+                        }
+                        content.push_str(&calls);
+                    }
+                }
+                (Role::Assistant, content)
+            }
+            _ => continue,
+        };
+
+        let content = redact::apply(opts.redact.as_ref(), content.trim()).into_owned();
+        if content.is_empty() {
+            continue;
+        }
+        match turns.last_mut() {
+            Some(last) if last.role == role => {
+                last.content.push_str("\n\n");
+                last.content.push_str(&content);
+            }
+            _ => turns.push(Turn { role, content }),
+        }
+    }
+
+    // Datasets expect the user to speak first.
+    let lead = turns.iter().take_while(|t| t.role == Role::Assistant).count();
+    turns.drain(..lead);
+    Some(turns)
+}
+
+fn tool_results(content: &MessageContent) -> String {
+    let MessageContent::Blocks(blocks) = content else { return String::new() };
+    let parts: Vec<String> = blocks
+        .iter()
+        .filter_map(|b| match b {
+            ContentBlock::ToolResult { content: Some(c), .. } => {
+                let s = c.as_str().map(String::from).unwrap_or_else(|| c.to_string());
+                Some(format!("[result] {}", text::preview(&s, TOOL_RESULT_CHARS)))
+            }
+            _ => None,
+        })
+        .collect();
+    parts.join("\n")
+}
+
+fn render(conv: &[Turn], opts: &DatasetOpts) -> serde_json::Value {
+    match opts.format {
+        DatasetFormat::OpenAiChat => {
+            let mut messages = Vec::new();
+            if let Some(sys) = &opts.system {
+                messages.push(json!({"role": "system", "content": sys}));
+            }
+            for t in conv {
+                let role = if t.role == Role::User { "user" } else { "assistant" };
+                messages.push(json!({"role": role, "content": t.content}));
+            }
+            json!({ "messages": messages })
+        }
+        DatasetFormat::ShareGpt => {
+            let mut turns = Vec::new();
+            if let Some(sys) = &opts.system {
+                turns.push(json!({"from": "system", "value": sys}));
+            }
+            for t in conv {
+                let from = if t.role == Role::User { "human" } else { "gpt" };
+                turns.push(json!({"from": from, "value": t.content}));
+            }
+            json!({ "conversations": turns })
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(format: DatasetFormat, system: Option<&str>) -> DatasetOpts {
+        DatasetOpts {
+            format,
+            out: String::new(),
+            project: None,
+            after: None,
+            before: None,
+            min_turns: 1,
+            inline_tools: false,
+            system: system.map(String::from),
+            redact: None,
+            max_tokens: 0,
+        }
+    }
+
+    fn conv() -> Vec<Turn> {
+        vec![
+            Turn { role: Role::User, content: "hi".into() },
+            Turn { role: Role::Assistant, content: "hello".into() },
+        ]
+    }
+
+    #[test]
+    fn format_parse() {
+        assert_eq!(DatasetFormat::parse("openai-chat").unwrap(), DatasetFormat::OpenAiChat);
+        assert_eq!(DatasetFormat::parse("sharegpt").unwrap(), DatasetFormat::ShareGpt);
+        assert!(DatasetFormat::parse("alpaca").is_err());
+    }
+
+    #[test]
+    fn render_openai_with_system() {
+        let v = render(&conv(), &opts(DatasetFormat::OpenAiChat, Some("sys")));
+        let roles: Vec<&str> = v["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, ["system", "user", "assistant"]);
+    }
+
+    #[test]
+    fn render_sharegpt() {
+        let v = render(&conv(), &opts(DatasetFormat::ShareGpt, None));
+        assert_eq!(v["conversations"][0]["from"], "human");
+        assert_eq!(v["conversations"][1]["value"], "hello");
+    }
+}
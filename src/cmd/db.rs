@@ -0,0 +1,439 @@
+/// smc db — mirror sessions into SQLite and query them with SQL.
+///
+/// `sync` is incremental: a session is re-imported only when its file size
+/// or mtime changed since the last run, and dropped once its file is gone.
+/// `query` opens the database read-only.
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use rusqlite::{params, types::ValueRef, Connection, OpenFlags};
+use serde::Serialize;
+
+use crate::models::{ContentBlock, MessageContent, Record, UsageDedup};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::state;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DbSyncOpts {
+    /// Database path (default: ~/.smc/smc.db).
+    pub db: Option<String>,
+    pub max_tokens: usize,
+}
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DbQueryOpts {
+    pub db: Option<String>,
+    pub sql: String,
+    pub max_tokens: usize,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    session_id    TEXT PRIMARY KEY,
+    project       TEXT NOT NULL,
+    path          TEXT NOT NULL,
+    size_bytes    INTEGER NOT NULL,
+    mtime         INTEGER NOT NULL,
+    first_ts      TEXT,
+    last_ts       TEXT,
+    message_count INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    session_id  TEXT NOT NULL,
+    line        INTEGER NOT NULL,
+    uuid        TEXT,
+    parent_uuid TEXT,
+    role        TEXT NOT NULL,
+    timestamp   TEXT,
+    git_branch  TEXT,
+    cwd         TEXT,
+    model       TEXT,
+    text        TEXT NOT NULL,
+    thinking    TEXT,
+    PRIMARY KEY (session_id, line)
+);
+CREATE TABLE IF NOT EXISTS tool_calls (
+    session_id  TEXT NOT NULL,
+    line        INTEGER NOT NULL,
+    seq         INTEGER NOT NULL,
+    tool_use_id TEXT,
+    name        TEXT NOT NULL,
+    input       TEXT NOT NULL,
+    PRIMARY KEY (session_id, line, seq)
+);
+CREATE TABLE IF NOT EXISTS usage (
+    session_id                  TEXT NOT NULL,
+    line                        INTEGER NOT NULL,
+    message_id                  TEXT,
+    model                       TEXT,
+    input_tokens                INTEGER NOT NULL,
+    output_tokens               INTEGER NOT NULL,
+    cache_creation_input_tokens INTEGER NOT NULL,
+    cache_read_input_tokens     INTEGER NOT NULL,
+    PRIMARY KEY (session_id, line)
+);
+CREATE INDEX IF NOT EXISTS messages_ts ON messages (timestamp);
+CREATE INDEX IF NOT EXISTS tool_calls_name ON tool_calls (name);
+";
+
+/// Stored as `PRAGMA user_version`. A database from another version is
+/// rebuilt from scratch on the next sync.
+const SCHEMA_VERSION: i64 = 2;
+
+/// Message records paired with their 1-based JSONL line number.
+type Numbered = Vec<(usize, Record)>;
+
+/// Files parsed per batch before writing, to bound memory on large corpora.
+const BATCH: usize = 32;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct SyncDone {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    db: String,
+    sessions: usize,
+    updated: usize,
+    unchanged: usize,
+    /// Sessions whose files no longer exist.
+    removed: usize,
+    messages: usize,
+    tool_calls: usize,
+    elapsed_ms: u128,
+}
+
+#[derive(Serialize, Debug)]
+struct RowRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    values: serde_json::Map<String, serde_json::Value>,
+}
+
+// ── sync ───────────────────────────────────────────────────────────────────
+
+pub fn sync<W: Write>(opts: &DbSyncOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let path = db_path(opts.db.as_deref())?;
+    let mut conn = Connection::open(&path).with_context(|| format!("cannot open {}", path.display()))?;
+    let version: i64 = conn.pragma_query_value(None, "user_version", |r| r.get(0))?;
+    if version != SCHEMA_VERSION {
+        conn.execute_batch("DROP TABLE IF EXISTS sessions; DROP TABLE IF EXISTS messages;
+             DROP TABLE IF EXISTS tool_calls; DROP TABLE IF EXISTS usage;")?;
+    }
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    let mut known: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut gone: Vec<String> = Vec::new();
+    {
+        let mut stmt = conn.prepare("SELECT session_id, size_bytes, mtime, path FROM sessions")?;
+        let mut rows = stmt.query([])?;
+        while let Some(r) = rows.next()? {
+            let (id, path): (String, String) = (r.get(0)?, r.get(3)?);
+            if std::path::Path::new(&path).exists() {
+                known.insert(id, (r.get(1)?, r.get(2)?));
+            } else {
+                gone.push(id);
+            }
+        }
+    }
+
+    let tx = conn.transaction()?;
+    for id in &gone {
+        for table in ["sessions", "messages", "tool_calls", "usage"] {
+            tx.execute(&format!("DELETE FROM {} WHERE session_id = ?1", table), [id])?;
+        }
+    }
+    tx.commit()?;
+
+    let stale: Vec<(&SessionFile, u64)> = files
+        .iter()
+        .map(|f| (f, state::mtime_secs(&f.path)))
+        .filter(|(f, mtime)| known.get(&f.session_id) != Some(&(f.size_bytes, *mtime)))
+        .collect();
+
+    let mut messages = 0usize;
+    let mut tool_calls = 0usize;
+
+    for batch in stale.chunks(BATCH) {
+        let parsed: Vec<(&SessionFile, u64, Result<Numbered>)> = batch
+            .par_iter()
+            .map(|(f, mtime)| (*f, *mtime, read_numbered(f)))
+            .collect();
+
+        let tx = conn.transaction()?;
+        for (file, mtime, records) in parsed {
+            let records = match records {
+                Ok(r) => r,
+                Err(e) => {
+                    em.warn(Some(&file.path.display().to_string()), &format!("{:#}", e));
+                    continue;
+                }
+            };
+            let (m, t) = import_session(&tx, file, mtime, &records)?;
+            messages += m;
+            tool_calls += t;
+        }
+        tx.commit()?;
+    }
+
+    em.emit(&SyncDone {
+        record_type: "db_sync",
+        db: path.display().to_string(),
+        sessions: files.len(),
+        updated: stale.len(),
+        unchanged: files.len() - stale.len(),
+        removed: gone.len(),
+        messages,
+        tool_calls,
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+fn read_numbered(file: &SessionFile) -> Result<Numbered> {
+    let f = std::fs::File::open(&file.path)?;
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+    let mut out = Vec::new();
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(record) = serde_json::from_str::<Record>(&line) {
+            if record.is_message() {
+                out.push((line_num + 1, record));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Replace one session's rows. Returns (messages, tool calls) inserted.
+fn import_session(
+    tx: &rusqlite::Transaction,
+    file: &SessionFile,
+    mtime: u64,
+    records: &[(usize, Record)],
+) -> Result<(usize, usize)> {
+    for table in ["messages", "tool_calls", "usage"] {
+        tx.execute(&format!("DELETE FROM {} WHERE session_id = ?1", table), [&file.session_id])?;
+    }
+
+    let mut msg_stmt = tx.prepare_cached(
+        "INSERT INTO messages (session_id, line, uuid, parent_uuid, role, timestamp, git_branch, cwd, model, text, thinking)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+    )?;
+    let mut tool_stmt = tx.prepare_cached(
+        "INSERT INTO tool_calls (session_id, line, seq, tool_use_id, name, input) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    let mut usage_stmt = tx.prepare_cached(
+        "INSERT INTO usage (session_id, line, message_id, model, input_tokens, output_tokens,
+                            cache_creation_input_tokens, cache_read_input_tokens)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+
+    // One row per API response, on the first line that carries it.
+    let mut dedup = UsageDedup::default();
+    let mut tool_count = 0usize;
+    for (line, record) in records {
+        let Some(msg) = record.as_message() else { continue };
+        let thinking = msg.thinking_content();
+        let parent = msg.parent_uuid.as_ref().and_then(|v| v.as_str());
+        msg_stmt.execute(params![
+            file.session_id,
+            line,
+            msg.uuid,
+            parent,
+            record.role(),
+            msg.timestamp.as_ref().map(|t| t.as_str()),
+            msg.git_branch,
+            msg.cwd,
+            msg.message.model,
+            msg.text_no_thinking(),
+            (!thinking.is_empty()).then_some(thinking),
+        ])?;
+
+        if let MessageContent::Blocks(blocks) = &msg.message.content {
+            let mut seq = 0usize;
+            for block in blocks {
+                if let ContentBlock::ToolUse { id, name, input } = block {
+                    tool_stmt.execute(params![file.session_id, line, seq, id, name, input.to_string()])?;
+                    seq += 1;
+                }
+            }
+            tool_count += seq;
+        }
+
+        if let Some(u) = dedup.first(msg) {
+            usage_stmt.execute(params![
+                file.session_id,
+                line,
+                msg.message.id,
+                msg.message.model,
+                u.input_tokens,
+                u.output_tokens,
+                u.cache_creation_input_tokens,
+                u.cache_read_input_tokens,
+            ])?;
+        }
+    }
+
+    let times = records.iter().filter_map(|(_, r)| r.as_message()?.timestamp.as_ref());
+    let first = times.clone().min().map(|t| t.as_str());
+    let last = times.max().map(|t| t.as_str());
+    tx.execute(
+        "INSERT OR REPLACE INTO sessions (session_id, project, path, size_bytes, mtime, first_ts, last_ts, message_count)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            file.session_id,
+            file.project_name,
+            file.path.display().to_string(),
+            file.size_bytes,
+            mtime,
+            first,
+            last,
+            records.len(),
+        ],
+    )?;
+
+    Ok((records.len(), tool_count))
+}
+
+// ── query ──────────────────────────────────────────────────────────────────
+
+pub fn query<W: Write>(opts: &DbQueryOpts, em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let path = db_path(opts.db.as_deref())?;
+    anyhow::ensure!(path.exists(), "no database at {} — run `smc db sync` first", path.display());
+    let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("cannot open {}", path.display()))?;
+
+    let mut stmt = conn.prepare(&opts.sql)?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = stmt.query([])?;
+
+    let mut count = 0usize;
+    while let Some(row) = rows.next()? {
+        let mut values = serde_json::Map::new();
+        for (i, name) in columns.iter().enumerate() {
+            values.insert(name.clone(), to_json(row.get_ref(i)?));
+        }
+        if !em.emit(&RowRecord { record_type: "row", values })? {
+            break;
+        }
+        count += 1;
+    }
+
+    em.emit(&crate::output::SummaryRecord {
+        record_type: "summary",
+        count,
+        files_scanned: None,
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn db_path(path: Option<&str>) -> Result<PathBuf> {
+    match path {
+        Some(p) => Ok(PathBuf::from(p)),
+        None => Ok(state::smc_home()?.join("smc.db")),
+    }
+}
+
+fn to_json(v: ValueRef) -> serde_json::Value {
+    match v {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()).into(),
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    const USER: &str = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"hi"}}"#;
+
+    /// One API response split over two lines, both carrying its usage.
+    fn response(id: &str) -> String {
+        [
+            format!(
+                r#"{{"type":"assistant","timestamp":"2026-01-01T00:00:01Z","message":{{"id":"{}","role":"assistant","model":"m","content":[{{"type":"text","text":"ok"}}],"usage":{{"input_tokens":3,"output_tokens":5}}}}}}"#,
+                id
+            ),
+            format!(
+                r#"{{"type":"assistant","timestamp":"2026-01-01T00:00:02Z","message":{{"id":"{}","role":"assistant","model":"m","content":[{{"type":"tool_use","id":"t1","name":"Bash","input":{{"command":"ls"}}}}],"usage":{{"input_tokens":3,"output_tokens":5}}}}}}"#,
+                id
+            ),
+        ]
+        .join("\n")
+    }
+
+    fn sync_once(db: &str, files: &[SessionFile]) -> serde_json::Value {
+        let opts = DbSyncOpts { db: Some(db.into()), max_tokens: 0 };
+        let mut em = Emitter::capturing(0);
+        sync(&opts, files, &mut em).unwrap();
+        em.into_records().into_iter().find(|r| r["type"] == "db_sync").unwrap()
+    }
+
+    fn count(db: &str, table: &str) -> i64 {
+        let conn = Connection::open(db).unwrap();
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0)).unwrap()
+    }
+
+    #[test]
+    fn sync_is_incremental_and_drops_deleted_sessions() {
+        let dir = TempDir::new("db-sync");
+        let db = dir.join("smc.db").display().to_string();
+        let files = vec![
+            dir.session("s1", "p", &format!("{}\n{}\n", USER, response("r1"))),
+            dir.session("s2", "p", &format!("{}\n{}\n", USER, response("r2"))),
+        ];
+
+        let done = sync_once(&db, &files);
+        assert_eq!((done["updated"].as_u64(), done["messages"].as_u64()), (Some(2), Some(6)));
+        assert_eq!(done["tool_calls"], 2);
+        assert_eq!(count(&db, "sessions"), 2);
+        assert_eq!(count(&db, "messages"), 6);
+        assert_eq!(count(&db, "tool_calls"), 2);
+        // One usage row per response, not per line.
+        assert_eq!(count(&db, "usage"), 2);
+
+        let done = sync_once(&db, &files);
+        assert_eq!((done["updated"].as_u64(), done["unchanged"].as_u64()), (Some(0), Some(2)));
+        assert_eq!(count(&db, "messages"), 6);
+
+        std::fs::remove_file(&files[1].path).unwrap();
+        let done = sync_once(&db, &files[..1]);
+        assert_eq!((done["removed"].as_u64(), done["updated"].as_u64()), (Some(1), Some(0)));
+        for table in ["sessions", "messages", "tool_calls", "usage"] {
+            let conn = Connection::open(&db).unwrap();
+            let orphans: i64 = conn
+                .query_row(&format!("SELECT COUNT(*) FROM {} WHERE session_id = 's2'", table), [], |r| r.get(0))
+                .unwrap();
+            assert_eq!(orphans, 0, "{}", table);
+        }
+        assert_eq!(count(&db, "messages"), 3);
+    }
+}
@@ -0,0 +1,384 @@
+/// smc digest — markdown summary of activity since the previous run.
+///
+/// Writes `<out>/<YYYY-MM-DD>.md` (local date; later runs on the same day
+/// append) listing sessions with new messages, grouped by project, the
+/// files they changed, and API errors. With `--since last-run` the window
+/// starts where the previous digest ended (`~/.smc/digest.json`), or 24h
+/// ago on the first run — suitable for cron or a SessionEnd hook. Only an
+/// unfiltered `last-run` digest moves that marker, so a one-off digest of
+/// one project doesn't swallow the others' activity.
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cmd::errors;
+use crate::models::{ContentBlock, MessageContent, Record, UserKind};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::{state, text, time};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DigestOpts {
+    /// `last-run`, or a date bound (`YYYY-MM-DD` / RFC 3339).
+    pub since: String,
+    /// Directory the dated markdown files go in.
+    pub out: PathBuf,
+    pub project: Option<String>,
+    /// `files` is a subset of the corpus (`--filter`, `--max-file-size`, stdin).
+    pub narrowed: bool,
+    /// Don't write the digest or move the last-run marker.
+    pub dry_run: bool,
+}
+
+impl DigestOpts {
+    /// Whether this run's window ends the next `last-run` digest's: only
+    /// when it continued from the marker over every session.
+    fn moves_marker(&self) -> bool {
+        !self.dry_run && self.since == "last-run" && self.project.is_none() && !self.narrowed
+    }
+}
+
+const STATE_FILE: &str = "digest.json";
+
+/// Max chars of each session's first-prompt preview.
+const PREVIEW_CHARS: usize = 120;
+
+/// Tools whose `file_path` / `notebook_path` counts as a changed file.
+pub const WRITE_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+// ── State ──────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct DigestState {
+    /// End of the window covered by the previous digest (RFC 3339).
+    #[serde(default)]
+    last_run: Option<String>,
+}
+
+impl DigestState {
+    fn path() -> Result<PathBuf> {
+        Ok(state::smc_home()?.join(STATE_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).with_context(|| format!("cannot parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("cannot write {}", path.display()))
+    }
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct DigestRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    /// Markdown file written (absent when there was nothing new, or `--dry-run`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    since: String,
+    until: String,
+    sessions: usize,
+    new_sessions: usize,
+    messages: usize,
+    files_changed: usize,
+    errors: usize,
+    elapsed_ms: u128,
+}
+
+/// One session's activity inside the window.
+#[derive(Debug, Default)]
+struct SessionActivity {
+    project: String,
+    session_id: String,
+    /// The session started inside the window.
+    new: bool,
+    messages: usize,
+    prompts: usize,
+    preview: Option<String>,
+    files: BTreeSet<String>,
+    errors: BTreeMap<String, usize>,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &DigestOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let until = Utc::now();
+    let mut st = DigestState::load()?;
+    let since = match opts.since.as_str() {
+        "last-run" => match &st.last_run {
+            Some(s) => time::parse_bound(s)?,
+            None => until - chrono::Duration::hours(24),
+        },
+        s => time::parse_bound(s)?,
+    };
+    let since_secs = since.timestamp().max(0) as u64;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        // Untouched since the window opened: nothing new inside.
+        .filter(|f| state::mtime_secs(&f.path) >= since_secs)
+        .collect();
+
+    let mut sessions: Vec<SessionActivity> = filtered
+        .par_iter()
+        .filter_map(|file| scan_file(file, since, until))
+        .collect();
+    sessions.sort_by(|a, b| a.project.cmp(&b.project).then(b.messages.cmp(&a.messages)));
+
+    let mut rec = DigestRecord {
+        record_type: "digest",
+        path: None,
+        since: since.to_rfc3339_opts(SecondsFormat::Secs, true),
+        until: until.to_rfc3339_opts(SecondsFormat::Secs, true),
+        sessions: sessions.len(),
+        new_sessions: sessions.iter().filter(|s| s.new).count(),
+        messages: sessions.iter().map(|s| s.messages).sum(),
+        files_changed: sessions.iter().flat_map(|s| &s.files).collect::<BTreeSet<_>>().len(),
+        errors: sessions.iter().flat_map(|s| s.errors.values()).sum(),
+        elapsed_ms: 0,
+    };
+
+    if !opts.dry_run && !sessions.is_empty() {
+        std::fs::create_dir_all(&opts.out)
+            .with_context(|| format!("cannot create {}", opts.out.display()))?;
+        let path = opts.out.join(format!("{}.md", until.with_timezone(&Local).format("%Y-%m-%d")));
+        let mut f = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("cannot open {}", path.display()))?;
+        let existing = f.metadata().map(|m| m.len()).unwrap_or(0);
+        let body = render(&sessions, since, until);
+        let body = if existing > 0 { format!("\n---\n\n{}", body) } else { body };
+        f.write_all(body.as_bytes()).with_context(|| format!("cannot write {}", path.display()))?;
+        rec.path = Some(path.display().to_string());
+    }
+    if opts.moves_marker() {
+        st.last_run = Some(rec.until.clone());
+        st.save()?;
+    }
+
+    rec.elapsed_ms = start.elapsed().as_millis();
+    em.emit(&rec)?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Per-file scan ──────────────────────────────────────────────────────────
+
+fn scan_file(file: &SessionFile, since: DateTime<Utc>, until: DateTime<Utc>) -> Option<SessionActivity> {
+    let f = std::fs::File::open(&file.path).ok()?;
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+
+    let mut act = SessionActivity {
+        project: file.project_name.clone(),
+        session_id: file.session_id.clone(),
+        new: true,
+        ..Default::default()
+    };
+
+    for line in reader.lines() {
+        let Ok(line) = line else { continue };
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+        let ts = match &record {
+            Record::ApiError(e) => e.timestamp.as_ref().and_then(|t| t.utc),
+            r => r.as_message().and_then(|m| m.time()),
+        };
+        let Some(ts) = ts else { continue };
+        if ts < since {
+            act.new = false;
+            continue;
+        }
+        if ts >= until {
+            continue;
+        }
+
+        if let Record::ApiError(e) = &record {
+            *act.errors.entry(e.kind()).or_default() += 1;
+            continue;
+        }
+        let Some(msg) = record.as_message() else { continue };
+        if msg.is_api_error_message {
+            *act.errors.entry(errors::message_kind(&msg.text_content())).or_default() += 1;
+            continue;
+        }
+        act.messages += 1;
+
+        if msg.user_kind() == Some(UserKind::Human) {
+            act.prompts += 1;
+            if act.preview.is_none() {
+                act.preview = Some(text::oneline(msg.text_no_thinking().trim(), PREVIEW_CHARS));
+            }
+        }
+        if let MessageContent::Blocks(blocks) = &msg.message.content {
+            for block in blocks {
+                let ContentBlock::ToolUse { name, input, .. } = block else { continue };
+                if !WRITE_TOOLS.contains(&name.as_str()) {
+                    continue;
+                }
+                if let Some(p) = input.get("file_path").or_else(|| input.get("notebook_path")).and_then(Value::as_str) {
+                    act.files.insert(p.to_string());
+                }
+            }
+        }
+    }
+
+    (act.messages > 0 || !act.errors.is_empty()).then_some(act)
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn render(sessions: &[SessionActivity], since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+    const FMT: &str = "%Y-%m-%d %H:%M";
+    let mut md = String::new();
+    let messages: usize = sessions.iter().map(|s| s.messages).sum();
+    let new = sessions.iter().filter(|s| s.new).count();
+    let files: BTreeSet<&String> = sessions.iter().flat_map(|s| &s.files).collect();
+    let mut errors: BTreeMap<&str, usize> = BTreeMap::new();
+    for (kind, n) in sessions.iter().flat_map(|s| &s.errors) {
+        *errors.entry(kind.as_str()).or_default() += n;
+    }
+
+    md.push_str(&format!(
+        "# Claude activity {} → {}\n\n",
+        since.with_timezone(&Local).format(FMT),
+        until.with_timezone(&Local).format(FMT)
+    ));
+    md.push_str(&format!(
+        "{} sessions ({} new) · {} messages · {} files changed · {} API errors\n",
+        sessions.len(),
+        new,
+        messages,
+        files.len(),
+        errors.values().sum::<usize>()
+    ));
+
+    let mut project = None;
+    for s in sessions {
+        if project != Some(&s.project) {
+            md.push_str(&format!("\n## {}\n\n", s.project));
+            project = Some(&s.project);
+        }
+        let short: String = s.session_id.chars().take(8).collect();
+        md.push_str(&format!(
+            "- `{}`{} — {} messages, {} prompts",
+            short,
+            if s.new { " (new)" } else { "" },
+            s.messages,
+            s.prompts
+        ));
+        if let Some(p) = &s.preview {
+            md.push_str(&format!(" — {}", p));
+        }
+        md.push('\n');
+        if !s.files.is_empty() {
+            let list: Vec<String> = s.files.iter().map(|f| format!("`{}`", f)).collect();
+            md.push_str(&format!("  - Changed: {}\n", list.join(", ")));
+        }
+    }
+
+    if !errors.is_empty() {
+        md.push_str("\n## API errors\n\n");
+        for (kind, n) in &errors {
+            md.push_str(&format!("- {} × {}\n", kind, n));
+        }
+    }
+    md
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        time::parse_bound(s).unwrap()
+    }
+
+    #[test]
+    fn counts_only_the_window() {
+        let dir = TempDir::new("digest");
+        let file = dir.session(
+            "s1",
+            "alpha",
+            concat!(
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"old"}}"#, "\n",
+                r#"{"type":"user","timestamp":"2025-01-02T10:00:00Z","message":{"role":"user","content":"fix the build"}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-02T10:01:00Z","message":{"role":"assistant","content":[{"type":"tool_use","name":"Edit","input":{"file_path":"/w/src/lib.rs"}},{"type":"tool_use","name":"Read","input":{"file_path":"/w/README.md"}}]}}"#, "\n",
+                r#"{"type":"system","subtype":"api_error","timestamp":"2025-01-02T10:02:00Z","error":{"status":529,"error":{"type":"error","error":{"type":"overloaded_error"}}}}"#, "\n",
+                r#"{"type":"user","timestamp":"2025-01-03T10:00:00Z","message":{"role":"user","content":"too late"}}"#, "\n",
+            ),
+        );
+
+        let act = scan_file(&file, at("2025-01-02"), at("2025-01-03")).unwrap();
+        assert!(!act.new);
+        assert_eq!(act.messages, 2);
+        assert_eq!(act.prompts, 1);
+        assert_eq!(act.preview.as_deref(), Some("fix the build"));
+        assert_eq!(act.files.iter().collect::<Vec<_>>(), ["/w/src/lib.rs"]);
+        assert_eq!(act.errors.get("overloaded_error"), Some(&1));
+
+        assert!(scan_file(&file, at("2025-01-04"), at("2025-01-05")).is_none());
+    }
+
+    #[test]
+    fn only_unfiltered_last_run_moves_marker() {
+        let opts = |since: &str, project: Option<&str>, narrowed| DigestOpts {
+            since: since.into(),
+            out: PathBuf::new(),
+            project: project.map(String::from),
+            narrowed,
+            dry_run: false,
+        };
+        assert!(opts("last-run", None, false).moves_marker());
+        assert!(!opts("last-run", Some("alpha"), false).moves_marker());
+        assert!(!opts("last-run", None, true).moves_marker());
+        assert!(!opts("2025-01-01", None, false).moves_marker());
+        assert!(!DigestOpts { dry_run: true, ..opts("last-run", None, false) }.moves_marker());
+    }
+
+    #[test]
+    fn renders_projects_and_errors() {
+        let s = SessionActivity {
+            project: "alpha".into(),
+            session_id: "aaaa1111-0000".into(),
+            new: true,
+            messages: 3,
+            prompts: 1,
+            preview: Some("fix the build".into()),
+            files: ["/w/src/lib.rs".to_string()].into(),
+            errors: [("overloaded_error".to_string(), 2)].into(),
+        };
+        let md = render(&[s], at("2025-01-02"), at("2025-01-03"));
+        assert!(md.contains("1 sessions (1 new) · 3 messages · 1 files changed · 2 API errors"));
+        assert!(md.contains("## alpha\n\n- `aaaa1111` (new) — 3 messages, 1 prompts — fix the build\n"));
+        assert!(md.contains("  - Changed: `/w/src/lib.rs`\n"));
+        assert!(md.contains("- overloaded_error × 2\n"));
+    }
+}
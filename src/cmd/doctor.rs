@@ -0,0 +1,264 @@
+/// smc doctor — integrity check over the whole session tree.
+///
+/// Finds unreadable files, truncated final lines, JSON parse failures,
+/// sessions without messages, duplicate session IDs, and clock-skewed
+/// timestamps. Only a summary is emitted unless `verbose` is set.
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::models::Record;
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DoctorOpts {
+    pub project: Option<String>,
+    /// Emit a record per unhealthy file, not just the summary.
+    pub verbose: bool,
+    pub max_tokens: usize,
+}
+
+/// Timestamps this far past "now" are flagged as from the future.
+const FUTURE_SLACK_MINUTES: i64 = 5;
+/// A timestamp this far behind the latest one already seen is a regression.
+const REGRESSION_SLACK_MINUTES: i64 = 10;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct Issue {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    count: usize,
+    detail: String,
+}
+
+#[derive(Serialize, Debug)]
+struct FileReport {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    session_id: String,
+    file: String,
+    issues: Vec<Issue>,
+}
+
+#[derive(Serialize, Debug)]
+struct DoctorSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    files_scanned: usize,
+    healthy: usize,
+    files_with_issues: usize,
+    issues: BTreeMap<&'static str, usize>,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &DoctorOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let now = Utc::now();
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let mut reports: Vec<FileReport> = filtered.par_iter().map(|file| check_file(file, now)).collect();
+
+    let mut by_id: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, f) in filtered.iter().enumerate() {
+        by_id.entry(f.session_id.as_str()).or_default().push(i);
+    }
+    for idxs in by_id.values().filter(|v| v.len() > 1) {
+        for &i in idxs {
+            let others: Vec<String> = idxs
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| filtered[j].path.display().to_string())
+                .collect();
+            reports[i].issues.push(Issue {
+                kind: "duplicate_session_id",
+                line: None,
+                count: others.len(),
+                detail: format!("also at {}", others.join(", ")),
+            });
+        }
+    }
+
+    let mut issues: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut unhealthy = 0usize;
+    let mut budget_left = true;
+    for report in &reports {
+        if report.issues.is_empty() {
+            continue;
+        }
+        unhealthy += 1;
+        for issue in &report.issues {
+            *issues.entry(issue.kind).or_default() += issue.count;
+        }
+        if opts.verbose && budget_left {
+            budget_left = em.emit(report)?;
+        }
+    }
+
+    em.emit(&DoctorSummary {
+        record_type: "summary",
+        files_scanned: reports.len(),
+        healthy: reports.len() - unhealthy,
+        files_with_issues: unhealthy,
+        issues,
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Per-file checks ────────────────────────────────────────────────────────
+
+fn check_file(file: &SessionFile, now: DateTime<Utc>) -> FileReport {
+    let mut report = FileReport {
+        record_type: "doctor_file",
+        project: file.project_name.clone(),
+        session_id: file.session_id.clone(),
+        file: file.path.display().to_string(),
+        issues: Vec::new(),
+    };
+
+    let bytes = match std::fs::read(&file.path) {
+        Ok(b) => b,
+        Err(e) => {
+            report.issues.push(Issue { kind: "unreadable", line: None, count: 1, detail: e.to_string() });
+            return report;
+        }
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = text.lines().collect();
+    let unterminated = !bytes.is_empty() && !bytes.ends_with(b"\n");
+
+    let mut parse = Tally::default();
+    let mut future = Tally::default();
+    let mut regress = Tally::default();
+    let mut messages = 0usize;
+    let mut latest: Option<DateTime<Utc>> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = match serde_json::from_str::<Record>(line) {
+            Ok(r) => r,
+            Err(e) if unterminated && i + 1 == lines.len() => {
+                report.issues.push(Issue {
+                    kind: "truncated_last_line",
+                    line: Some(i + 1),
+                    count: 1,
+                    detail: e.to_string(),
+                });
+                continue;
+            }
+            Err(e) => {
+                parse.add(i + 1, || e.to_string());
+                continue;
+            }
+        };
+
+        let Some(msg) = record.as_message() else { continue };
+        messages += 1;
+        let Some(t) = msg.time() else { continue };
+        if t > now + Duration::minutes(FUTURE_SLACK_MINUTES) {
+            future.add(i + 1, || format!("{} is in the future", t.to_rfc3339()));
+        }
+        match latest {
+            Some(l) if t < l - Duration::minutes(REGRESSION_SLACK_MINUTES) => {
+                regress.add(i + 1, || format!("{} is earlier than {}", t.to_rfc3339(), l.to_rfc3339()));
+            }
+            Some(l) if t <= l => {}
+            _ => latest = Some(t),
+        }
+    }
+
+    parse.push("parse_error", &mut report.issues);
+    future.push("future_timestamp", &mut report.issues);
+    regress.push("timestamp_regression", &mut report.issues);
+    if messages == 0 {
+        report.issues.push(Issue {
+            kind: "no_messages",
+            line: None,
+            count: 1,
+            detail: format!("{} lines, no user/assistant/system messages", lines.len()),
+        });
+    }
+    report
+}
+
+/// Occurrence count plus the first line and detail, for repeated issues.
+#[derive(Default)]
+struct Tally {
+    count: usize,
+    first: Option<(usize, String)>,
+}
+
+impl Tally {
+    fn add(&mut self, line: usize, detail: impl FnOnce() -> String) {
+        self.count += 1;
+        if self.first.is_none() {
+            self.first = Some((line, detail()));
+        }
+    }
+
+    fn push(self, kind: &'static str, issues: &mut Vec<Issue>) {
+        if let Some((line, detail)) = self.first {
+            issues.push(Issue { kind, line: Some(line), count: self.count, detail });
+        }
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn check(body: &str) -> Vec<&'static str> {
+        let dir = TempDir::new("doctor");
+        let file = dir.session("s", "p", body);
+        check_file(&file, Utc::now()).issues.iter().map(|i| i.kind).collect()
+    }
+
+    const USER: &str = r#"{"type":"user","message":{"role":"user","content":"hi"},"timestamp":"2025-01-01T10:00:00Z"}"#;
+
+    #[test]
+    fn healthy_file() {
+        assert!(check(&format!("{}\n", USER)).is_empty());
+    }
+
+    #[test]
+    fn truncated_vs_parse_error() {
+        assert_eq!(check(&format!("{}\n{{oops\n{}\n{{\"type\":\"us", USER, USER)), ["truncated_last_line", "parse_error"]);
+    }
+
+    #[test]
+    fn skew_and_empty() {
+        let late = USER.replace("2025-01-01T10", "2025-01-01T12");
+        assert_eq!(check(&format!("{}\n{}\n", late, USER)), ["timestamp_regression"]);
+        assert_eq!(check("{\"type\":\"progress\"}\n"), ["no_messages"]);
+        let future = USER.replace("2025", "2999");
+        assert_eq!(check(&format!("{}\n", future)), ["future_timestamp"]);
+    }
+}
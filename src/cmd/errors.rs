@@ -0,0 +1,210 @@
+/// smc errors — list API error / retry events across all sessions.
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::models::{Record, Timestamp};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::time::{self, DateRange};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ErrorsOpts {
+    pub limit: usize,
+    pub project: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct ErrorEvent {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    session_id: String,
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    /// `retry` for system api_error events, `message` for synthetic
+    /// assistant "API Error: …" messages.
+    source: &'static str,
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_attempt: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_in_ms: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+struct ErrorsSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    count: usize,
+    total: usize,
+    files_scanned: usize,
+    kinds: BTreeMap<String, usize>,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ErrorsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let range = time::parse_range(opts.after.as_deref(), opts.before.as_deref())?;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let mut events: Vec<ErrorEvent> = filtered
+        .par_iter()
+        .flat_map_iter(|file| scan_file(file, range))
+        .collect();
+
+    let mut kinds: BTreeMap<String, usize> = BTreeMap::new();
+    for ev in &events {
+        *kinds.entry(ev.kind.clone()).or_default() += 1;
+    }
+
+    events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    let show = if opts.limit > 0 { opts.limit.min(events.len()) } else { events.len() };
+    let mut count = 0usize;
+    for ev in events.iter().take(show) {
+        if !em.emit(ev)? {
+            break;
+        }
+        count += 1;
+    }
+
+    let summary = ErrorsSummary {
+        record_type: "summary",
+        count,
+        total: events.len(),
+        files_scanned: filtered.len(),
+        kinds,
+        elapsed_ms: start.elapsed().as_millis(),
+    };
+    em.emit(&summary)?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Per-file scan ──────────────────────────────────────────────────────────
+
+fn scan_file(file: &SessionFile, (after, before): DateRange) -> Vec<ErrorEvent> {
+    let mut events = Vec::new();
+
+    let Ok(f) = std::fs::File::open(&file.path) else { return events };
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+
+    for (line_num, line) in reader.lines().enumerate() {
+        let Ok(line) = line else { continue };
+        // Cheap pre-filter: skip full deserialization for ordinary lines.
+        if !line.contains("api_error") && !line.contains("isApiErrorMessage") {
+            continue;
+        }
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+
+        let ev = match record {
+            Record::ApiError(e) => ErrorEvent {
+                record_type: "api_error",
+                project: file.project_name.clone(),
+                session_id: file.session_id.clone(),
+                line: line_num + 1,
+                kind: e.kind(),
+                status: e.status(),
+                message: e.message(),
+                retry_attempt: e.retry_attempt,
+                max_retries: e.max_retries,
+                retry_in_ms: e.retry_in_ms,
+                timestamp: e.timestamp,
+                source: "retry",
+            },
+            Record::Assistant(m) if m.is_api_error_message => {
+                let text = m.text_content();
+                ErrorEvent {
+                    record_type: "api_error",
+                    project: file.project_name.clone(),
+                    session_id: file.session_id.clone(),
+                    line: line_num + 1,
+                    kind: message_kind(&text),
+                    status: message_status(&text),
+                    message: Some(text.chars().take(300).collect()),
+                    retry_attempt: None,
+                    max_retries: None,
+                    retry_in_ms: None,
+                    timestamp: m.timestamp,
+                    source: "message",
+                }
+            }
+            _ => continue,
+        };
+
+        if time::in_range(ev.timestamp.as_ref().and_then(|t| t.utc), after, before) {
+            events.push(ev);
+        }
+    }
+
+    events
+}
+
+/// Pull `"type":"…_error"` out of an "API Error: 529 {…}" message body.
+pub fn message_kind(text: &str) -> String {
+    if let Some(i) = text.find("_error\"") {
+        if let Some(q) = text[..i].rfind('"') {
+            return format!("{}_error", &text[q + 1..i]);
+        }
+    }
+    match message_status(text) {
+        Some(s) => format!("http_{}", s),
+        None => "unknown".into(),
+    }
+}
+
+/// Status code following "API Error: ".
+fn message_status(text: &str) -> Option<u64> {
+    let rest = text.split("API Error: ").nth(1)?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_from_embedded_json() {
+        let text = r#"API Error: 529 {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert_eq!(message_kind(text), "overloaded_error");
+        assert_eq!(message_status(text), Some(529));
+    }
+
+    #[test]
+    fn kind_falls_back_to_status() {
+        assert_eq!(message_kind("API Error: 500 Internal server error"), "http_500");
+        assert_eq!(message_kind("API Error: Request timed out."), "unknown");
+    }
+}
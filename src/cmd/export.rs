@@ -1,23 +1,94 @@
 /// smc export — export a session as markdown.
+///
+/// The page is a template with `{{placeholder}}` fields (see
+/// [`DEFAULT_TEMPLATE`]); `{{front_matter}}` expands to a YAML block with
+/// the session's metadata when front matter is on, so exports drop into
+/// Obsidian-style vaults as queryable notes.
+use std::collections::BTreeMap;
 use std::io::Write;
+use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Serialize;
 
-use crate::models::{ContentBlock, MessageContent};
+use crate::cmd::show::{self, ThinkingMode};
+use crate::cmd::stats;
+use crate::models::{ContentBlock, MessageContent, Record, Timestamp, Usage, UsageDedup, UserKind};
 use crate::output::Emitter;
+use crate::util::{clipboard, diff, plugin, policy, text, time};
 use crate::util::discover::SessionFile;
+use crate::util::redact::{self, Redactor};
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct ExportOpts {
     pub session: String,
     /// Write markdown to stdout (via emitter raw lines).
     pub to_stdout: bool,
-    /// Save markdown to this file path.
+    /// Save markdown to this file path. `{session}`, `{project}`, `{date}`,
+    /// `{branch}`, `{model}`, and `{title}` are filled in from the session;
+    /// missing directories are created.
     pub md_path: Option<String>,
+    /// Scrub secrets from the markdown before it is written.
+    pub redact: Option<Redactor>,
+    /// Put the markdown on the system clipboard, wrapped in smc tags. Without
+    /// --md this replaces the default file write.
+    pub copy: bool,
+    /// Copy the markdown without the smc tags.
+    pub no_tag: bool,
+    /// External command the raw session records are piped through (JSONL in,
+    /// JSONL out) before rendering.
+    pub post_filter: Option<String>,
+    /// Start the page with YAML front matter.
+    pub front_matter: bool,
+    /// End the page with a fenced JSON block of session numbers, for tools
+    /// that index exported notes.
+    pub stats_footer: bool,
+    /// Template file to render instead of [`DEFAULT_TEMPLATE`].
+    pub template: Option<PathBuf>,
+    /// Tags listed in the front matter.
+    pub tags: Vec<String>,
+    /// `Collapse` renders thinking as a `<details>` block, `Inline` as a
+    /// blockquote, `Omit` leaves it out.
+    pub thinking: ThinkingMode,
+    /// Rewrap prose lines at this many columns (0 = leave them alone).
+    pub wrap: usize,
+    pub tool_results: ToolResultOpts,
 }
 
+/// How much of each tool result goes into the page.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ToolResultOpts {
+    /// Chars kept per result, cut at a line end (0 = keep everything).
+    pub limit: usize,
+    /// Write each truncated result in full to `<dir>/<tool_use_id>.txt`.
+    pub files: Option<PathBuf>,
+}
+
+impl Default for ToolResultOpts {
+    fn default() -> Self {
+        Self { limit: 2000, files: None }
+    }
+}
+
+/// The full text of a truncated tool result, bound for
+/// [`ToolResultOpts::files`].
+#[derive(Debug)]
+pub struct Sidecar {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// Page layout. Placeholders: `{{front_matter}}`, `{{session_id}}`,
+/// `{{project}}`, `{{size}}`, `{{created}}`, `{{updated}}`, `{{branch}}`,
+/// `{{model}}`, `{{title}}`, `{{tokens}}`, `{{message_count}}`, `{{tags}}`,
+/// `{{messages}}`.
+pub const DEFAULT_TEMPLATE: &str =
+    "{{front_matter}}# Session: {{session_id}}\n\n**Project:** {{project}}  \n**Size:** {{size}}\n\n---\n\n{{messages}}";
+
 // ── Records ────────────────────────────────────────────────────────────────
 
 #[derive(Serialize, Debug)]
@@ -28,28 +99,123 @@ struct ExportDone {
     project: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     output_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    clipboard: Option<&'static str>,
+    /// Full tool results written next to the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_result_files: Option<usize>,
+    messages: usize,
+}
+
+/// The object in the `--stats-footer` block.
+#[derive(Serialize, Debug)]
+struct SessionStats<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: &'a str,
+    project: &'a str,
+    created: Option<&'a str>,
+    updated: Option<&'a str>,
+    duration_secs: Option<i64>,
     messages: usize,
+    messages_by_role: BTreeMap<&'a str, usize>,
+    tokens: u64,
+    usage: Usage,
+    tool_calls: usize,
+    tools: BTreeMap<&'a str, usize>,
 }
 
 // ── run ────────────────────────────────────────────────────────────────────
 
 pub fn run<W: Write>(opts: &ExportOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
-    let records = crate::cmd::parse_records(file)?;
+    let records = match &opts.post_filter {
+        Some(cmd) => filtered_records(file, cmd, em)?,
+        None => crate::cmd::parse_records(file)?,
+    };
 
-    let mut md = String::new();
-    md.push_str(&format!(
-        "# Session: {}\n\n**Project:** {}  \n**Size:** {}\n\n---\n\n",
-        file.session_id, file.project_name, file.size_human()
-    ));
+    let template = match &opts.template {
+        Some(p) => std::fs::read_to_string(p).with_context(|| format!("cannot read template {}", p.display()))?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let meta = Meta::collect(file, &records, &opts.tags);
+    let mut fields = meta.fields();
+    fields.insert("front_matter", if opts.front_matter { meta.front_matter() } else { String::new() });
+    let (messages, sidecars) = render_messages(&records, opts.thinking, &opts.tool_results);
+    fields.insert("messages", messages);
+    let mut md = render(&template, &fields);
+    if opts.stats_footer {
+        md.push_str(&stats_footer(&meta, &records));
+    }
+    let msg_count = meta.messages;
 
-    let mut msg_count = 0usize;
+    let md = redact::apply(opts.redact.as_ref(), &md);
+    let md = wrap(&md, opts.wrap);
 
-    for record in &records {
-        let Some(msg) = record.as_message() else { continue };
-        msg_count += 1;
+    if let Some(dir) = &opts.tool_results.files {
+        std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    }
+    for sidecar in &sidecars {
+        std::fs::write(&sidecar.path, redact::apply(opts.redact.as_ref(), &sidecar.text).as_bytes())
+            .with_context(|| format!("cannot write {}", sidecar.path.display()))?;
+    }
+
+    // write markdown
+    if opts.to_stdout {
+        // Emit as raw lines so it's readable markdown, not JSON-wrapped
+        for line in md.lines() {
+            em.raw(line)?;
+        }
+    }
+
+    let clipboard = if opts.copy { Some(clipboard::copy(&policy::clipboard_text(&md, opts.no_tag))?) } else { None };
+
+    let output_file = if let Some(p) = &opts.md_path {
+        let path = output_path(p, &meta);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+        }
+        std::fs::write(&path, md.as_bytes()).with_context(|| format!("cannot write {}", path.display()))?;
+        Some(path.display().to_string())
+    } else if !opts.to_stdout && !opts.copy {
+        let path = format!("{}.md", &file.session_id[..8.min(file.session_id.len())]);
+        std::fs::write(&path, md.as_bytes())?;
+        Some(path)
+    } else {
+        None
+    };
+
+    if !opts.to_stdout {
+        let done = ExportDone {
+            record_type: "export",
+            session_id: file.session_id.clone(),
+            project: file.project_name.clone(),
+            output_file,
+            clipboard,
+            tool_result_files: opts.tool_results.files.as_ref().map(|_| sidecars.len()),
+            messages: msg_count,
+        };
+        em.emit(&done)?;
+    }
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
 
+/// The conversation part of the page (`{{messages}}`), and the full text of
+/// every tool result cut short when `results.files` is set.
+pub fn render_messages(
+    records: &[Record],
+    thinking: ThinkingMode,
+    results: &ToolResultOpts,
+) -> (String, Vec<Sidecar>) {
+    let mut md = String::new();
+    let mut sidecars = Vec::new();
+    for record in records {
+        let Some(msg) = record.as_message() else { continue };
         let role = record.role();
-        let ts = msg.timestamp.as_deref().unwrap_or("unknown");
+        let ts = msg.timestamp.as_ref().map_or("unknown", Timestamp::as_str);
         let ts_short = ts.get(..19).unwrap_or(ts);
 
         md.push_str(&format!("## {} ({})\n\n", role.to_uppercase(), ts_short));
@@ -66,21 +232,50 @@ pub fn run<W: Write>(opts: &ExportOpts, file: &SessionFile, em: &mut Emitter<W>)
                             md.push_str(text);
                             md.push_str("\n\n");
                         }
-                        ContentBlock::Thinking { thinking } => {
-                            md.push_str(&format!(
+                        ContentBlock::Thinking { thinking: t } => match thinking {
+                            ThinkingMode::Collapse => md.push_str(&format!(
                                 "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n\n",
-                                thinking
-                            ));
-                        }
+                                t
+                            )),
+                            ThinkingMode::Inline => {
+                                md.push_str(&show::blockquote(t));
+                                md.push_str("\n\n");
+                            }
+                            ThinkingMode::Omit => {}
+                        },
                         ContentBlock::ToolUse { name, input, .. } => {
+                            if let Some(d) = diff::edit_diff(name, input) {
+                                md.push_str(&format!("**Tool: {}**\n```diff\n{}\n```\n\n", name, d));
+                                continue;
+                            }
                             let pretty = serde_json::to_string_pretty(input)
                                 .unwrap_or_else(|_| input.to_string());
                             md.push_str(&format!("**Tool: {}**\n```json\n{}\n```\n\n", name, pretty));
                         }
-                        ContentBlock::ToolResult { content: Some(c), .. } => {
-                            let s = c.to_string();
-                            let preview: String = s.chars().take(2000).collect();
-                            md.push_str(&format!("**Result:**\n```\n{}\n```\n\n", preview));
+                        ContentBlock::ToolResult { tool_use_id, content: Some(c), .. } => {
+                            let text = result_text(c);
+                            let Some(kept) = truncate_lines(&text, results.limit) else {
+                                md.push_str(&format!("**Result:**\n```\n{}\n```\n\n", text.trim_end()));
+                                continue;
+                            };
+                            let omitted = stats::format_bytes((text.len() - kept.len()) as u64);
+                            md.push_str(&format!("**Result:**\n```\n{}\n```\n\n", kept.trim_end()));
+                            match &results.files {
+                                Some(dir) => {
+                                    let name = tool_use_id.as_deref().map_or_else(
+                                        || format!("result-{}", sidecars.len() + 1),
+                                        |id| id.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-', "_"),
+                                    );
+                                    let path = dir.join(format!("{}.txt", name));
+                                    md.push_str(&format!(
+                                        "*(truncated, {} omitted; full result: {})*\n\n",
+                                        omitted,
+                                        path.display()
+                                    ));
+                                    sidecars.push(Sidecar { path, text });
+                                }
+                                None => md.push_str(&format!("*(truncated, {} omitted)*\n\n", omitted)),
+                            }
                         }
                         _ => {}
                     }
@@ -90,37 +285,495 @@ pub fn run<W: Write>(opts: &ExportOpts, file: &SessionFile, em: &mut Emitter<W>)
 
         md.push_str("---\n\n");
     }
+    (md, sidecars)
+}
 
-    // write markdown
-    if opts.to_stdout {
-        // Emit as raw lines so it's readable markdown, not JSON-wrapped
-        for line in md.lines() {
-            em.raw(line)?;
+/// A tool result as text: the string itself, or the text parts of a block
+/// list, so line breaks survive instead of showing as `\n` escapes.
+pub(crate) fn result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .map(|p| p.get("text").and_then(serde_json::Value::as_str).map_or_else(|| p.to_string(), String::from))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+/// The leading whole lines of `s` that fit in `limit` chars (a cut inside
+/// the first line if even that is too long), or `None` if all of `s` fits.
+fn truncate_lines(s: &str, limit: usize) -> Option<&str> {
+    if limit == 0 || s.chars().count() <= limit {
+        return None;
+    }
+    let (mut end, mut used) = (0, 0);
+    for line in s.split_inclusive('\n') {
+        used += line.chars().count();
+        if used > limit {
+            break;
         }
+        end += line.len();
+    }
+    if end == 0 {
+        end = s.char_indices().nth(limit).map_or(s.len(), |(i, _)| i);
     }
+    Some(&s[..end])
+}
 
-    let output_file = if let Some(p) = &opts.md_path {
-        std::fs::write(p, &md)?;
-        Some(p.clone())
-    } else if !opts.to_stdout {
-        let path = format!("{}.md", &file.session_id[..8.min(file.session_id.len())]);
-        std::fs::write(&path, &md)?;
-        Some(path)
-    } else {
-        None
+/// The YAML block `--front-matter` writes for `file`.
+pub fn front_matter(file: &SessionFile, records: &[Record], tags: &[String]) -> String {
+    Meta::collect(file, records, tags).front_matter()
+}
+
+/// The `--stats-footer` block: a `json` fence holding a `session_stats`
+/// object with message counts by role, token usage, tool calls by name, and
+/// the first-to-last-message duration.
+fn stats_footer(meta: &Meta, records: &[Record]) -> String {
+    let mut roles: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut usage = Usage::default();
+    let mut dedup = UsageDedup::default();
+    let mut tools: BTreeMap<&str, usize> = BTreeMap::new();
+    for record in records {
+        let Some(msg) = record.as_message() else { continue };
+        *roles.entry(record.role()).or_default() += 1;
+        if let Some(u) = dedup.first(msg) {
+            usage.add(u);
+        }
+        for name in msg.tool_names() {
+            *tools.entry(name).or_default() += 1;
+        }
+    }
+    let time = |t: &Option<String>| t.as_deref().and_then(time::parse_rfc3339);
+    let duration = time(&meta.created).zip(time(&meta.updated)).map(|(a, b)| (b - a).num_seconds().max(0));
+    let stats = SessionStats {
+        record_type: "session_stats",
+        session_id: &meta.session_id,
+        project: &meta.project,
+        created: meta.created.as_deref(),
+        updated: meta.updated.as_deref(),
+        duration_secs: duration,
+        messages: meta.messages,
+        messages_by_role: roles,
+        tokens: usage.total(),
+        usage,
+        tool_calls: tools.values().sum(),
+        tools,
     };
+    let json = serde_json::to_string_pretty(&stats).unwrap_or_default();
+    format!("\n\n```json\n{}\n```\n", json)
+}
 
-    if !opts.to_stdout {
-        let done = ExportDone {
-            record_type: "export",
+/// Replace each `{{key}}` in `template`; unknown placeholders stay as they are.
+fn render(template: &str, fields: &BTreeMap<&str, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}").and_then(|end| Some((end, fields.get(after[..end].trim())?))) {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expand the `{token}` fields of an output path: `{session}` (short ID),
+/// `{project}`, `{date}` (created), `{branch}`, `{model}`, and `{title}`
+/// (first prompt). Values are made filename-safe, so a `/` in a branch
+/// doesn't add a directory; a missing one becomes `unknown`. Unknown tokens
+/// stay as they are. `{project}/{date}-{title}.md` files each session under
+/// its project's directory.
+fn output_path(pattern: &str, meta: &Meta) -> PathBuf {
+    let value = |token: &str| {
+        let v = match token {
+            "session" => Some(&meta.session_id[..8.min(meta.session_id.len())]),
+            "project" => Some(meta.project.as_str()),
+            "date" => meta.created.as_deref().and_then(|c| c.get(..10)),
+            "branch" => meta.branch.as_deref(),
+            "model" => meta.model.as_deref(),
+            "title" => Some(meta.title.as_str()),
+            _ => return None,
+        };
+        let slug = file_slug(v.unwrap_or_default());
+        Some(if slug.is_empty() { "unknown".to_string() } else { slug })
+    };
+    let mut out = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').and_then(|end| Some((end, value(&after[..end])?))) {
+            Some((end, v)) => {
+                out.push_str(&v);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    PathBuf::from(out)
+}
+
+/// Letters, digits, `.`, `_`, and `-` kept; runs of anything else become a
+/// single `-`.
+fn file_slug(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_alphanumeric() || matches!(c, '.' | '_' | '-') {
+            out.push(c);
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches(|c| c == '-' || c == '.').to_string()
+}
+
+/// Max chars of the first prompt kept as `{{title}}`.
+const TITLE_CHARS: usize = 60;
+
+/// Session metadata for the template, front matter, and output path.
+struct Meta {
+    session_id: String,
+    project: String,
+    size: String,
+    created: Option<String>,
+    updated: Option<String>,
+    branch: Option<String>,
+    /// Model that answered most often.
+    model: Option<String>,
+    /// First prompt the user typed, on one line.
+    title: String,
+    tokens: u64,
+    messages: usize,
+    tags: Vec<String>,
+}
+
+impl Meta {
+    fn collect(file: &SessionFile, records: &[Record], tags: &[String]) -> Self {
+        let mut meta = Meta {
             session_id: file.session_id.clone(),
             project: file.project_name.clone(),
-            output_file,
-            messages: msg_count,
+            size: file.size_human(),
+            created: None,
+            updated: None,
+            branch: None,
+            model: None,
+            title: String::new(),
+            tokens: 0,
+            messages: 0,
+            tags: tags.to_vec(),
         };
-        em.emit(&done)?;
+        let mut models: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut dedup = UsageDedup::default();
+        for msg in records.iter().filter_map(Record::as_message) {
+            meta.messages += 1;
+            if let Some(ts) = &msg.timestamp {
+                meta.created.get_or_insert_with(|| ts.as_str().to_string());
+                meta.updated = Some(ts.as_str().to_string());
+            }
+            if meta.title.is_empty() && msg.user_kind() == Some(UserKind::Human) {
+                meta.title = text::oneline(&msg.text_content(), TITLE_CHARS);
+            }
+            if meta.branch.is_none() {
+                meta.branch = msg.git_branch.clone().filter(|b| !b.is_empty());
+            }
+            if let Some(m) = msg.message.model.as_deref().filter(|m| !m.starts_with('<')) {
+                *models.entry(m).or_default() += 1;
+            }
+            if let Some(usage) = dedup.first(msg) {
+                meta.tokens += usage.total();
+            }
+        }
+        meta.model = models.into_iter().max_by_key(|(_, n)| *n).map(|(m, _)| m.to_string());
+        meta
     }
 
-    em.flush()?;
-    Ok(())
+    fn fields(&self) -> BTreeMap<&'static str, String> {
+        let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+        BTreeMap::from([
+            ("session_id", self.session_id.clone()),
+            ("project", self.project.clone()),
+            ("size", self.size.clone()),
+            ("created", opt(&self.created)),
+            ("updated", opt(&self.updated)),
+            ("branch", opt(&self.branch)),
+            ("model", opt(&self.model)),
+            ("title", self.title.clone()),
+            ("tokens", self.tokens.to_string()),
+            ("message_count", self.messages.to_string()),
+            ("tags", self.tags.join(", ")),
+        ])
+    }
+
+    /// YAML block ending in a blank line. Strings are written as JSON
+    /// strings, which YAML reads as double-quoted scalars; timestamps stay
+    /// bare so they parse as dates.
+    fn front_matter(&self) -> String {
+        let q = |s: &str| serde_json::Value::from(s).to_string();
+        let mut y = String::from("---\n");
+        y.push_str(&format!("session_id: {}\n", q(&self.session_id)));
+        y.push_str(&format!("project: {}\n", q(&self.project)));
+        if let Some(t) = &self.created {
+            y.push_str(&format!("created: {}\n", t));
+        }
+        if let Some(t) = &self.updated {
+            y.push_str(&format!("updated: {}\n", t));
+        }
+        if let Some(b) = &self.branch {
+            y.push_str(&format!("branch: {}\n", q(b)));
+        }
+        if let Some(m) = &self.model {
+            y.push_str(&format!("model: {}\n", q(m)));
+        }
+        y.push_str(&format!("tokens: {}\n", self.tokens));
+        y.push_str(&format!("messages: {}\n", self.messages));
+        let tags: Vec<String> = self.tags.iter().map(|t| q(t)).collect();
+        y.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+        y.push_str("---\n\n");
+        y
+    }
+}
+
+/// Break prose lines longer than `width` columns at spaces. Fenced and
+/// indented code, front matter, tables, headings, and HTML lines are left
+/// as they are; list items and blockquotes keep their marker's indent or
+/// `>` on each new line. A word longer than the line stays whole.
+fn wrap(md: &str, width: usize) -> String {
+    if width == 0 {
+        return md.to_string();
+    }
+    let mut out = String::with_capacity(md.len() + md.len() / width);
+    let mut fence: Option<&str> = None;
+    let mut front_matter = md.starts_with("---\n");
+    for (i, line) in md.split_inclusive('\n').enumerate() {
+        let body = line.trim_end_matches('\n');
+        let trimmed = body.trim_start();
+        if front_matter {
+            front_matter = i == 0 || body != "---";
+            out.push_str(line);
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            out.push_str(line);
+            continue;
+        }
+        let verbatim = body.starts_with("    ") || body.starts_with('\t') || trimmed.starts_with(['|', '#', '<']);
+        if verbatim || body.chars().count() <= width {
+            out.push_str(line);
+            continue;
+        }
+        wrap_line(&mut out, body, width);
+        if line.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn wrap_line(out: &mut String, line: &str, width: usize) {
+    // Blockquote markers repeat on every line; a list marker becomes indent.
+    let quote_len = line.len() - line.trim_start_matches(['>', ' ']).len();
+    let (quote, rest) = line.split_at(quote_len);
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker_len = if rest.starts_with("- ") || rest.starts_with("* ") || rest.starts_with("+ ") {
+        2
+    } else if digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) {
+        digits + 2
+    } else {
+        0
+    };
+    let (marker, content) = rest.split_at(marker_len);
+    let indent = format!("{}{}", quote, " ".repeat(marker_len));
+    // Trailing double spaces are a hard line break; keep them on the last line.
+    let text = content.trim_end_matches(' ');
+    let hard_break = &content[text.len()..];
+
+    let avail = width.saturating_sub(indent.chars().count()).max(1);
+    let mut prefix = format!("{}{}", quote, marker);
+    let mut rest = text;
+    while let Some((limit, _)) = rest.char_indices().nth(avail) {
+        let cut = if rest[limit..].starts_with(' ') {
+            Some(limit)
+        } else {
+            rest[..limit].rfind(' ').filter(|&c| c > 0).or_else(|| rest[limit..].find(' ').map(|c| limit + c))
+        };
+        let Some(cut) = cut else { break };
+        out.push_str(&prefix);
+        out.push_str(&rest[..cut]);
+        out.push('\n');
+        rest = &rest[cut + 1..];
+        prefix.clone_from(&indent);
+    }
+    out.push_str(&prefix);
+    out.push_str(rest);
+    out.push_str(hard_break);
+}
+
+fn filtered_records<W: Write>(file: &SessionFile, cmd: &str, em: &mut Emitter<W>) -> Result<Vec<Record>> {
+    let raw: Vec<Box<serde_json::value::RawValue>> = std::fs::read_to_string(&file.path)?
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    let piped = plugin::pipe(cmd, &raw)?;
+    let path = file.path.display().to_string();
+    for w in &piped.warnings {
+        em.warn(Some(&path), w);
+    }
+    let mut records = Vec::with_capacity(piped.records.len());
+    for (i, v) in piped.records.iter().enumerate() {
+        match serde_json::from_str(v.get()) {
+            Ok(r) => records.push(r),
+            Err(e) => em.warn(Some(&path), &format!("plugin '{}' record {} is not a session record ({}), dropped", cmd, i + 1, e)),
+        }
+    }
+    Ok(records)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders() {
+        let fields = BTreeMap::from([("project", "alpha".to_string()), ("tags", String::new())]);
+        assert_eq!(render("# {{project}} [{{ tags }}] {{other}} {{", &fields), "# alpha [] {{other}} {{");
+    }
+
+    #[test]
+    fn expands_output_path_tokens() {
+        let meta = Meta {
+            session_id: "abcd1234-0000".into(),
+            project: "alpha".into(),
+            size: String::new(),
+            created: Some("2025-03-01T10:00:00Z".into()),
+            updated: None,
+            branch: Some("feature/login".into()),
+            model: Some("claude-sonnet-4".into()),
+            title: "fix the deploy script: it's broken!".into(),
+            tokens: 0,
+            messages: 0,
+            tags: Vec::new(),
+        };
+        assert_eq!(
+            output_path("out/{project}/{date}-{branch}-{title}.md", &meta),
+            PathBuf::from("out/alpha/2025-03-01-feature-login-fix-the-deploy-script-it-s-broken.md")
+        );
+        let bare = Meta { branch: None, title: String::new(), ..meta };
+        assert_eq!(output_path("{model}/{branch}/{session}{x}.md", &bare), PathBuf::from("claude-sonnet-4/unknown/abcd1234{x}.md"));
+    }
+
+    #[test]
+    fn stats_footer_is_fenced_json() {
+        let records: Vec<Record> = [
+            r#"{"type":"user","timestamp":"2025-03-01T10:00:00Z","message":{"role":"user","content":"go"}}"#,
+            r#"{"type":"assistant","timestamp":"2025-03-01T10:01:00Z","message":{"id":"msg_1","role":"assistant","usage":{"input_tokens":10,"output_tokens":5},"content":[{"type":"tool_use","name":"Bash","input":{}}]}}"#,
+            r#"{"type":"assistant","timestamp":"2025-03-01T10:01:30Z","message":{"id":"msg_1","role":"assistant","usage":{"input_tokens":10,"output_tokens":5},"content":[{"type":"tool_use","name":"Bash","input":{}}]}}"#,
+        ]
+        .iter()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+        let file = SessionFile { path: "s1.jsonl".into(), session_id: "s1".into(), project_name: "p".into(), size_bytes: 0 };
+        let footer = stats_footer(&Meta::collect(&file, &records, &[]), &records);
+        let json = footer.strip_prefix("\n\n```json\n").and_then(|f| f.strip_suffix("\n```\n")).unwrap();
+        let stats: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(stats["type"], "session_stats");
+        assert_eq!((stats["messages"].as_u64(), stats["messages_by_role"]["user"].as_u64()), (Some(3), Some(1)));
+        assert_eq!((stats["tokens"].as_u64(), stats["usage"]["output_tokens"].as_u64()), (Some(15), Some(5)));
+        assert_eq!((stats["tool_calls"].as_u64(), stats["tools"]["Bash"].as_u64()), (Some(2), Some(2)));
+        assert_eq!(stats["duration_secs"], 90);
+    }
+
+    #[test]
+    fn wraps_prose_but_not_code() {
+        let md = concat!(
+            "---\ntitle: \"a very long front matter value that must stay on one line\"\n---\n\n",
+            "one two three four five six seven eight nine ten\n",
+            "```\nlet code = \"a very long line of code that must not be rewrapped at all\";\n```\n",
+            "- item with quite a few words in it  \n",
+            "> quoted words that run past the edge\n",
+            "| a table row that is long but stays as it is |\n",
+            "https://example.com/a-single-word-longer-than-the-width\n",
+        );
+        let wrapped = wrap(md, 20);
+        let expected = concat!(
+            "---\ntitle: \"a very long front matter value that must stay on one line\"\n---\n\n",
+            "one two three four\nfive six seven eight\nnine ten\n",
+            "```\nlet code = \"a very long line of code that must not be rewrapped at all\";\n```\n",
+            "- item with quite a\n  few words in it  \n",
+            "> quoted words that\n> run past the edge\n",
+            "| a table row that is long but stays as it is |\n",
+            "https://example.com/a-single-word-longer-than-the-width\n",
+        );
+        assert_eq!(wrapped, expected);
+        assert_eq!(wrap(md, 0), md);
+    }
+
+    #[test]
+    fn truncates_tool_results_on_line_ends() {
+        let diff: String = (1..=300).map(|i| format!("+line {}\n", i)).collect();
+        let line = serde_json::json!({"type":"user","message":{"role":"user","content":[
+            {"type":"tool_result","tool_use_id":"toolu_1","content":diff},
+        ]}});
+        let records = vec![serde_json::from_value::<Record>(line).unwrap()];
+
+        let (md, sidecars) = render_messages(&records, ThinkingMode::Omit, &ToolResultOpts::default());
+        assert!(md.contains("+line 1\n+line 2\n"), "escaped: {}", md);
+        assert!(md.contains("+line 210\n```\n\n*(truncated, 900B omitted)*"), "{}", md);
+        assert!(sidecars.is_empty());
+
+        let (md, _) = render_messages(&records, ThinkingMode::Omit, &ToolResultOpts { limit: 0, files: None });
+        assert!(md.contains("+line 300\n```") && !md.contains("truncated"));
+
+        let files = ToolResultOpts { limit: 20, files: Some(PathBuf::from("results")) };
+        let (md, sidecars) = render_messages(&records, ThinkingMode::Omit, &files);
+        assert!(md.contains("```\n+line 1\n+line 2\n```"), "{}", md);
+        assert!(md.contains(&format!("full result: {})*", std::path::Path::new("results").join("toolu_1.txt").display())));
+        assert_eq!(sidecars[0].text, diff);
+
+        assert_eq!(truncate_lines("abcdef", 3), Some("abc"));
+    }
+
+    #[test]
+    fn front_matter_quotes_strings() {
+        let meta = Meta {
+            session_id: "s1".into(),
+            project: "my: app".into(),
+            size: "1KB".into(),
+            created: Some("2025-01-01T10:00:00Z".into()),
+            updated: None,
+            branch: None,
+            model: Some("claude-x".into()),
+            title: String::new(),
+            tokens: 12,
+            messages: 3,
+            tags: vec!["a".into()],
+        };
+        let y = meta.front_matter();
+        assert!(y.starts_with("---\nsession_id: \"s1\"\nproject: \"my: app\"\ncreated: 2025-01-01T10:00:00Z\n"));
+        assert!(y.contains("model: \"claude-x\"\ntokens: 12\nmessages: 3\ntags: [\"a\"]\n---\n\n"));
+        assert!(!y.contains("branch:"));
+    }
 }
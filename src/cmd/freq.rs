@@ -1,6 +1,7 @@
 /// smc freq — frequency analysis across all conversation logs.
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 
@@ -8,25 +9,53 @@ use anyhow::Result;
 use rayon::prelude::*;
 use serde::Serialize;
 
-use crate::models;
+use crate::cmd::{blame, stats};
+use crate::models::{self, RoleFilter, ToolFilter};
 use crate::output::Emitter;
+use crate::util::cache::{FileSummary, SummaryCache};
 use crate::util::discover::SessionFile;
+use crate::util::policy;
+use crate::util::progress::{Progress, Silent};
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct FreqOpts {
     pub mode: FreqMode,
+    /// What `chars` mode counts.
+    pub class: CharClass,
     pub limit: usize,
     pub raw: bool,
+    /// Summary cache file for chars/tools/roles (`None` = parse every file).
+    pub cache: Option<PathBuf>,
+    /// A [`ToolFilter`] spec restricting `tools` mode; percentages are then
+    /// shares of the matching tools only.
+    pub tool: Option<String>,
+    /// Only sessions of projects whose name contains this (any mode).
+    pub project: Option<String>,
+    /// A [`RoleFilter`] spec restricting `words` mode.
+    pub role: Option<String>,
+    /// `words` mode: top terms of each of these projects side by side
+    /// (`word_compare` records) instead of one corpus-wide list.
+    pub compare: Vec<String>,
+    /// Count messages holding smc's own output; see [`policy`]. The summary
+    /// cache always leaves them out, so this forces a full parse.
+    pub include_smc: bool,
     pub max_tokens: usize,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum FreqMode {
+    #[default]
     Chars,
     Words,
     Tools,
     Roles,
+    /// Reads, edits, and writes per file path in tool inputs.
+    Churn,
+    /// Records per top-level JSONL `type`, per project.
+    Types,
 }
 
 impl FreqMode {
@@ -36,11 +65,58 @@ impl FreqMode {
             "words" | "w" => Ok(Self::Words),
             "tools" | "t" => Ok(Self::Tools),
             "roles" | "r" => Ok(Self::Roles),
-            _ => anyhow::bail!("unknown freq mode '{}' — use: chars, words, tools, roles", s),
+            "churn" => Ok(Self::Churn),
+            "types" => Ok(Self::Types),
+            _ => anyhow::bail!("unknown freq mode '{}' — use: chars, words, tools, roles, churn, types", s),
         }
     }
 }
 
+/// Characters `chars` mode counts. `Letters` is the fixed a–z table (and the
+/// only class the summary cache holds); the others emit one ranked record
+/// per character, or per script for `Script`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CharClass {
+    #[default]
+    Letters,
+    Digits,
+    /// ASCII punctuation and symbols.
+    Punct,
+    /// Letters grouped by Unicode script (Latin, Cyrillic, Han, …).
+    Script,
+    Custom(Vec<char>),
+}
+
+impl CharClass {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "letters" => Ok(Self::Letters),
+            "digits" => Ok(Self::Digits),
+            "punct" => Ok(Self::Punct),
+            "unicode-script" => Ok(Self::Script),
+            _ => match s.strip_prefix("custom:") {
+                Some(set) if !set.is_empty() => Ok(Self::Custom(set.chars().collect())),
+                _ => anyhow::bail!(
+                    "unknown char class '{}' — use: letters, digits, punct, unicode-script, custom:<chars>",
+                    s
+                ),
+            },
+        }
+    }
+
+    /// The key `c` is counted under, if this class counts it.
+    fn key(&self, c: char) -> Option<String> {
+        let hit = match self {
+            Self::Letters => c.is_ascii_alphabetic(),
+            Self::Digits => c.is_ascii_digit(),
+            Self::Punct => c.is_ascii_punctuation(),
+            Self::Custom(set) => set.contains(&c),
+            Self::Script => return script(c).map(String::from),
+        };
+        hit.then(|| c.to_string())
+    }
+}
+
 // ── Records ────────────────────────────────────────────────────────────────
 
 #[derive(Serialize, Debug)]
@@ -62,6 +138,59 @@ struct FreqRecord {
     pct: Option<f64>,
 }
 
+/// One rank of `--compare`: each project's term at that rank, in the order
+/// the projects were given.
+#[derive(Serialize, Debug)]
+struct CompareRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    rank: usize,
+    columns: Vec<CompareColumn>,
+}
+
+#[derive(Serialize, Debug)]
+struct CompareColumn {
+    project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    count: u64,
+    pct: f64,
+}
+
+#[derive(Serialize, Debug)]
+struct ChurnRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    path: String,
+    reads: u64,
+    /// Edit, MultiEdit, and NotebookEdit calls.
+    edits: u64,
+    writes: u64,
+    /// Sessions that edited or wrote the file.
+    sessions: u64,
+    /// Edits and writes per read; absent when the file was never read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes_per_read: Option<f64>,
+    /// At least [`HIGH_CHURN`] edits and writes.
+    high_churn: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct TypeFreqRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    /// The record's `type` value; `(none)` when it has none.
+    key: String,
+    count: u64,
+    /// Share of the project's records.
+    pct: f64,
+    /// smc reads these as [`models::Record::Unknown`] (or they have no
+    /// type): a record type it doesn't model yet, or a schema change.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    unknown: bool,
+}
+
 #[derive(Serialize, Debug)]
 struct FreqSummary {
     #[serde(rename = "type")]
@@ -75,15 +204,57 @@ struct FreqSummary {
 // ── run ────────────────────────────────────────────────────────────────────
 
 pub fn run<W: Write>(opts: &FreqOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
-    let start = std::time::Instant::now();
+    run_with_progress(opts, files, em, &Silent)
+}
 
-    match opts.mode {
-        FreqMode::Chars if opts.raw => run_chars_raw(files, em)?,
-        FreqMode::Chars => run_chars_parsed(files, em)?,
-        FreqMode::Words => run_words(files, opts.limit, em)?,
-        FreqMode::Tools => run_tools(files, opts.limit, em)?,
-        FreqMode::Roles => run_roles(files, em)?,
-    }
+/// [`run`], reporting per-file scan progress to `progress`.
+pub fn run_with_progress<W: Write>(
+    opts: &FreqOpts,
+    files: &[SessionFile],
+    em: &mut Emitter<W>,
+    progress: &dyn Progress,
+) -> Result<()> {
+    let start = std::time::Instant::now();
+    let tools = opts.tool.as_deref().map(ToolFilter::parse).transpose()?;
+    anyhow::ensure!(tools.is_none() || opts.mode == FreqMode::Tools, "--tool only applies to tools mode");
+    let roles = opts.role.as_deref().map(RoleFilter::parse).transpose()?;
+    anyhow::ensure!(roles.is_none() || opts.mode == FreqMode::Words, "--role only applies to words mode");
+    anyhow::ensure!(opts.compare.is_empty() || opts.mode == FreqMode::Words, "--compare only applies to words mode");
+    anyhow::ensure!(opts.compare.len() != 1, "--compare needs at least two projects");
+    anyhow::ensure!(
+        opts.class == CharClass::Letters || opts.mode == FreqMode::Chars,
+        "--class only applies to chars mode"
+    );
+
+    let compare = opts
+        .compare
+        .iter()
+        .map(|p| stats::resolve_project(p, files))
+        .collect::<Result<Vec<_>>>()?;
+    let files: Vec<SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(p) => f.project_name.to_lowercase().contains(&p.to_lowercase()),
+            None => compare.is_empty() || compare.contains(&f.project_name),
+        })
+        .cloned()
+        .collect();
+    let files = files.as_slice();
+
+    progress.start(files.len(), files.iter().map(|f| f.size_bytes).sum());
+    let cache = opts.cache.as_ref().map(SummaryCache::open);
+    let result = match (opts.mode, &cache) {
+        (FreqMode::Words, _) if !compare.is_empty() => run_compare(files, &compare, roles.as_ref(), opts.include_smc, progress, opts.limit, em),
+        (FreqMode::Chars, _) if opts.class != CharClass::Letters => {
+            emit_ranked("char_freq", count_class(files, &opts.class, opts.raw, opts.include_smc, progress), opts.limit, em)
+        }
+        (FreqMode::Chars | FreqMode::Tools | FreqMode::Roles, Some(cache)) if !opts.raw && !opts.include_smc => {
+            run_cached(opts, files, cache, tools.as_ref(), progress, em)
+        }
+        _ => run_uncached(opts, files, tools.as_ref(), roles.as_ref(), progress, em),
+    };
+    progress.finish();
+    result?;
 
     let summary = FreqSummary {
         record_type: "summary",
@@ -98,9 +269,72 @@ pub fn run<W: Write>(opts: &FreqOpts, files: &[SessionFile], em: &mut Emitter<W>
     Ok(())
 }
 
+fn run_uncached<W: Write>(
+    opts: &FreqOpts,
+    files: &[SessionFile],
+    tools: Option<&ToolFilter>,
+    roles: Option<&RoleFilter>,
+    progress: &dyn Progress,
+    em: &mut Emitter<W>,
+) -> Result<()> {
+    match opts.mode {
+        FreqMode::Chars if opts.raw => run_chars_raw(files, progress, em),
+        FreqMode::Chars => run_chars_parsed(files, opts.include_smc, progress, em),
+        FreqMode::Words => emit_ranked("word_freq", count_words(files, roles, opts.include_smc, progress), opts.limit, em),
+        FreqMode::Tools => run_tools(files, tools, opts.include_smc, progress, opts.limit, em),
+        FreqMode::Roles => run_roles(files, opts.include_smc, progress, em),
+        FreqMode::Churn => run_churn(files, progress, opts.limit, em),
+        FreqMode::Types => run_types(files, progress, opts.limit, em),
+    }
+}
+
+// ── Cached (chars / tools / roles) ─────────────────────────────────────────
+
+fn run_cached<W: Write>(
+    opts: &FreqOpts,
+    files: &[SessionFile],
+    cache: &SummaryCache,
+    tools: Option<&ToolFilter>,
+    progress: &dyn Progress,
+    em: &mut Emitter<W>,
+) -> Result<()> {
+    let summaries: Vec<FileSummary> = files
+        .par_iter()
+        .map(|file| {
+            let s = cache.get(file);
+            progress.inc(file.size_bytes);
+            s
+        })
+        .collect();
+    cache.save()?;
+
+    let merge = |pick: fn(&FileSummary) -> &BTreeMap<String, u64>| {
+        let mut total: HashMap<String, u64> = HashMap::new();
+        for (key, count) in summaries.iter().flat_map(pick) {
+            *total.entry(key.clone()).or_default() += count;
+        }
+        total
+    };
+    match opts.mode {
+        FreqMode::Tools => emit_ranked("tool_freq", keep_tools(merge(|s| &s.tools), tools), opts.limit, em),
+        FreqMode::Roles => emit_ranked("role_freq", merge(|s| &s.roles), usize::MAX, em),
+        _ => {
+            let counts: Vec<AtomicU64> = (0..26)
+                .map(|i| AtomicU64::new(summaries.iter().map(|s| s.letters[i]).sum()))
+                .collect();
+            emit_char_counts(&counts, em)
+        }
+    }
+}
+
 // ── Chars (parsed) ─────────────────────────────────────────────────────────
 
-fn run_chars_parsed<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+fn run_chars_parsed<W: Write>(
+    files: &[SessionFile],
+    include_smc: bool,
+    progress: &dyn Progress,
+    em: &mut Emitter<W>,
+) -> Result<()> {
     let counts: Vec<AtomicU64> = (0..26).map(|_| AtomicU64::new(0)).collect();
 
     files.par_iter().for_each(|file| {
@@ -109,6 +343,9 @@ fn run_chars_parsed<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Res
             let reader = std::io::BufReader::with_capacity(256 * 1024, f);
             for line in reader.lines() {
                 let Ok(line) = line else { continue };
+                if policy::skips(include_smc, &line) {
+                    continue;
+                }
                 let Ok(record) = serde_json::from_str::<models::Record>(&line) else { continue };
                 let Some(msg) = record.as_message() else { continue };
                 let text = msg.text_content();
@@ -122,6 +359,7 @@ fn run_chars_parsed<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Res
                 }
             }
         }
+        progress.inc(file.size_bytes);
     });
 
     emit_char_counts(&counts, em)
@@ -129,7 +367,11 @@ fn run_chars_parsed<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Res
 
 // ── Chars (raw) ────────────────────────────────────────────────────────────
 
-fn run_chars_raw<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+fn run_chars_raw<W: Write>(
+    files: &[SessionFile],
+    progress: &dyn Progress,
+    em: &mut Emitter<W>,
+) -> Result<()> {
     let counts: Vec<AtomicU64> = (0..26).map(|_| AtomicU64::new(0)).collect();
 
     files.par_iter().for_each(|file| {
@@ -143,6 +385,7 @@ fn run_chars_raw<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result
                 counts[idx].fetch_add(1, Ordering::Relaxed);
             }
         }
+        progress.inc(file.size_bytes);
     });
 
     emit_char_counts(&counts, em)
@@ -169,9 +412,104 @@ fn emit_char_counts<W: Write>(counts: &[AtomicU64], em: &mut Emitter<W>) -> Resu
     Ok(())
 }
 
+// ── Chars (other classes) ──────────────────────────────────────────────────
+
+/// Counts of `class` over message text, or over the raw file with `raw`.
+fn count_class(
+    files: &[SessionFile],
+    class: &CharClass,
+    raw: bool,
+    include_smc: bool,
+    progress: &dyn Progress,
+) -> HashMap<String, u64> {
+    let counts: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+
+    files.par_iter().for_each(|file| {
+        let mut local: HashMap<char, u64> = HashMap::new();
+        let mut add = |text: &str| {
+            for c in text.chars() {
+                *local.entry(c).or_default() += 1;
+            }
+        };
+        if raw {
+            if let Ok(data) = std::fs::read(&file.path) {
+                add(&String::from_utf8_lossy(&data));
+            }
+        } else if let Ok(f) = std::fs::File::open(&file.path) {
+            use std::io::BufRead;
+            let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+            for line in reader.lines() {
+                let Ok(line) = line else { continue };
+                if policy::skips(include_smc, &line) {
+                    continue;
+                }
+                let Ok(record) = serde_json::from_str::<models::Record>(&line) else { continue };
+                let Some(msg) = record.as_message() else { continue };
+                add(&msg.text_content());
+            }
+        }
+        let mut global = counts.lock().unwrap();
+        for (c, count) in local {
+            if let Some(key) = class.key(c) {
+                *global.entry(key).or_default() += count;
+            }
+        }
+        progress.inc(file.size_bytes);
+    });
+
+    counts.into_inner().unwrap()
+}
+
+/// Unicode script of a letter, from the main blocks of each script;
+/// `None` for anything that isn't a letter or emoji.
+fn script(c: char) -> Option<&'static str> {
+    const SCRIPTS: &[(u32, u32, &str)] = &[
+        (0x0370, 0x03FF, "Greek"),
+        (0x1F00, 0x1FFF, "Greek"),
+        (0x0400, 0x052F, "Cyrillic"),
+        (0x0530, 0x058F, "Armenian"),
+        (0x0590, 0x05FF, "Hebrew"),
+        (0x0600, 0x06FF, "Arabic"),
+        (0x0750, 0x077F, "Arabic"),
+        (0x0900, 0x097F, "Devanagari"),
+        (0x0980, 0x09FF, "Bengali"),
+        (0x0E00, 0x0E7F, "Thai"),
+        (0x10A0, 0x10FF, "Georgian"),
+        (0x1100, 0x11FF, "Hangul"),
+        (0x3130, 0x318F, "Hangul"),
+        (0xAC00, 0xD7AF, "Hangul"),
+        (0x3040, 0x309F, "Hiragana"),
+        (0x30A0, 0x30FF, "Katakana"),
+        (0x3400, 0x4DBF, "Han"),
+        (0x4E00, 0x9FFF, "Han"),
+        (0xF900, 0xFAFF, "Han"),
+        (0x20000, 0x2FFFF, "Han"),
+        (0x2600, 0x27BF, "Emoji"),
+        (0x1F300, 0x1FAFF, "Emoji"),
+    ];
+    let cp = c as u32;
+    if let Some((_, _, name)) = SCRIPTS.iter().find(|(lo, hi, _)| (*lo..=*hi).contains(&cp)) {
+        return Some(name);
+    }
+    if !c.is_alphabetic() {
+        return None;
+    }
+    Some(if c.is_ascii_alphabetic() || (0x00C0..=0x024F).contains(&cp) || (0x1E00..=0x1EFF).contains(&cp) {
+        "Latin"
+    } else {
+        "Other"
+    })
+}
+
 // ── Words ──────────────────────────────────────────────────────────────────
 
-fn run_words<W: Write>(files: &[SessionFile], limit: usize, em: &mut Emitter<W>) -> Result<()> {
+/// Words of 3+ alphanumeric chars, lowercased, over messages `roles` keeps.
+fn count_words(
+    files: &[SessionFile],
+    roles: Option<&RoleFilter>,
+    include_smc: bool,
+    progress: &dyn Progress,
+) -> HashMap<String, u64> {
     let word_counts: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
 
     files.par_iter().for_each(|file| {
@@ -181,8 +519,14 @@ fn run_words<W: Write>(files: &[SessionFile], limit: usize, em: &mut Emitter<W>)
             let reader = std::io::BufReader::with_capacity(256 * 1024, f);
             for line in reader.lines() {
                 let Ok(line) = line else { continue };
+                if policy::skips(include_smc, &line) {
+                    continue;
+                }
                 let Ok(record) = serde_json::from_str::<models::Record>(&line) else { continue };
                 let Some(msg) = record.as_message() else { continue };
+                if roles.is_some_and(|r| !r.matches(record.role(), msg.has_tool_result())) {
+                    continue;
+                }
                 let text = msg.text_content();
                 for word in text.split(|c: char| !c.is_alphanumeric()) {
                     if word.len() >= 3 {
@@ -195,33 +539,68 @@ fn run_words<W: Write>(files: &[SessionFile], limit: usize, em: &mut Emitter<W>)
         for (word, count) in local {
             *global.entry(word).or_default() += count;
         }
+        progress.inc(file.size_bytes);
     });
 
-    let counts = word_counts.into_inner().unwrap();
-    let mut sorted: Vec<_> = counts.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
-
-    let grand_total: u64 = sorted.iter().map(|(_, c)| c).sum();
+    word_counts.into_inner().unwrap()
+}
 
-    for (word, count) in sorted.iter().take(limit) {
-        let pct = if grand_total > 0 { *count as f64 / grand_total as f64 * 100.0 } else { 0.0 };
-        let rec = FreqRecord {
-            record_type: "word_freq",
-            key: word.clone(),
-            count: *count,
-            pct: Some(pct),
+fn run_compare<W: Write>(
+    files: &[SessionFile],
+    projects: &[String],
+    roles: Option<&RoleFilter>,
+    include_smc: bool,
+    progress: &dyn Progress,
+    limit: usize,
+    em: &mut Emitter<W>,
+) -> Result<()> {
+    let columns: Vec<(u64, Vec<(String, u64)>)> = projects
+        .iter()
+        .map(|p| {
+            let own: Vec<SessionFile> = files.iter().filter(|f| f.project_name == *p).cloned().collect();
+            let counts = count_words(&own, roles, include_smc, progress);
+            let total = counts.values().sum();
+            (total, ranked(counts))
+        })
+        .collect();
+
+    let depth = columns.iter().map(|(_, c)| c.len()).max().unwrap_or(0).min(limit);
+    for rank in 0..depth {
+        let rec = CompareRecord {
+            record_type: "word_compare",
+            rank: rank + 1,
+            columns: projects
+                .iter()
+                .zip(&columns)
+                .map(|(project, (total, terms))| {
+                    let term = terms.get(rank);
+                    let count = term.map_or(0, |(_, c)| *c);
+                    CompareColumn {
+                        project: project.clone(),
+                        key: term.map(|(k, _)| k.clone()),
+                        count,
+                        pct: if *total > 0 { count as f64 / *total as f64 * 100.0 } else { 0.0 },
+                    }
+                })
+                .collect(),
         };
         if !em.emit(&rec)? {
             break;
         }
     }
-
     Ok(())
 }
 
 // ── Tools ──────────────────────────────────────────────────────────────────
 
-fn run_tools<W: Write>(files: &[SessionFile], limit: usize, em: &mut Emitter<W>) -> Result<()> {
+fn run_tools<W: Write>(
+    files: &[SessionFile],
+    tools: Option<&ToolFilter>,
+    include_smc: bool,
+    progress: &dyn Progress,
+    limit: usize,
+    em: &mut Emitter<W>,
+) -> Result<()> {
     let tool_counts: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
 
     files.par_iter().for_each(|file| {
@@ -231,6 +610,9 @@ fn run_tools<W: Write>(files: &[SessionFile], limit: usize, em: &mut Emitter<W>)
             let reader = std::io::BufReader::with_capacity(256 * 1024, f);
             for line in reader.lines() {
                 let Ok(line) = line else { continue };
+                if policy::skips(include_smc, &line) {
+                    continue;
+                }
                 let Ok(record) = serde_json::from_str::<models::Record>(&line) else { continue };
                 let Some(msg) = record.as_message() else { continue };
                 for tool in msg.tool_names() {
@@ -242,33 +624,27 @@ fn run_tools<W: Write>(files: &[SessionFile], limit: usize, em: &mut Emitter<W>)
         for (tool, count) in local {
             *global.entry(tool).or_default() += count;
         }
+        progress.inc(file.size_bytes);
     });
 
-    let counts = tool_counts.into_inner().unwrap();
-    let mut sorted: Vec<_> = counts.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
-
-    let grand_total: u64 = sorted.iter().map(|(_, c)| c).sum();
+    emit_ranked("tool_freq", keep_tools(tool_counts.into_inner().unwrap(), tools), limit, em)
+}
 
-    for (tool, count) in sorted.iter().take(limit) {
-        let pct = if grand_total > 0 { *count as f64 / grand_total as f64 * 100.0 } else { 0.0 };
-        let rec = FreqRecord {
-            record_type: "tool_freq",
-            key: tool.clone(),
-            count: *count,
-            pct: Some(pct),
-        };
-        if !em.emit(&rec)? {
-            break;
-        }
+fn keep_tools(mut counts: HashMap<String, u64>, filter: Option<&ToolFilter>) -> HashMap<String, u64> {
+    if let Some(f) = filter {
+        counts.retain(|name, _| f.matches(name));
     }
-
-    Ok(())
+    counts
 }
 
 // ── Roles ──────────────────────────────────────────────────────────────────
 
-fn run_roles<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+fn run_roles<W: Write>(
+    files: &[SessionFile],
+    include_smc: bool,
+    progress: &dyn Progress,
+    em: &mut Emitter<W>,
+) -> Result<()> {
     let role_counts: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
 
     files.par_iter().for_each(|file| {
@@ -278,6 +654,9 @@ fn run_roles<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result<()>
             let reader = std::io::BufReader::with_capacity(256 * 1024, f);
             for line in reader.lines() {
                 let Ok(line) = line else { continue };
+                if policy::skips(include_smc, &line) {
+                    continue;
+                }
                 let Ok(record) = serde_json::from_str::<models::Record>(&line) else { continue };
                 if record.is_message() {
                     *local.entry(record.role().to_string()).or_default() += 1;
@@ -288,22 +667,185 @@ fn run_roles<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result<()>
         for (role, count) in local {
             *global.entry(role).or_default() += count;
         }
+        progress.inc(file.size_bytes);
+    });
+
+    emit_ranked("role_freq", role_counts.into_inner().unwrap(), usize::MAX, em)
+}
+
+// ── Churn ──────────────────────────────────────────────────────────────────
+
+/// Edits plus writes from which a file is flagged `high_churn`.
+const HIGH_CHURN: u64 = 10;
+
+#[derive(Default)]
+struct Churn {
+    reads: u64,
+    edits: u64,
+    writes: u64,
+    sessions: u64,
+}
+
+/// Files by edits plus writes, most changed first.
+fn run_churn<W: Write>(files: &[SessionFile], progress: &dyn Progress, limit: usize, em: &mut Emitter<W>) -> Result<()> {
+    let churn: Mutex<HashMap<String, Churn>> = Mutex::new(HashMap::new());
+
+    files.par_iter().for_each(|file| {
+        let mut local: HashMap<String, Churn> = HashMap::new();
+        if let Ok(f) = std::fs::File::open(&file.path) {
+            use std::io::BufRead;
+            let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+            for line in reader.lines() {
+                let Ok(line) = line else { continue };
+                // Cheap pre-filter before a full parse.
+                if !line.contains("_path") {
+                    continue;
+                }
+                let Ok(record) = serde_json::from_str::<models::Record>(&line) else { continue };
+                let Some(msg) = record.as_message() else { continue };
+                let models::MessageContent::Blocks(blocks) = &msg.message.content else { continue };
+                for block in blocks {
+                    let models::ContentBlock::ToolUse { name, input, .. } = block else { continue };
+                    let Some(path) = blame::target_path(input) else { continue };
+                    let c = local.entry(path.to_string()).or_default();
+                    match name.as_str() {
+                        "Read" => c.reads += 1,
+                        "Write" => c.writes += 1,
+                        "Edit" | "MultiEdit" | "NotebookEdit" => c.edits += 1,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        let mut global = churn.lock().unwrap();
+        for (path, c) in local {
+            let g = global.entry(path).or_default();
+            g.reads += c.reads;
+            g.edits += c.edits;
+            g.writes += c.writes;
+            g.sessions += u64::from(c.edits + c.writes > 0);
+        }
+        progress.inc(file.size_bytes);
+    });
+
+    let mut sorted: Vec<(String, Churn)> = churn.into_inner().unwrap().into_iter().collect();
+    sorted.retain(|(_, c)| c.reads + c.edits + c.writes > 0);
+    sorted.sort_by(|(pa, a), (pb, b)| {
+        (b.edits + b.writes).cmp(&(a.edits + a.writes)).then_with(|| b.reads.cmp(&a.reads)).then_with(|| pa.cmp(pb))
     });
 
-    let counts = role_counts.into_inner().unwrap();
+    for (path, c) in sorted.into_iter().take(limit) {
+        let changes = c.edits + c.writes;
+        let rec = ChurnRecord {
+            record_type: "file_churn",
+            path,
+            reads: c.reads,
+            edits: c.edits,
+            writes: c.writes,
+            sessions: c.sessions,
+            changes_per_read: (c.reads > 0).then(|| (changes as f64 / c.reads as f64 * 100.0).round() / 100.0),
+            high_churn: changes >= HIGH_CHURN,
+        };
+        if !em.emit(&rec)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// ── Types ──────────────────────────────────────────────────────────────────
+
+/// Just the tag of a JSONL record.
+#[derive(serde::Deserialize)]
+pub(crate) struct TypeTag {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// Records per `type` value in each project, most common first. Lines that
+/// aren't JSON objects are left to `smc validate`.
+fn run_types<W: Write>(files: &[SessionFile], progress: &dyn Progress, limit: usize, em: &mut Emitter<W>) -> Result<()> {
+    // (project, type) → (count, unknown)
+    let counts: Mutex<HashMap<(String, String), (u64, bool)>> = Mutex::new(HashMap::new());
+
+    files.par_iter().for_each(|file| {
+        // Whether a type is unknown depends only on the tag, so each one is
+        // fully parsed once per file.
+        let mut local: HashMap<String, (u64, bool)> = HashMap::new();
+        if let Ok(f) = std::fs::File::open(&file.path) {
+            use std::io::BufRead;
+            let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+            for line in reader.lines() {
+                let Ok(line) = line else { continue };
+                let Ok(tag) = serde_json::from_str::<TypeTag>(&line) else { continue };
+                let Some(key) = tag.kind else {
+                    local.entry("(none)".to_string()).or_insert((0, true)).0 += 1;
+                    continue;
+                };
+                let entry = local.entry(key).or_insert_with(|| {
+                    (0, matches!(serde_json::from_str::<models::Record>(&line), Ok(models::Record::Unknown)))
+                });
+                entry.0 += 1;
+            }
+        }
+        let mut global = counts.lock().unwrap();
+        for (key, (count, unknown)) in local {
+            let g = global.entry((file.project_name.clone(), key)).or_default();
+            g.0 += count;
+            g.1 |= unknown;
+        }
+        progress.inc(file.size_bytes);
+    });
+
+    let mut by_project: BTreeMap<String, Vec<(String, u64, bool)>> = BTreeMap::new();
+    for ((project, key), (count, unknown)) in counts.into_inner().unwrap() {
+        by_project.entry(project).or_default().push((key, count, unknown));
+    }
+    for (project, mut types) in by_project {
+        let total: u64 = types.iter().map(|t| t.1).sum();
+        types.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (key, count, unknown) in types.into_iter().take(limit) {
+            let rec = TypeFreqRecord {
+                record_type: "type_freq",
+                project: project.clone(),
+                key,
+                count,
+                pct: if total > 0 { count as f64 / total as f64 * 100.0 } else { 0.0 },
+                unknown,
+            };
+            if !em.emit(&rec)? {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// Highest count first, ties by key. Without the key tiebreak, ties would
+/// follow hash-map order, and cached and uncached runs would disagree.
+fn ranked(counts: impl IntoIterator<Item = (String, u64)>) -> Vec<(String, u64)> {
     let mut sorted: Vec<_> = counts.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted.sort_by(|(ka, a), (kb, b)| b.cmp(a).then_with(|| ka.cmp(kb)));
+    sorted
+}
 
+/// Emit `counts` as `record_type` records, highest first (ties by key), with
+/// each key's share of the grand total.
+fn emit_ranked<W: Write>(
+    record_type: &'static str,
+    counts: impl IntoIterator<Item = (String, u64)>,
+    limit: usize,
+    em: &mut Emitter<W>,
+) -> Result<()> {
+    let sorted = ranked(counts);
     let grand_total: u64 = sorted.iter().map(|(_, c)| c).sum();
 
-    for (role, count) in &sorted {
-        let pct = if grand_total > 0 { *count as f64 / grand_total as f64 * 100.0 } else { 0.0 };
-        let rec = FreqRecord {
-            record_type: "role_freq",
-            key: role.clone(),
-            count: *count,
-            pct: Some(pct),
-        };
+    for (key, count) in sorted.into_iter().take(limit) {
+        let pct = if grand_total > 0 { count as f64 / grand_total as f64 * 100.0 } else { 0.0 };
+        let rec = FreqRecord { record_type, key, count, pct: Some(pct) };
         if !em.emit(&rec)? {
             break;
         }
@@ -311,3 +853,147 @@ fn run_roles<W: Write>(files: &[SessionFile], em: &mut Emitter<W>) -> Result<()>
 
     Ok(())
 }
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn char_classes() {
+        assert_eq!(CharClass::parse("custom:{}").unwrap(), CharClass::Custom(vec!['{', '}']));
+        assert!(CharClass::parse("custom:").is_err());
+        assert_eq!(CharClass::Punct.key('('), Some("(".into()));
+        assert_eq!(CharClass::Digits.key('a'), None);
+        let scripts: Vec<Option<&str>> = ['a', 'é', 'ж', '漢', 'か', '7', '🚀'].iter().map(|&c| script(c)).collect();
+        assert_eq!(
+            scripts,
+            [Some("Latin"), Some("Latin"), Some("Cyrillic"), Some("Han"), Some("Hiragana"), None, Some("Emoji")]
+        );
+    }
+
+    #[test]
+    fn words_scoped_by_role_and_compared() {
+        let dir = TempDir::new("freq");
+        let file = |id: &str, project: &str, user: &str, asst: &str| {
+            let body = format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"{}"}}}}
+{{"type":"assistant","message":{{"role":"assistant","content":"{}"}}}}
+"#,
+                user, asst
+            );
+            dir.session(id, project, &body)
+        };
+        let files = vec![file("s1", "web", "deploy deploy now", "sure"), file("s2", "api", "schema", "okay okay")];
+        let opts = |role: Option<&str>, compare: &[&str]| FreqOpts {
+            mode: FreqMode::Words,
+            class: CharClass::Letters,
+            limit: 5,
+            raw: false,
+            cache: None,
+            tool: None,
+            project: None,
+            role: role.map(String::from),
+            compare: compare.iter().map(|s| s.to_string()).collect(),
+            include_smc: false,
+            max_tokens: 0,
+        };
+
+        let mut em = Emitter::capturing(0);
+        run(&opts(Some("asst"), &[]), &files, &mut em).unwrap();
+        let keys: Vec<String> = em.into_records().iter().filter_map(|r| Some(r["key"].as_str()?.to_string())).collect();
+        assert_eq!(keys, ["okay", "sure"]);
+
+        let mut em = Emitter::capturing(0);
+        run(&opts(None, &["web", "api"]), &files, &mut em).unwrap();
+        let records = em.into_records();
+        assert_eq!(records[0]["columns"][0]["key"], "deploy");
+        assert_eq!(records[0]["columns"][1]["key"], "okay");
+        assert_eq!(records[2]["columns"][0]["key"], "sure");
+        assert!(records[2]["columns"][1].get("key").is_none());
+    }
+
+    #[test]
+    fn types_per_project_flag_unknown() {
+        let dir = TempDir::new("freq-types");
+        let file = |id: &str, project: &str, body: &str| dir.session(id, project, body);
+        let user = r#"{"type":"user","message":{"role":"user","content":"hi"}}"#;
+        let files = vec![
+            file("s1", "web", &[user, user, r#"{"type":"summary","summary":"x"}"#, r#"{"type":"progress","data":{}}"#, "not json"].join("\n")),
+            file("s2", "api", &[user, r#"{"uuid":"u"}"#].join("\n")),
+        ];
+        let opts = FreqOpts {
+            mode: FreqMode::Types,
+            class: CharClass::Letters,
+            limit: 10,
+            raw: false,
+            cache: None,
+            tool: None,
+            project: None,
+            role: None,
+            compare: Vec::new(),
+            include_smc: false,
+            max_tokens: 0,
+        };
+
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+        let rows: Vec<(&str, &str, u64, bool)> = records
+            .iter()
+            .filter(|r| r["type"] == "type_freq")
+            .map(|r| (r["project"].as_str().unwrap(), r["key"].as_str().unwrap(), r["count"].as_u64().unwrap(), r.get("unknown").is_some()))
+            .collect();
+        assert_eq!(
+            rows,
+            [("api", "(none)", 1, true), ("api", "user", 1, false), ("web", "user", 2, false), ("web", "progress", 1, false), ("web", "summary", 1, true)]
+        );
+        assert_eq!(records[2]["pct"], 50.0);
+    }
+
+    #[test]
+    fn churn_counts_reads_edits_and_writes() {
+        let dir = TempDir::new("freq-churn");
+        let call = |name: &str, path: &str| {
+            format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[{{"type":"tool_use","name":"{}","input":{{"file_path":"{}"}}}}]}}}}"#,
+                name, path
+            ) + "\n"
+        };
+        let file = |id: &str, body: String| {
+            dir.session(id, "p", &body)
+        };
+        let files = vec![
+            file("s1", call("Read", "/a.rs") + &call("Edit", "/a.rs") + &call("Edit", "/a.rs") + &call("Read", "/b.rs")),
+            file("s2", call("Write", "/a.rs") + &call("Grep", "/a.rs") + &call("Write", "/c.rs")),
+        ];
+        let opts = FreqOpts {
+            mode: FreqMode::Churn,
+            class: CharClass::Letters,
+            limit: 10,
+            raw: false,
+            cache: None,
+            tool: None,
+            project: None,
+            role: None,
+            compare: Vec::new(),
+            include_smc: false,
+            max_tokens: 0,
+        };
+
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+        let a = &records[0];
+        assert_eq!(a["path"], "/a.rs");
+        let counts: Vec<u64> = ["reads", "edits", "writes", "sessions"].iter().map(|k| a[*k].as_u64().unwrap()).collect();
+        assert_eq!(counts, [1, 2, 1, 2]);
+        assert_eq!(a["changes_per_read"], 3.0);
+        assert_eq!(a["high_churn"], false);
+        assert_eq!(records[1]["path"], "/c.rs");
+        assert!(records[1].get("changes_per_read").is_none());
+        assert_eq!((records[2]["path"].as_str(), records[2]["changes_per_read"].as_f64()), (Some("/b.rs"), Some(0.0)));
+    }
+}
@@ -0,0 +1,235 @@
+/// smc growth — how the corpus grew: sessions and bytes per period, by
+/// project, keyed on each session's first message.
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
+use serde::Serialize;
+
+use crate::cmd::stats::format_bytes;
+use crate::output::Emitter;
+use crate::util::cache::{self, SummaryCache};
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct GrowthOpts {
+    pub interval: Interval,
+    /// Summary cache file (`None` = parse every file).
+    pub cache: Option<PathBuf>,
+    pub max_tokens: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interval {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Year,
+}
+
+impl Interval {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            _ => anyhow::bail!("unknown interval '{}' — use: day, week, month, year", s),
+        }
+    }
+
+    /// Period label; sorts in time order.
+    fn label(self, t: DateTime<Utc>) -> String {
+        match self {
+            Self::Day => t.format("%Y-%m-%d").to_string(),
+            Self::Week => {
+                let w = t.iso_week();
+                format!("{}-W{:02}", w.year(), w.week())
+            }
+            Self::Month => t.format("%Y-%m").to_string(),
+            Self::Year => t.format("%Y").to_string(),
+        }
+    }
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct GrowthRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    period: String,
+    /// Sessions started in this period.
+    sessions: usize,
+    bytes: u64,
+    cumulative_sessions: usize,
+    cumulative_bytes: u64,
+    cumulative_human: String,
+    /// Per-project additions this period, largest first.
+    projects: Vec<ProjectGrowth>,
+}
+
+#[derive(Serialize, Debug)]
+struct ProjectGrowth {
+    project: String,
+    sessions: usize,
+    bytes: u64,
+    cumulative_bytes: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct GrowthSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    periods: usize,
+    sessions: usize,
+    bytes: u64,
+    /// Sessions with no timestamp, left out of the periods.
+    undated: usize,
+    /// Projects by total bytes, largest first.
+    by_project: Vec<ProjectTotal>,
+}
+
+#[derive(Serialize, Debug)]
+struct ProjectTotal {
+    project: String,
+    sessions: usize,
+    bytes: u64,
+    pct_bytes: f64,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &GrowthOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let cache = opts.cache.as_ref().map(SummaryCache::open);
+    let refs: Vec<&SessionFile> = files.iter().collect();
+    let summaries = cache::summaries(&refs, cache.as_ref());
+    if let Some(c) = &cache {
+        c.save()?;
+    }
+
+    // period → project → (sessions, bytes)
+    let mut periods: BTreeMap<String, HashMap<&str, (usize, u64)>> = BTreeMap::new();
+    let mut undated = 0;
+    for (file, s) in files.iter().zip(&summaries) {
+        let Some(t) = s.first_timestamp.as_ref().and_then(|t| t.utc) else {
+            undated += 1;
+            continue;
+        };
+        let entry = periods.entry(opts.interval.label(t)).or_default().entry(&file.project_name).or_default();
+        entry.0 += 1;
+        entry.1 += file.size_bytes;
+    }
+
+    let n_periods = periods.len();
+    let (mut sessions, mut bytes) = (0, 0);
+    let mut totals: HashMap<&str, (usize, u64)> = HashMap::new();
+    let mut rows = Vec::with_capacity(n_periods);
+    for (period, projects) in periods {
+        let mut added: Vec<ProjectGrowth> = projects
+            .into_iter()
+            .map(|(project, (s, b))| {
+                let total = totals.entry(project).or_default();
+                total.0 += s;
+                total.1 += b;
+                ProjectGrowth { project: project.to_string(), sessions: s, bytes: b, cumulative_bytes: total.1 }
+            })
+            .collect();
+        added.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.project.cmp(&b.project)));
+        let period_sessions: usize = added.iter().map(|p| p.sessions).sum();
+        let period_bytes: u64 = added.iter().map(|p| p.bytes).sum();
+        sessions += period_sessions;
+        bytes += period_bytes;
+
+        rows.push(GrowthRecord {
+            record_type: "growth",
+            period,
+            sessions: period_sessions,
+            bytes: period_bytes,
+            cumulative_sessions: sessions,
+            cumulative_bytes: bytes,
+            cumulative_human: format_bytes(bytes),
+            projects: added,
+        });
+    }
+    for rec in &rows {
+        if !em.emit(rec)? {
+            break;
+        }
+    }
+
+    let mut by_project: Vec<ProjectTotal> = totals
+        .into_iter()
+        .map(|(project, (s, b))| ProjectTotal {
+            project: project.to_string(),
+            sessions: s,
+            bytes: b,
+            pct_bytes: if bytes > 0 { (b as f64 / bytes as f64 * 1000.0).round() / 10.0 } else { 0.0 },
+        })
+        .collect();
+    by_project.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.project.cmp(&b.project)));
+
+    em.emit(&GrowthSummary {
+        record_type: "summary",
+        periods: n_periods,
+        sessions,
+        bytes,
+        undated,
+        by_project,
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn cumulative_by_period_and_project() {
+        let dir = TempDir::new("growth");
+        let file = |id: &str, project: &str, ts: Option<&str>| {
+            let body = match ts {
+                Some(ts) => format!(
+                    r#"{{"type":"user","timestamp":"{}","message":{{"role":"user","content":"hi"}}}}"#,
+                    ts
+                ) + "\n",
+                None => r#"{"type":"user","message":{"role":"user","content":"hi"}}"#.to_string() + "\n",
+            };
+            SessionFile { size_bytes: 100, ..dir.session(id, project, &body) }
+        };
+        let files = vec![
+            file("s1", "a", Some("2025-01-05T10:00:00Z")),
+            file("s2", "b", Some("2025-01-20T10:00:00Z")),
+            file("s3", "a", Some("2025-03-01T10:00:00Z")),
+            file("s4", "a", None),
+        ];
+
+        let opts = GrowthOpts { interval: Interval::Month, cache: None, max_tokens: 0 };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+
+        let rows: Vec<(&str, u64, u64)> = records[..2]
+            .iter()
+            .map(|r| (r["period"].as_str().unwrap(), r["sessions"].as_u64().unwrap(), r["cumulative_bytes"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(rows, [("2025-01", 2, 200), ("2025-03", 1, 300)]);
+        assert_eq!(records[1]["projects"][0]["cumulative_bytes"], 200);
+        let summary = &records[2];
+        assert_eq!(summary["undated"], 1);
+        assert_eq!(summary["by_project"][0]["project"], "a");
+
+        assert_eq!(Interval::Week.label("2025-01-01T00:00:00Z".parse().unwrap()), "2025-W01");
+        assert!(Interval::parse("fortnight").is_err());
+    }
+}
@@ -0,0 +1,238 @@
+/// smc hours — when you work with Claude: messages by hour of day, per
+/// weekday and per project, with each weekday's earliest and latest
+/// message and the typical first and last message of an active day.
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::output::Emitter;
+use crate::session;
+use crate::util::discover::SessionFile;
+use crate::util::time;
+
+const WEEKDAYS: [Weekday; 7] =
+    [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct HoursOpts {
+    pub project: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+    /// Bucket by UTC instead of the machine's timezone.
+    pub utc: bool,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct WeekdayRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    weekday: String,
+    /// Dates with at least one message.
+    days: usize,
+    #[serde(flatten)]
+    span: Span,
+    /// Median time of the first and last message of an active day.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typical_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typical_end: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ProjectRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    days: usize,
+    #[serde(flatten)]
+    span: Span,
+}
+
+/// Messages in a slice of the corpus, by hour of day.
+#[derive(Serialize, Debug, Default)]
+struct Span {
+    messages: usize,
+    /// Earliest and latest time of day seen (`HH:MM`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    earliest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+    /// Messages per hour, index 0 = 00:00–00:59.
+    hours: [usize; 24],
+}
+
+#[derive(Serialize, Debug)]
+struct HoursSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    timezone: &'static str,
+    days: usize,
+    #[serde(flatten)]
+    span: Span,
+    files_scanned: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &HoursOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let (after, before) = time::parse_range(opts.after.as_deref(), opts.before.as_deref())?;
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(p) => f.project_name.to_lowercase().contains(&p.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let wall_clock = |t: DateTime<Utc>| if opts.utc { t.naive_utc() } else { t.with_timezone(&Local).naive_local() };
+    // (project, wall-clock time) of every timestamped message in range.
+    let mut times: Vec<(&str, NaiveDateTime)> = filtered
+        .par_iter()
+        .flat_map_iter(|file| {
+            let messages = session::load(file).map(|s| s.messages).unwrap_or_default();
+            messages
+                .into_iter()
+                .filter_map(|m| m.record.time())
+                .filter(|t| time::in_range(Some(*t), after, before))
+                .map(|t| (file.project_name.as_str(), wall_clock(t)))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    times.sort_by_key(|(_, t)| *t);
+
+    // First and last message per date, for the typical day.
+    let mut bounds: BTreeMap<NaiveDate, (NaiveTime, NaiveTime)> = BTreeMap::new();
+    for (_, t) in &times {
+        let b = bounds.entry(t.date()).or_insert((t.time(), t.time()));
+        b.1 = t.time();
+    }
+
+    let mut cont = true;
+    for day in WEEKDAYS {
+        let on_day: Vec<NaiveDateTime> = times.iter().map(|(_, t)| *t).filter(|t| t.weekday() == day).collect();
+        let day_bounds: Vec<&(NaiveTime, NaiveTime)> =
+            bounds.iter().filter(|(d, _)| d.weekday() == day).map(|(_, b)| b).collect();
+        let rec = WeekdayRecord {
+            record_type: "weekday_hours",
+            weekday: day.to_string(),
+            days: day_bounds.len(),
+            span: span(&on_day),
+            typical_start: median(day_bounds.iter().map(|b| b.0).collect()),
+            typical_end: median(day_bounds.iter().map(|b| b.1).collect()),
+        };
+        cont = em.emit(&rec)?;
+        if !cont {
+            break;
+        }
+    }
+
+    if cont {
+        let mut by_project: HashMap<&str, Vec<NaiveDateTime>> = HashMap::new();
+        for (project, t) in &times {
+            by_project.entry(project).or_default().push(*t);
+        }
+        let mut projects: Vec<(&str, Vec<NaiveDateTime>)> = by_project.into_iter().collect();
+        projects.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(b.0)));
+        for (project, ts) in projects {
+            let mut dates: Vec<NaiveDate> = ts.iter().map(|t| t.date()).collect();
+            dates.dedup();
+            let rec = ProjectRecord {
+                record_type: "project_hours",
+                project: project.to_string(),
+                days: dates.len(),
+                span: span(&ts),
+            };
+            if !em.emit(&rec)? {
+                break;
+            }
+        }
+    }
+
+    let all: Vec<NaiveDateTime> = times.iter().map(|(_, t)| *t).collect();
+    em.emit(&HoursSummary {
+        record_type: "summary",
+        timezone: if opts.utc { "utc" } else { "local" },
+        days: bounds.len(),
+        span: span(&all),
+        files_scanned: filtered.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn span(times: &[NaiveDateTime]) -> Span {
+    let mut s = Span { messages: times.len(), ..Default::default() };
+    for t in times {
+        s.hours[t.hour() as usize] += 1;
+    }
+    s.earliest = times.iter().map(|t| t.time()).min().map(hhmm);
+    s.latest = times.iter().map(|t| t.time()).max().map(hhmm);
+    s
+}
+
+fn median(mut times: Vec<NaiveTime>) -> Option<String> {
+    times.sort();
+    times.get(times.len() / 2).copied().map(hhmm)
+}
+
+fn hhmm(t: NaiveTime) -> String {
+    t.format("%H:%M").to_string()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn hours_by_weekday_and_project() {
+        let dir = TempDir::new("hours");
+        let file = |id: &str, project: &str, stamps: &[&str]| {
+            let body: String = stamps
+                .iter()
+                .map(|ts| format!(r#"{{"type":"user","timestamp":"{}","message":{{"role":"user","content":"hi"}}}}"#, ts) + "\n")
+                .collect();
+            dir.session(id, project, &body)
+        };
+        // 2025-01-06 and 2025-01-13 are Mondays.
+        let files = vec![
+            file("s1", "web", &["2025-01-06T09:15:00Z", "2025-01-06T17:40:00Z", "2025-01-13T08:00:00Z"]),
+            file("s2", "api", &["2025-01-07T22:05:00Z", "2025-02-01T10:00:00Z"]),
+        ];
+
+        let opts = HoursOpts { project: None, after: None, before: Some("2025-01-31".into()), utc: true, max_tokens: 0 };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+
+        let mon = &records[0];
+        assert_eq!((mon["weekday"].as_str(), mon["days"].as_u64(), mon["messages"].as_u64()), (Some("Mon"), Some(2), Some(3)));
+        assert_eq!((mon["earliest"].as_str(), mon["latest"].as_str()), (Some("08:00"), Some("17:40")));
+        assert_eq!(mon["typical_start"], "09:15");
+        assert_eq!(mon["hours"][9], 1);
+        assert_eq!(records[1]["latest"], "22:05");
+        assert!(records[2].get("earliest").is_none());
+
+        assert_eq!(records[7]["project"], "web");
+        assert_eq!(records[7]["days"], 2);
+        let summary = records.last().unwrap();
+        assert_eq!((summary["messages"].as_u64(), summary["days"].as_u64()), (Some(4), Some(3)));
+    }
+}
@@ -1,13 +1,41 @@
 pub mod search;
 pub mod sessions;
 pub mod show;
+pub mod thread;
 pub mod tools;
+pub mod tools_schema;
+pub mod unknown;
+pub mod tokens;
 pub mod export;
+pub mod obsidian;
 pub mod context;
+pub mod msg;
+pub mod split;
 pub mod stats;
 pub mod projects;
+pub mod growth;
+pub mod hours;
 pub mod freq;
 pub mod recent;
+pub mod validate;
+pub mod errors;
+pub mod results;
+pub mod outcomes;
+pub mod site;
+pub mod dataset;
+pub mod alerts;
+pub mod blame;
+pub mod resume;
+pub mod tag;
+pub mod doctor;
+pub mod digest;
+pub mod bench;
+#[cfg(feature = "sqlite")]
+pub mod db;
+#[cfg(feature = "backup")]
+pub mod backup;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 use std::io::BufRead;
 
@@ -0,0 +1,262 @@
+/// smc msg — print one JSONL record in full, addressed by `<session>:<line>`
+/// as search hits and `smc context` report it.
+///
+/// Nothing is truncated: the default record carries every text, thinking,
+/// tool call, and tool result block; `--json` prints the line exactly as
+/// stored; `--field` prints a single part as plain text.
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{ContentBlock, MessageContent, MessageRecord, Record, Timestamp};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct MsgOpts {
+    pub session: String,
+    /// 1-based JSONL line.
+    pub line: usize,
+    /// Print the stored line as is.
+    pub json: bool,
+    /// Print only this part of the message.
+    pub field: Option<MsgField>,
+    pub max_tokens: usize,
+}
+
+/// A single part of a message, printed as plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsgField {
+    /// Text blocks, without thinking.
+    Text,
+    /// Each tool call's input as pretty-printed JSON.
+    ToolInput,
+    Thinking,
+}
+
+impl MsgField {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "tool-input" => Ok(Self::ToolInput),
+            "thinking" => Ok(Self::Thinking),
+            _ => anyhow::bail!("unknown field '{}' — use: text, tool-input, thinking", s),
+        }
+    }
+}
+
+/// Split `<session>:<line>` into its parts.
+pub fn parse_target(s: &str) -> Result<(String, usize)> {
+    let bad = || anyhow::anyhow!("invalid message address '{}' — use: <session>:<line>", s);
+    let (session, line) = s.rsplit_once(':').ok_or_else(bad)?;
+    let line: usize = line.parse().map_err(|_| bad())?;
+    anyhow::ensure!(!session.is_empty() && line > 0, bad());
+    Ok((session.to_string(), line))
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct MsgRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: String,
+    line: usize,
+    role: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_results: Vec<ToolResult>,
+}
+
+#[derive(Serialize, Debug)]
+struct ToolCall {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    input: Value,
+}
+
+#[derive(Serialize, Debug)]
+struct ToolResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_use_id: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    is_error: bool,
+    content: Value,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &MsgOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
+    let raw = read_line(file, opts.line)?;
+    if opts.json {
+        em.raw(&raw)?;
+        em.flush()?;
+        return Ok(());
+    }
+
+    let record = serde_json::from_str::<Record>(&raw).map_err(|e| {
+        anyhow::anyhow!("line {} is not a valid record: {} — use --json for the raw line", opts.line, e)
+    })?;
+    let Some(msg) = record.as_message() else {
+        let kind = serde_json::from_str::<Value>(&raw).ok().and_then(|v| v["type"].as_str().map(String::from));
+        anyhow::bail!(
+            "line {} is a {} record, not a message — use --json for the raw line",
+            opts.line,
+            kind.as_deref().unwrap_or("non-message")
+        );
+    };
+
+    match opts.field {
+        Some(field) => {
+            let text = field_text(msg, field);
+            anyhow::ensure!(!text.is_empty(), "line {} has no {}", opts.line, field_name(field));
+            em.raw(&text)?;
+        }
+        None => {
+            em.emit(&build(msg, record.role(), &file.session_id, opts.line))?;
+        }
+    }
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// The 1-based `line` of the file, without its line ending.
+fn read_line(file: &SessionFile, line: usize) -> Result<String> {
+    let f = std::fs::File::open(&file.path)?;
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+    let mut count = 0;
+    for l in reader.split(b'\n') {
+        let l = l?;
+        count += 1;
+        if count == line {
+            let s = String::from_utf8_lossy(&l);
+            return Ok(s.strip_suffix('\r').unwrap_or(&s).to_string());
+        }
+    }
+    anyhow::bail!("session {} has {} lines, no line {}", file.session_id, count, line)
+}
+
+fn build(msg: &MessageRecord, role: &'static str, session_id: &str, line: usize) -> MsgRecord {
+    let mut rec = MsgRecord {
+        record_type: "msg",
+        session_id: session_id.to_string(),
+        line,
+        role,
+        uuid: msg.uuid.clone(),
+        timestamp: msg.timestamp.clone(),
+        text: msg.text_no_thinking(),
+        thinking: Some(msg.thinking_content()).filter(|t| !t.is_empty()),
+        tool_calls: Vec::new(),
+        tool_results: Vec::new(),
+    };
+    if let MessageContent::Blocks(blocks) = &msg.message.content {
+        for block in blocks {
+            match block {
+                ContentBlock::ToolUse { id, name, input } => {
+                    rec.tool_calls.push(ToolCall { name: name.clone(), id: id.clone(), input: input.clone() })
+                }
+                ContentBlock::ToolResult { tool_use_id, content, is_error } => rec.tool_results.push(ToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                    is_error: *is_error,
+                    content: content.clone().unwrap_or(Value::Null),
+                }),
+                _ => {}
+            }
+        }
+    }
+    rec
+}
+
+fn field_text(msg: &MessageRecord, field: MsgField) -> String {
+    match field {
+        MsgField::Text => msg.text_no_thinking(),
+        MsgField::Thinking => msg.thinking_content(),
+        MsgField::ToolInput => {
+            let MessageContent::Blocks(blocks) = &msg.message.content else { return String::new() };
+            let inputs: Vec<String> = blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::ToolUse { input, .. } => {
+                        Some(serde_json::to_string_pretty(input).unwrap_or_else(|_| input.to_string()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            inputs.join("\n")
+        }
+    }
+}
+
+fn field_name(field: MsgField) -> &'static str {
+    match field {
+        MsgField::Text => "text",
+        MsgField::ToolInput => "tool input",
+        MsgField::Thinking => "thinking",
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn prints_one_record_in_full() {
+        let dir = TempDir::new("msg");
+        let long = "x".repeat(5000);
+        let asst = format!(
+            r#"{{"type":"assistant","uuid":"u2","message":{{"role":"assistant","content":[{{"type":"thinking","thinking":"hmm"}},{{"type":"text","text":"{}"}},{{"type":"tool_use","id":"t1","name":"Bash","input":{{"command":"ls"}}}}]}}}}"#,
+            long
+        );
+        let body = [
+            r#"{"type":"user","message":{"role":"user","content":"hi"}}"#,
+            asst.as_str(),
+            r#"{"type":"file-history-snapshot","snapshot":{}}"#,
+        ]
+        .join("\n");
+        let file = dir.session("s1", "p", &body);
+        let opts = |line, json, field| MsgOpts { session: "s1".into(), line, json, field, max_tokens: 0 };
+
+        let mut em = Emitter::capturing(0);
+        run(&opts(2, false, None), &file, &mut em).unwrap();
+        let rec = &em.into_records()[0];
+        assert_eq!((rec["line"].as_u64(), rec["role"].as_str(), rec["uuid"].as_str()), (Some(2), Some("assistant"), Some("u2")));
+        assert_eq!(rec["text"].as_str().unwrap().len(), 5000);
+        assert_eq!(rec["thinking"], "hmm");
+        assert_eq!(rec["tool_calls"][0]["input"]["command"], "ls");
+
+        let printed = |opts: MsgOpts| {
+            let mut em = Emitter::capturing(0);
+            run(&opts, &file, &mut em).unwrap();
+            String::from_utf8(em.into_bytes()).unwrap()
+        };
+        assert_eq!(printed(opts(2, true, None)), asst + "\n");
+        assert_eq!(printed(opts(2, false, Some(MsgField::ToolInput))), "{\n  \"command\": \"ls\"\n}\n");
+
+        assert!(run(&opts(1, false, Some(MsgField::Thinking)), &file, &mut Emitter::capturing(0)).is_err());
+        assert!(run(&opts(3, false, None), &file, &mut Emitter::capturing(0)).is_err());
+        assert!(run(&opts(4, true, None), &file, &mut Emitter::capturing(0)).is_err());
+
+        assert_eq!(parse_target("abcd1234:17").unwrap(), ("abcd1234".to_string(), 17));
+        assert!(parse_target("abcd1234").is_err());
+        assert!(parse_target("abcd1234:0").is_err());
+    }
+}
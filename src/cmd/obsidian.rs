@@ -0,0 +1,351 @@
+/// smc sync-obsidian — mirror sessions into an Obsidian vault as linked notes.
+///
+/// Layout under the vault:
+///   Sessions/<session-id>.md  — front matter, links to project and files, conversation
+///   Projects/<project>.md     — every synced session of the project, newest first
+///   Files/<path-slug>.md      — every synced session that changed the file
+///
+/// `.smc-sync.json` in the vault remembers each session's size and mtime, so
+/// only new or changed sessions are re-rendered; a note is rewritten only when
+/// its content differs. Notes of sessions Claude Code has since deleted stay
+/// in the vault and in the indexes.
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::digest::WRITE_TOOLS;
+use crate::cmd::export;
+use crate::cmd::show::ThinkingMode;
+use crate::models::{ContentBlock, MessageContent, Record, UserKind};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::redact::{self, Redactor};
+use crate::util::{state, text};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ObsidianOpts {
+    pub vault: PathBuf,
+    pub project: Option<String>,
+    /// Scrub secrets from session notes.
+    pub redact: Option<Redactor>,
+    /// Tags for the session notes' front matter.
+    pub tags: Vec<String>,
+    pub max_tokens: usize,
+}
+
+const STATE_FILE: &str = ".smc-sync.json";
+
+/// Max chars of the first-prompt title in index notes.
+const TITLE_CHARS: usize = 80;
+
+// ── State ──────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SyncState {
+    #[serde(default)]
+    sessions: BTreeMap<String, Synced>,
+}
+
+/// What the index notes need to know about a synced session.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Synced {
+    size: u64,
+    mtime: u64,
+    project: String,
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+impl SyncState {
+    fn load(vault: &Path) -> Result<Self> {
+        let path = vault.join(STATE_FILE);
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).with_context(|| format!("cannot parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+
+    fn save(&self, vault: &Path) -> Result<()> {
+        let path = vault.join(STATE_FILE);
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?).with_context(|| format!("cannot write {}", path.display()))
+    }
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct NoteRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    kind: &'static str,
+    path: String,
+}
+
+#[derive(Serialize, Debug)]
+struct SyncSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    vault: String,
+    sessions_scanned: usize,
+    sessions_changed: usize,
+    notes_written: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ObsidianOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    std::fs::create_dir_all(&opts.vault).with_context(|| format!("cannot create {}", opts.vault.display()))?;
+    let mut sync = SyncState::load(&opts.vault)?;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let mut written = Vec::new();
+    let mut changed = 0usize;
+    for file in &filtered {
+        let mtime = state::mtime_secs(&file.path);
+        let unchanged = sync.sessions.get(&file.session_id).is_some_and(|s| s.size == file.size_bytes && s.mtime == mtime);
+        if unchanged {
+            continue;
+        }
+        changed += 1;
+        let records = crate::cmd::parse_records(file)?;
+        let synced = Synced {
+            size: file.size_bytes,
+            mtime,
+            project: file.project_name.clone(),
+            date: first_date(&records),
+            title: first_prompt(&records),
+            files: changed_files(&records),
+        };
+        let note = session_note(file, &records, &synced, opts);
+        let note = redact::apply(opts.redact.as_ref(), &note);
+        if write_note(&opts.vault, &session_link(&file.session_id), &note)? {
+            written.push(("session", session_link(&file.session_id)));
+        }
+        sync.sessions.insert(file.session_id.clone(), synced);
+    }
+
+    // Index notes are cheap to render from the sync state, so all of them are
+    // rendered and only the ones whose text changed get written.
+    let mut projects: BTreeMap<&str, Vec<(&String, &Synced)>> = BTreeMap::new();
+    let mut touched: BTreeMap<&str, Vec<(&String, &Synced)>> = BTreeMap::new();
+    for (id, s) in &sync.sessions {
+        projects.entry(&s.project).or_default().push((id, s));
+        for f in &s.files {
+            touched.entry(f).or_default().push((id, s));
+        }
+    }
+    for (project, sessions) in &mut projects {
+        let link = project_link(project);
+        if write_note(&opts.vault, &link, &project_note(project, sessions))? {
+            written.push(("project", link));
+        }
+    }
+    for (path, sessions) in &mut touched {
+        let link = file_link(path);
+        if write_note(&opts.vault, &link, &file_note(path, sessions))? {
+            written.push(("file", link));
+        }
+    }
+    sync.save(&opts.vault)?;
+
+    for (kind, link) in &written {
+        let rec = NoteRecord { record_type: "note", kind, path: format!("{}.md", link) };
+        if !em.emit(&rec)? {
+            break;
+        }
+    }
+    em.emit(&SyncSummary {
+        record_type: "summary",
+        vault: opts.vault.display().to_string(),
+        sessions_scanned: filtered.len(),
+        sessions_changed: changed,
+        notes_written: written.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Notes ──────────────────────────────────────────────────────────────────
+
+fn session_note(file: &SessionFile, records: &[Record], synced: &Synced, opts: &ObsidianOpts) -> String {
+    let mut md = export::front_matter(file, records, &opts.tags);
+    md.push_str(&format!("# {}\n\n", heading(&file.session_id, synced)));
+    md.push_str(&format!("**Project:** {}  \n", wikilink(&project_link(&synced.project), &synced.project)));
+    if !synced.files.is_empty() {
+        let links: Vec<String> = synced.files.iter().map(|f| wikilink(&file_link(f), basename(f))).collect();
+        md.push_str(&format!("**Files changed:** {}  \n", links.join(", ")));
+    }
+    md.push_str("\n---\n\n");
+    md.push_str(&export::render_messages(records, ThinkingMode::Collapse, &Default::default()).0);
+    md
+}
+
+fn project_note(project: &str, sessions: &mut [(&String, &Synced)]) -> String {
+    sessions.sort_by(|a, b| b.1.date.cmp(&a.1.date).then_with(|| a.0.cmp(b.0)));
+    let mut md = format!("# {}\n\n{} sessions\n\n", project, sessions.len());
+    for (id, s) in sessions.iter() {
+        md.push_str(&format!("- {}\n", wikilink(&session_link(id), &heading(id, s))));
+    }
+    md
+}
+
+fn file_note(path: &str, sessions: &mut [(&String, &Synced)]) -> String {
+    sessions.sort_by(|a, b| b.1.date.cmp(&a.1.date).then_with(|| a.0.cmp(b.0)));
+    let mut md = format!("# {}\n\n`{}`\n\nChanged in:\n\n", basename(path), path);
+    for (id, s) in sessions.iter() {
+        md.push_str(&format!(
+            "- {} ({})\n",
+            wikilink(&session_link(id), &heading(id, s)),
+            wikilink(&project_link(&s.project), &s.project)
+        ));
+    }
+    md
+}
+
+/// Write `<vault>/<link>.md` unless it already holds `content`; returns
+/// whether it was written.
+fn write_note(vault: &Path, link: &str, content: &str) -> Result<bool> {
+    let path = vault.join(format!("{}.md", link));
+    if std::fs::read(&path).is_ok_and(|old| old == content.as_bytes()) {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    }
+    std::fs::write(&path, content).with_context(|| format!("cannot write {}", path.display()))?;
+    Ok(true)
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn session_link(session_id: &str) -> String {
+    format!("Sessions/{}", session_id)
+}
+
+fn project_link(project: &str) -> String {
+    format!("Projects/{}", slug(project))
+}
+
+fn file_link(path: &str) -> String {
+    format!("Files/{}", slug(path.trim_start_matches('/')))
+}
+
+fn wikilink(target: &str, label: &str) -> String {
+    format!("[[{}|{}]]", target, label.replace('|', "-").replace(['[', ']'], ""))
+}
+
+/// `2025-03-01 · fix the deploy script`, or the short ID without a prompt.
+fn heading(session_id: &str, s: &Synced) -> String {
+    let title = if s.title.is_empty() { &session_id[..8.min(session_id.len())] } else { s.title.as_str() };
+    match &s.date {
+        Some(d) => format!("{} · {}", d, title),
+        None => title.to_string(),
+    }
+}
+
+/// A note name: path separators and characters Obsidian rejects in links
+/// become `-`.
+fn slug(s: &str) -> String {
+    s.chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']') { '-' } else { c })
+        .collect()
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn first_date(records: &[Record]) -> Option<String> {
+    records
+        .iter()
+        .filter_map(Record::as_message)
+        .find_map(|m| m.timestamp.as_ref()?.as_str().get(..10).map(str::to_string))
+}
+
+/// First prompt the user typed, on one line.
+fn first_prompt(records: &[Record]) -> String {
+    records
+        .iter()
+        .filter_map(Record::as_message)
+        .find(|m| m.user_kind() == Some(UserKind::Human))
+        .map(|m| text::oneline(&m.text_content(), TITLE_CHARS))
+        .unwrap_or_default()
+}
+
+/// Paths written by Edit / Write / NotebookEdit calls, sorted.
+fn changed_files(records: &[Record]) -> Vec<String> {
+    let mut files = BTreeSet::new();
+    for msg in records.iter().filter_map(Record::as_message) {
+        let MessageContent::Blocks(blocks) = &msg.message.content else { continue };
+        for block in blocks {
+            let ContentBlock::ToolUse { name, input, .. } = block else { continue };
+            if !WRITE_TOOLS.contains(&name.as_str()) {
+                continue;
+            }
+            if let Some(p) = input.get("file_path").or_else(|| input.get("notebook_path")).and_then(|v| v.as_str()) {
+                files.insert(p.to_string());
+            }
+        }
+    }
+    files.into_iter().collect()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn syncs_incrementally_with_links() {
+        let root = TempDir::new("obsidian");
+        let vault = root.join("vault");
+        let body = concat!(
+            r#"{"type":"user","timestamp":"2025-01-02T10:00:00Z","message":{"role":"user","content":"fix [the] build"}}"#, "\n",
+            r#"{"type":"assistant","timestamp":"2025-01-02T10:01:00Z","message":{"role":"assistant","content":[{"type":"tool_use","name":"Edit","input":{"file_path":"/w/src/lib.rs"}}]}}"#, "\n",
+        );
+        let files = vec![root.session("s1", "alpha", body)];
+        let opts = ObsidianOpts { vault: vault.clone(), project: None, redact: None, tags: vec![], max_tokens: 0 };
+
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let summary = em.into_records().pop().unwrap();
+        assert_eq!((summary["sessions_changed"].as_u64(), summary["notes_written"].as_u64()), (Some(1), Some(3)));
+
+        let note = std::fs::read_to_string(vault.join("Sessions/s1.md")).unwrap();
+        assert!(note.starts_with("---\nsession_id: \"s1\"\n"));
+        assert!(note.contains("**Project:** [[Projects/alpha|alpha]]"));
+        assert!(note.contains("[[Files/w-src-lib.rs|lib.rs]]"));
+        let project = std::fs::read_to_string(vault.join("Projects/alpha.md")).unwrap();
+        assert!(project.contains("- [[Sessions/s1|2025-01-02 · fix the build]]"));
+        assert!(vault.join("Files/w-src-lib.rs.md").exists());
+
+        // Nothing changed: nothing re-rendered or written.
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let summary = em.into_records().pop().unwrap();
+        assert_eq!((summary["sessions_changed"].as_u64(), summary["notes_written"].as_u64()), (Some(0), Some(0)));
+    }
+}
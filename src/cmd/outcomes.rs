@@ -0,0 +1,301 @@
+/// smc outcomes — a heuristic verdict per session, counted per project.
+///
+/// Each session gets the first outcome that fits:
+///
+/// - `completed`: ran `git commit` through Bash.
+/// - `interrupted_heavy`: the user stopped Claude often.
+/// - `error_heavy`: API errors and failed tool calls piled up.
+/// - `abandoned`: ended on an unanswered prompt or an interruption.
+/// - `other`: none of the above, e.g. a question that was simply answered.
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{ContentBlock, MessageContent, Timestamp, UserKind};
+use crate::output::Emitter;
+use crate::session::{self, Session};
+use crate::util::discover::SessionFile;
+
+/// Text Claude Code logs as a user message when a request is cancelled.
+const INTERRUPT_MARKER: &str = "[Request interrupted by user";
+
+/// Interruptions from which a session is `interrupted_heavy`, or their
+/// share of human prompts (with at least two interruptions).
+const HEAVY_INTERRUPTS: usize = 3;
+const HEAVY_INTERRUPT_SHARE: f64 = 0.3;
+
+/// Errors from which a session is `error_heavy`, or their share of tool
+/// calls (with at least three errors).
+const HEAVY_ERRORS: usize = 5;
+const HEAVY_ERROR_SHARE: f64 = 0.25;
+
+const OUTCOMES: [&str; 5] = ["completed", "interrupted_heavy", "error_heavy", "abandoned", "other"];
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct OutcomesOpts {
+    pub project: Option<String>,
+    /// Also emit one `outcome` record per session.
+    pub sessions: bool,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct OutcomeRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    session_id: String,
+    outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_timestamp: Option<Timestamp>,
+    #[serde(flatten)]
+    signals: Signals,
+}
+
+/// What the verdict is based on.
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Signals {
+    /// Bash calls running `git commit`.
+    commits: usize,
+    interruptions: usize,
+    /// API error messages plus failed tool results.
+    errors: usize,
+    human_turns: usize,
+    tool_calls: usize,
+    /// The last message is a prompt or interruption nobody answered.
+    ended_unanswered: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct ProjectOutcomes {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    sessions: usize,
+    /// Sessions per outcome, every outcome listed.
+    outcomes: BTreeMap<&'static str, usize>,
+    /// Share of sessions that are `completed`.
+    completed_pct: f64,
+}
+
+#[derive(Serialize, Debug)]
+struct OutcomesSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    sessions: usize,
+    outcomes: BTreeMap<&'static str, usize>,
+    completed_pct: f64,
+    files_scanned: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &OutcomesOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(p) => f.project_name.to_lowercase().contains(&p.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let mut rows: Vec<OutcomeRecord> = filtered
+        .par_iter()
+        .filter_map(|file| {
+            let s = session::load(file).ok()?;
+            let signals = signals(&s);
+            Some(OutcomeRecord {
+                record_type: "outcome",
+                project: s.project.clone(),
+                session_id: s.session_id.clone(),
+                outcome: classify(&signals),
+                last_timestamp: s.last_timestamp().cloned(),
+                signals,
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| b.last_timestamp.cmp(&a.last_timestamp).then_with(|| a.session_id.cmp(&b.session_id)));
+
+    let mut by_project: BTreeMap<&str, BTreeMap<&'static str, usize>> = BTreeMap::new();
+    let mut total: BTreeMap<&'static str, usize> = OUTCOMES.iter().map(|o| (*o, 0)).collect();
+    for row in &rows {
+        let counts = by_project.entry(&row.project).or_insert_with(|| OUTCOMES.iter().map(|o| (*o, 0)).collect());
+        *counts.entry(row.outcome).or_default() += 1;
+        *total.entry(row.outcome).or_default() += 1;
+    }
+
+    let mut projects: Vec<ProjectOutcomes> = by_project
+        .into_iter()
+        .map(|(project, outcomes)| {
+            let sessions = outcomes.values().sum();
+            ProjectOutcomes {
+                record_type: "project_outcomes",
+                project: project.to_string(),
+                sessions,
+                completed_pct: pct(outcomes["completed"], sessions),
+                outcomes,
+            }
+        })
+        .collect();
+    projects.sort_by(|a, b| b.sessions.cmp(&a.sessions).then_with(|| a.project.cmp(&b.project)));
+
+    let mut cont = true;
+    if opts.sessions {
+        for row in &rows {
+            cont = em.emit(row)?;
+            if !cont {
+                break;
+            }
+        }
+    }
+    if cont {
+        for rec in &projects {
+            if !em.emit(rec)? {
+                break;
+            }
+        }
+    }
+
+    em.emit(&OutcomesSummary {
+        record_type: "summary",
+        sessions: rows.len(),
+        completed_pct: pct(total["completed"], rows.len()),
+        outcomes: total,
+        files_scanned: filtered.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn signals(s: &Session) -> Signals {
+    let mut sig = Signals::default();
+    for m in &s.messages {
+        let blocks = match &m.record.message.content {
+            MessageContent::Blocks(b) => b.as_slice(),
+            MessageContent::Text(_) => &[],
+        };
+        for block in blocks {
+            match block {
+                ContentBlock::ToolUse { name, input, .. } => {
+                    sig.tool_calls += 1;
+                    let command = input.get("command").and_then(Value::as_str).unwrap_or("");
+                    if name == "Bash" && command.contains("git commit") {
+                        sig.commits += 1;
+                    }
+                }
+                ContentBlock::ToolResult { is_error: true, .. } => sig.errors += 1,
+                _ => {}
+            }
+        }
+        if m.record.is_api_error_message {
+            sig.errors += 1;
+        }
+        if m.role == "user" && m.record.text_no_thinking().contains(INTERRUPT_MARKER) {
+            sig.interruptions += 1;
+        } else if m.record.user_kind() == Some(UserKind::Human) {
+            sig.human_turns += 1;
+        }
+    }
+
+    let last = s.messages.iter().rev().find(|m| m.role != "system");
+    sig.ended_unanswered = last.is_some_and(|m| {
+        m.record.user_kind() == Some(UserKind::Human) || m.record.text_no_thinking().contains(INTERRUPT_MARKER)
+    });
+    sig
+}
+
+fn classify(s: &Signals) -> &'static str {
+    let share = |n: usize, of: usize| if of == 0 { 0.0 } else { n as f64 / of as f64 };
+    if s.commits > 0 {
+        "completed"
+    } else if s.interruptions >= HEAVY_INTERRUPTS
+        || (s.interruptions >= 2 && share(s.interruptions, s.human_turns) >= HEAVY_INTERRUPT_SHARE)
+    {
+        "interrupted_heavy"
+    } else if s.errors >= HEAVY_ERRORS || (s.errors >= 3 && share(s.errors, s.tool_calls) >= HEAVY_ERROR_SHARE) {
+        "error_heavy"
+    } else if s.ended_unanswered {
+        "abandoned"
+    } else {
+        "other"
+    }
+}
+
+fn pct(n: usize, of: usize) -> f64 {
+    if of == 0 {
+        0.0
+    } else {
+        (n as f64 / of as f64 * 1000.0).round() / 10.0
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn user(text: &str) -> String {
+        format!(r#"{{"type":"user","message":{{"role":"user","content":"{}"}}}}"#, text) + "\n"
+    }
+
+    fn assistant(content: &str) -> String {
+        format!(r#"{{"type":"assistant","message":{{"role":"assistant","content":{}}}}}"#, content) + "\n"
+    }
+
+    #[test]
+    fn classifies_sessions_per_project() {
+        let dir = TempDir::new("outcomes");
+        let file = |id: &str, project: &str, body: String| dir.session(id, project, &body);
+        let commit = r#"[{"type":"tool_use","name":"Bash","input":{"command":"git commit -m fix"}}]"#;
+        let failed = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","is_error":true,"content":"boom"}]}}"#;
+        let files = vec![
+            file("done", "web", user("fix it") + &assistant(commit) + &assistant(r#""done""#)),
+            file("stopped", "web", user("go") + &user("[Request interrupted by user]") + &user("again") + &user("[Request interrupted by user]")),
+            file("broken", "api", user("run") + &format!("{}\n", failed).repeat(5) + &assistant(r#""sorry""#)),
+            file("left", "api", user("hi") + &assistant(r#""hello""#) + &user("one more thing")),
+            file("answered", "api", user("what is x") + &assistant(r#""x is y""#)),
+        ];
+
+        let opts = OutcomesOpts { project: None, sessions: true, max_tokens: 0 };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+
+        let mut verdicts: Vec<(String, String)> = records
+            .iter()
+            .filter(|r| r["type"] == "outcome")
+            .map(|r| (r["session_id"].as_str().unwrap().into(), r["outcome"].as_str().unwrap().into()))
+            .collect();
+        verdicts.sort();
+        let expected = [
+            ("answered", "other"),
+            ("broken", "error_heavy"),
+            ("done", "completed"),
+            ("left", "abandoned"),
+            ("stopped", "interrupted_heavy"),
+        ];
+        assert_eq!(verdicts, expected.map(|(a, b)| (a.to_string(), b.to_string())));
+
+        let api = records.iter().find(|r| r["type"] == "project_outcomes" && r["project"] == "api").unwrap();
+        assert_eq!(api["sessions"], 3);
+        assert_eq!(api["outcomes"]["completed"], 0);
+        let summary = records.last().unwrap();
+        assert_eq!(summary["completed_pct"], 20.0);
+    }
+}
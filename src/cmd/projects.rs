@@ -1,17 +1,25 @@
 /// smc projects — list projects with session counts, sizes, and date ranges.
 use std::collections::HashMap;
 use std::io::Write;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use serde::Serialize;
 
-use crate::models;
 use crate::output::Emitter;
+use crate::util::cache::{self, SummaryCache};
 use crate::util::discover::SessionFile;
+use crate::util::fuzzy;
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct ProjectsOpts {
+    /// Only projects near this filter, closest first (see [`fuzzy::rank`]).
+    pub matches: Option<String>,
+    /// Summary cache file (`None` = parse every file).
+    pub cache: Option<PathBuf>,
     pub max_tokens: usize,
 }
 
@@ -29,11 +37,14 @@ struct ProjectRecord {
     earliest: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     latest: Option<String>,
+    /// Edits between the `--match` filter and the name (0 = contains it).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance: Option<usize>,
 }
 
 // ── run ────────────────────────────────────────────────────────────────────
 
-pub fn run<W: Write>(_opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+pub fn run<W: Write>(opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
     struct Info {
         sessions: usize,
         total_size: u64,
@@ -41,9 +52,17 @@ pub fn run<W: Write>(_opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitt
         latest: Option<String>,
     }
 
-    let mut projects: HashMap<String, Info> = HashMap::new();
+    // First and last message of every session, read in parallel (or from
+    // the cache); a project spans the earliest first to the latest last.
+    let cache = opts.cache.as_ref().map(SummaryCache::open);
+    let refs: Vec<&SessionFile> = files.iter().collect();
+    let summaries = cache::summaries(&refs, cache.as_ref());
+    if let Some(c) = &cache {
+        c.save()?;
+    }
 
-    for file in files {
+    let mut projects: HashMap<String, Info> = HashMap::new();
+    for (file, summary) in files.iter().zip(summaries) {
         let entry = projects.entry(file.project_name.clone()).or_insert(Info {
             sessions: 0,
             total_size: 0,
@@ -53,25 +72,14 @@ pub fn run<W: Write>(_opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitt
         entry.sessions += 1;
         entry.total_size += file.size_bytes;
 
-        if let Ok(f) = std::fs::File::open(&file.path) {
-            use std::io::BufRead;
-            let reader = std::io::BufReader::new(f);
-            for line in reader.lines().take(5) {
-                let Ok(line) = line else { continue };
-                if let Ok(record) = serde_json::from_str::<models::Record>(&line) {
-                    if let Some(msg) = record.as_message() {
-                        if let Some(ts) = &msg.timestamp {
-                            let ts_date = ts.get(..10).unwrap_or(ts);
-                            if entry.earliest.as_deref().map_or(true, |e| ts_date < e) {
-                                entry.earliest = Some(ts_date.to_string());
-                            }
-                            if entry.latest.as_deref().map_or(true, |l| ts_date > l) {
-                                entry.latest = Some(ts_date.to_string());
-                            }
-                            break;
-                        }
-                    }
-                }
+        if let Some(first) = summary.first_timestamp.map(|t| t.date()) {
+            if entry.earliest.as_deref().map_or(true, |e| first.as_str() < e) {
+                entry.earliest = Some(first);
+            }
+        }
+        if let Some(last) = summary.last_timestamp.map(|t| t.date()) {
+            if entry.latest.as_deref().map_or(true, |l| last.as_str() > l) {
+                entry.latest = Some(last);
             }
         }
     }
@@ -84,6 +92,14 @@ pub fn run<W: Write>(_opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitt
             .cmp(a.1.latest.as_deref().unwrap_or(""))
     });
 
+    let mut distances: HashMap<String, usize> = HashMap::new();
+    if let Some(query) = &opts.matches {
+        let ranked = fuzzy::rank(sorted.iter().map(|(name, _)| name.as_str()), query);
+        distances = ranked.into_iter().map(|m| (m.project, m.distance)).collect();
+        sorted.retain(|(name, _)| distances.contains_key(name));
+        sorted.sort_by_key(|(name, _)| distances[name]);
+    }
+
     for (name, info) in &sorted {
         let rec = ProjectRecord {
             record_type: "project",
@@ -93,6 +109,7 @@ pub fn run<W: Write>(_opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitt
             size_human: crate::cmd::stats::format_bytes(info.total_size),
             earliest: info.earliest.clone(),
             latest: info.latest.clone(),
+            distance: distances.get(name).copied(),
         };
         if !em.emit(&rec)? {
             break;
@@ -110,3 +127,37 @@ pub fn run<W: Write>(_opts: &ProjectsOpts, files: &[SessionFile], em: &mut Emitt
     em.flush()?;
     Ok(())
 }
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn dates_span_whole_sessions() {
+        let dir = TempDir::new("projects");
+        let file = |id: &str, project: &str, stamps: &[&str]| {
+            let body: String = stamps
+                .iter()
+                .map(|ts| format!(r#"{{"type":"user","timestamp":"{}","message":{{"role":"user","content":"hi"}}}}"#, ts) + "\n")
+                .collect();
+            dir.session(id, project, &body)
+        };
+        let mut long = vec!["2025-01-01T10:00:00Z"; 8];
+        long.push("2025-03-09T10:00:00Z");
+        let files = vec![
+            file("s1", "web", &long),
+            file("s2", "web", &["2024-12-30T10:00:00Z"]),
+            file("s3", "api", &["2025-02-01T10:00:00Z"]),
+        ];
+
+        let mut em = Emitter::capturing(0);
+        run(&ProjectsOpts { matches: None, cache: None, max_tokens: 0 }, &files, &mut em).unwrap();
+        let records = em.into_records();
+        assert_eq!((records[0]["name"].as_str(), records[0]["sessions"].as_u64()), (Some("web"), Some(2)));
+        assert_eq!((records[0]["earliest"].as_str(), records[0]["latest"].as_str()), (Some("2024-12-30"), Some("2025-03-09")));
+        assert_eq!(records[1]["name"], "api");
+    }
+}
@@ -1,19 +1,46 @@
 /// smc recent — show most recent messages across all sessions.
+///
+/// Each session is read backwards, newest message first, and the sessions
+/// are merged on timestamp, so the top N is exact however the messages are
+/// spread across files, and a file is only read as far back as needed.
+/// Sessions are opened in batches, most recently written first, which keeps
+/// the open files bounded and lets older batches be skipped.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::io::Write;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
 use serde::Serialize;
 
-use crate::models::Record;
+use crate::models::{Record, RoleFilter, Timestamp};
 use crate::output::Emitter;
 use crate::util::discover::SessionFile;
+use crate::util::tail::ReverseLines;
+use crate::util::time::{self, TimeFormat};
+use crate::util::{policy, state};
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct RecentOpts {
+    /// Messages to show (0 = every message in range).
     pub limit: usize,
+    /// Only messages at or after this bound (date, RFC 3339, or a span such
+    /// as `48h`; see [`time::parse_bound`]).
+    pub after: Option<String>,
+    /// Only messages before this bound.
+    pub before: Option<String>,
+    /// A [`RoleFilter`] spec, e.g. `user,asst` or `any-human`.
     pub role: Option<String>,
     pub project: Option<String>,
+    pub time_format: TimeFormat,
+    /// Add a `file://` URL to each message.
+    pub links: bool,
+    /// Keep messages holding smc's own output; see [`policy`].
+    pub include_smc: bool,
     pub max_tokens: usize,
 }
 
@@ -26,14 +53,27 @@ struct RecentRecord {
     project: String,
     session_id: String,
     role: String,
-    timestamp: String,
+    /// Omitted when the message has none (formerly `""`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age: Option<String>,
     text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
 }
 
+/// Session files read at once. Each open reader holds a file descriptor;
+/// the default limit is 256 on macOS, and rayon and stdio need some too.
+const MAX_OPEN: usize = 32;
+
 // ── run ────────────────────────────────────────────────────────────────────
 
 pub fn run<W: Write>(opts: &RecentOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
-    let filtered: Vec<&SessionFile> = files
+    let roles = opts.role.as_deref().map(RoleFilter::parse).transpose()?;
+    let (after, before) = time::parse_range(opts.after.as_deref(), opts.before.as_deref())?;
+    let limit = if opts.limit == 0 { usize::MAX } else { opts.limit };
+    let mut filtered: Vec<&SessionFile> = files
         .iter()
         .filter(|f| {
             if let Some(proj) = &opts.project {
@@ -42,71 +82,219 @@ pub fn run<W: Write>(opts: &RecentOpts, files: &[SessionFile], em: &mut Emitter<
                 true
             }
         })
+        // Not written to since the bound: nothing in range.
+        .filter(|f| after.map_or(true, |a| state::mtime_secs(&f.path) >= a.timestamp().max(0) as u64))
         .collect();
 
-    let mut all: Vec<RecentRecord> = Vec::new();
+    // Newest first, so once the top N is settled the older files needn't
+    // be opened at all: nothing in a file is newer than its last write.
+    filtered.sort_by_cached_key(|f| Reverse(state::mtime_secs(&f.path)));
 
-    for file in &filtered {
-        let Ok(f) = std::fs::File::open(&file.path) else { continue };
+    let scan = Scan { opts, roles, after, before };
+    let mut top: Vec<RecentRecord> = Vec::new();
+    for batch in filtered.chunks(MAX_OPEN) {
+        let oldest = top.last().and_then(|r| r.timestamp.as_ref()?.utc);
+        if top.len() >= limit && oldest.is_some_and(|t| t.timestamp() > state::mtime_secs(&batch[0].path) as i64) {
+            break;
+        }
+        let mut streams = Vec::with_capacity(batch.len());
+        for file in batch {
+            match ReverseLines::open(&file.path) {
+                Ok(lines) => streams.push((*file, lines)),
+                Err(e) => em.warn(Some(&file.path.display().to_string()), &format!("cannot open: {}", e)),
+            }
+        }
+        top.extend(scan.merge(&mut streams, limit));
+        // Stable, so ties keep the earlier batch first.
+        top.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        top.truncate(limit);
+    }
 
-        use std::io::BufRead;
-        let reader = std::io::BufReader::new(f);
+    let mut count = 0;
+    for mut rec in top {
+        rec.age = opts.time_format.render(&mut rec.timestamp);
+        count += 1;
+        if !em.emit(&rec)? {
+            break;
+        }
+    }
 
-        let mut last_lines: Vec<String> = Vec::new();
-        for line in reader.lines() {
-            let Ok(line) = line else { continue };
-            if line.trim().is_empty() {
-                continue;
-            }
-            last_lines.push(line);
-            if last_lines.len() > opts.limit * 2 + 50 {
-                last_lines.drain(..last_lines.len() - opts.limit - 25);
+    let summary = crate::output::SummaryRecord {
+        record_type: "summary",
+        count,
+        files_scanned: Some(filtered.len()),
+        elapsed_ms: 0,
+    };
+    em.emit(&summary)?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// The filters a message must pass.
+struct Scan<'a> {
+    opts: &'a RecentOpts,
+    roles: Option<RoleFilter>,
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+}
+
+impl Scan<'_> {
+    /// The newest `limit` messages across `streams`, newest first. Each
+    /// session's newest message, then a merge: take the newest head, refill
+    /// it from that session, repeat.
+    fn merge(&self, streams: &mut [(&SessionFile, ReverseLines)], limit: usize) -> Vec<RecentRecord> {
+        let mut heads: Vec<Option<RecentRecord>> =
+            streams.par_iter_mut().map(|(file, lines)| self.next_record(file, lines)).collect();
+        let mut heap: BinaryHeap<(Option<Timestamp>, Reverse<usize>)> =
+            heads.iter().enumerate().filter_map(|(i, h)| Some((h.as_ref()?.timestamp.clone(), Reverse(i)))).collect();
+
+        let mut out = Vec::new();
+        while out.len() < limit {
+            let Some((_, Reverse(i))) = heap.pop() else { break };
+            let Some(rec) = heads[i].take() else { continue };
+            let (file, lines) = &mut streams[i];
+            heads[i] = self.next_record(file, lines);
+            if let Some(next) = &heads[i] {
+                heap.push((next.timestamp.clone(), Reverse(i)));
             }
+            out.push(rec);
         }
+        out
+    }
 
-        for line in last_lines.iter().rev().take(opts.limit + 10) {
-            let Ok(record) = serde_json::from_str::<Record>(line) else { continue };
+    /// The next message of `file` going back that passes the filters.
+    /// Skips those at or after `before`, and stops at the first one older
+    /// than `after`: the rest are older still.
+    fn next_record(&self, file: &SessionFile, lines: &mut ReverseLines) -> Option<RecentRecord> {
+        for line in lines {
+            let Ok(line) = line else { return None };
+            let line = String::from_utf8_lossy(&line);
+            if line.trim().is_empty() || policy::skips(self.opts.include_smc, &line) {
+                continue;
+            }
+            let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
             let Some(msg) = record.as_message() else { continue };
+            if !time::in_range(msg.time(), self.after, None) {
+                return None;
+            }
+            if !time::in_range(msg.time(), None, self.before) {
+                continue;
+            }
 
             let role = record.role().to_string();
-            if let Some(rf) = &opts.role {
-                if role != *rf {
-                    continue;
-                }
+            if self.roles.as_ref().is_some_and(|r| !r.matches(&role, msg.has_tool_result())) {
+                continue;
             }
 
-            let ts = msg.timestamp.clone().unwrap_or_default();
             let text = msg.text_content();
             let preview: String = text.chars().take(120).collect::<String>().replace('\n', " ");
-
-            all.push(RecentRecord {
+            return Some(RecentRecord {
                 record_type: "recent",
                 project: file.project_name.clone(),
                 session_id: file.session_id.clone(),
                 role,
-                timestamp: ts,
+                timestamp: msg.timestamp.clone(),
+                age: None,
                 text: preview,
+                url: self.opts.links.then(|| file.file_url()),
             });
         }
+        None
     }
+}
 
-    all.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+// ── Tests ──────────────────────────────────────────────────────────────────
 
-    let show = std::cmp::min(opts.limit, all.len());
-    for rec in all.iter().take(show) {
-        if !em.emit(rec)? {
-            break;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn merges_sessions_newest_first() {
+        let dir = TempDir::new("recent");
+        let file = |id: &str, msgs: &[(&str, &str)]| {
+            let body: String = msgs
+                .iter()
+                .map(|(ts, text)| {
+                    format!(r#"{{"type":"user","timestamp":"{}","message":{{"role":"user","content":"{}"}}}}"#, ts, text)
+                        + "\n"
+                })
+                .collect();
+            dir.session(id, "p", &body)
+        };
+        let chatty: Vec<(String, String)> =
+            (0..50).map(|i| (format!("2025-01-02T10:{:02}:00Z", i), format!("c{}", i))).collect();
+        let chatty: Vec<(&str, &str)> = chatty.iter().map(|(t, m)| (t.as_str(), m.as_str())).collect();
+        let files = vec![
+            file("s1", &chatty),
+            file("s2", &[("2025-01-01T09:00:00Z", "old"), ("2025-01-02T10:48:30Z", "quiet")]),
+        ];
+        let opts = |limit, after: Option<&str>, before: Option<&str>| RecentOpts {
+            limit,
+            after: after.map(String::from),
+            before: before.map(String::from),
+            role: None,
+            project: None,
+            time_format: TimeFormat::default(),
+            links: false,
+            include_smc: false,
+            max_tokens: 0,
+        };
+        let texts = |opts: RecentOpts| {
+            let mut em = Emitter::capturing(0);
+            run(&opts, &files, &mut em).unwrap();
+            let records = em.into_records();
+            records.iter().filter_map(|r| r["text"].as_str().map(String::from)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(texts(opts(3, None, None)), ["c49", "quiet", "c48"]);
+        let all = texts(opts(0, Some("2025-01-02T10:47:00Z"), None));
+        assert_eq!(all, ["c49", "quiet", "c48", "c47"]);
+        assert_eq!(texts(opts(0, None, None)).last().map(String::as_str), Some("old"));
+        assert_eq!(texts(opts(2, None, Some("2025-01-02T10:48:00Z"))), ["c47", "c46"]);
+        let window = texts(opts(0, Some("2025-01-02T10:00:00Z"), Some("2025-01-02T10:02:00Z")));
+        assert_eq!(window, ["c1", "c0"]);
     }
 
-    let summary = crate::output::SummaryRecord {
-        record_type: "summary",
-        count: show,
-        files_scanned: Some(filtered.len()),
-        elapsed_ms: 0,
-    };
-    em.emit(&summary)?;
+    #[test]
+    fn more_sessions_than_open_readers() {
+        let dir = TempDir::new("recent-many");
+        let msg = |i: usize| {
+            format!(
+                r#"{{"type":"user","timestamp":"2025-01-02T{:02}:{:02}:00Z","message":{{"role":"user","content":"m{}"}}}}"#,
+                i / 60,
+                i % 60,
+                i
+            )
+        };
+        let n = MAX_OPEN * 2 + 10;
+        let mut files: Vec<SessionFile> = (0..n).map(|i| dir.session(&format!("s{}", i), "p", &msg(i))).collect();
+        files.push(SessionFile { path: dir.join("gone.jsonl"), ..files[0].clone() });
 
-    em.flush()?;
-    Ok(())
+        let opts = RecentOpts {
+            limit: 3,
+            after: None,
+            before: None,
+            role: None,
+            project: None,
+            time_format: TimeFormat::default(),
+            links: false,
+            include_smc: false,
+            max_tokens: 0,
+        };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+        let texts: Vec<&str> = records.iter().filter(|r| r["type"] == "recent").map(|r| r["text"].as_str().unwrap()).collect();
+        let last = |k: usize| format!("m{}", n - k);
+        assert_eq!(texts, [last(1), last(2), last(3)]);
+        let warnings: Vec<_> = records.iter().filter(|r| r["type"] == "warning").collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0]["file"].as_str().unwrap().ends_with("gone.jsonl"));
+        assert_eq!(records.last().unwrap()["files_scanned"], n + 1);
+    }
 }
@@ -0,0 +1,323 @@
+/// smc results — search tool output: result payloads and the structured
+/// `toolUseResult` Claude Code stores beside them.
+///
+/// Each hit is shown with the call that produced it (tool name and input,
+/// found by `tool_use_id` earlier in the session) and snippets around the
+/// matches, rather than the message preview regular search hits carry:
+/// results are often megabytes of logs or file content.
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cmd::export;
+use crate::models::{ContentBlock, MessageContent, Record, Timestamp, ToolFilter};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::time::{self, DateRange};
+
+/// Most snippets per hit.
+const MAX_SNIPPETS: usize = 3;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ResultsOpts {
+    pub queries: Vec<String>,
+    pub is_regex: bool,
+    /// Require every term in the same result.
+    pub and_mode: bool,
+    /// A [`ToolFilter`] spec for the producing call, e.g. `Bash` or `~mcp__`.
+    pub tool: Option<String>,
+    pub project: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+    /// Hits to show, newest first (0 = all).
+    pub limit: usize,
+    /// Chars of result text per snippet.
+    pub context: usize,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct ResultHit {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    session_id: String,
+    /// Line of the message holding the result.
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    /// The producing call; absent when it isn't in the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_use_id: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    is_error: bool,
+    /// `content` (the result block) or `tool_use_result` (the structured copy).
+    source: &'static str,
+    matched_query: String,
+    /// Size of the searched payload.
+    result_bytes: usize,
+    snippets: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ResultsSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    query: String,
+    count: usize,
+    total: usize,
+    files_scanned: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ResultsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    anyhow::ensure!(!opts.queries.is_empty(), "search query cannot be empty");
+    let start = std::time::Instant::now();
+    let terms = Terms::new(&opts.queries, opts.is_regex, opts.and_mode)?;
+    let tools = opts.tool.as_deref().map(ToolFilter::parse).transpose()?;
+    let range = time::parse_range(opts.after.as_deref(), opts.before.as_deref())?;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let mut hits: Vec<ResultHit> = filtered
+        .par_iter()
+        .flat_map_iter(|file| scan_file(file, &terms, tools.as_ref(), range, opts.context))
+        .collect();
+    hits.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    let show = if opts.limit > 0 { opts.limit.min(hits.len()) } else { hits.len() };
+    let mut count = 0usize;
+    for hit in hits.iter().take(show) {
+        if !em.emit(hit)? {
+            break;
+        }
+        count += 1;
+    }
+
+    em.emit(&ResultsSummary {
+        record_type: "summary",
+        query: opts.queries.join(", "),
+        count,
+        total: hits.len(),
+        files_scanned: filtered.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Matching ───────────────────────────────────────────────────────────────
+
+/// Query terms as case-insensitive literals, or regexes with `-e`.
+struct Terms {
+    res: Vec<Regex>,
+    and_mode: bool,
+}
+
+impl Terms {
+    fn new(queries: &[String], is_regex: bool, and_mode: bool) -> Result<Self> {
+        let res = queries
+            .iter()
+            .map(|q| if is_regex { Regex::new(q) } else { RegexBuilder::new(&regex::escape(q)).case_insensitive(true).build() })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Self { res, and_mode })
+    }
+
+    /// The matched text of the first term found, or of every term (`and_mode`).
+    fn matched(&self, text: &str) -> Option<String> {
+        if self.and_mode {
+            let found: Option<Vec<&str>> = self.res.iter().map(|re| re.find(text).map(|m| m.as_str())).collect();
+            return found.map(|f| f.join(" + "));
+        }
+        self.res.iter().find_map(|re| re.find(text)).map(|m| m.as_str().to_string())
+    }
+
+    /// Up to [`MAX_SNIPPETS`] windows of `width` chars around matches, each
+    /// starting past the previous one.
+    fn snippets(&self, text: &str, width: usize) -> Vec<String> {
+        let mut starts: Vec<usize> =
+            self.res.iter().flat_map(|re| re.find_iter(text).filter(|m| !m.is_empty()).map(|m| m.start())).collect();
+        starts.sort_unstable();
+
+        let mut out = Vec::new();
+        let mut covered = 0;
+        for start in starts {
+            if start < covered || out.len() == MAX_SNIPPETS {
+                continue;
+            }
+            let from = text[..start].char_indices().rev().take(width / 2).last().map_or(start, |(i, _)| i);
+            let window: String = text[from..].chars().take(width).collect();
+            covered = from + window.len();
+            out.push(window);
+        }
+        out
+    }
+}
+
+// ── Per-file scan ──────────────────────────────────────────────────────────
+
+fn scan_file(
+    file: &SessionFile,
+    terms: &Terms,
+    tools: Option<&ToolFilter>,
+    (after, before): DateRange,
+    context: usize,
+) -> Vec<ResultHit> {
+    let mut hits = Vec::new();
+    let Ok(f) = std::fs::File::open(&file.path) else { return hits };
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+
+    // tool_use_id → (name, input, line) of every call seen so far.
+    let mut calls: HashMap<String, (String, Value, usize)> = HashMap::new();
+    for (line_num, line) in reader.lines().enumerate() {
+        let Ok(line) = line else { continue };
+        // Both calls and results mention it ("tool_use", "tool_use_id").
+        if !line.contains("tool_use") {
+            continue;
+        }
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+        let Some(msg) = record.as_message() else { continue };
+        let MessageContent::Blocks(blocks) = &msg.message.content else { continue };
+
+        for block in blocks {
+            match block {
+                ContentBlock::ToolUse { id: Some(id), name, input } => {
+                    calls.insert(id.clone(), (name.clone(), input.clone(), line_num + 1));
+                }
+                ContentBlock::ToolResult { tool_use_id, content, is_error } => {
+                    if !time::in_range(msg.time(), after, before) {
+                        continue;
+                    }
+                    let call = tool_use_id.as_ref().and_then(|id| calls.get(id));
+                    if tools.is_some_and(|t| !call.is_some_and(|(name, _, _)| t.matches(name))) {
+                        continue;
+                    }
+                    let content = content.as_ref().map(export::result_text).unwrap_or_default();
+                    let structured = msg.tool_use_result.as_ref().map(string_leaves).unwrap_or_default();
+                    let Some((source, text, matched)) = [("content", content), ("tool_use_result", structured)]
+                        .into_iter()
+                        .find_map(|(source, text)| terms.matched(&text).map(|m| (source, text, m)))
+                    else {
+                        continue;
+                    };
+                    hits.push(ResultHit {
+                        record_type: "result_hit",
+                        project: file.project_name.clone(),
+                        session_id: file.session_id.clone(),
+                        line: line_num + 1,
+                        timestamp: msg.timestamp.clone(),
+                        tool: call.map(|c| c.0.clone()),
+                        input: call.map(|c| c.1.clone()),
+                        call_line: call.map(|c| c.2),
+                        tool_use_id: tool_use_id.clone(),
+                        is_error: *is_error,
+                        source,
+                        matched_query: matched,
+                        result_bytes: text.len(),
+                        snippets: terms.snippets(&text, context),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    hits
+}
+
+/// Every string in a structured result (stdout, stderr, file content, …),
+/// one per line; keys and numbers are left out.
+fn string_leaves(v: &Value) -> String {
+    fn walk<'a>(v: &'a Value, out: &mut Vec<&'a str>) {
+        match v {
+            Value::String(s) if !s.is_empty() => out.push(s),
+            Value::Array(items) => items.iter().for_each(|i| walk(i, out)),
+            Value::Object(map) => map.values().for_each(|i| walk(i, out)),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(v, &mut out);
+    out.join("\n")
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn finds_results_with_their_calls() {
+        let dir = TempDir::new("results");
+        let log = format!("{}error: connection refused on :5432\n{}", "ok\n".repeat(100), "ok\n".repeat(100));
+        let lines = [
+            serde_json::json!({"type":"assistant","timestamp":"2025-01-01T10:00:00Z","message":{"role":"assistant","content":[
+                {"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}},
+            ]}}),
+            serde_json::json!({"type":"user","timestamp":"2025-01-01T10:01:00Z","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"t1","content":log},
+            ]}}),
+            serde_json::json!({"type":"assistant","timestamp":"2025-01-01T10:02:00Z","message":{"role":"assistant","content":[
+                {"type":"tool_use","id":"t2","name":"Read","input":{"file_path":"db.toml"}},
+            ]}}),
+            serde_json::json!({"type":"user","timestamp":"2025-01-01T10:03:00Z","toolUseResult":{"file":{"content":"port = 5432"}},
+                "message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t2","content":"(shown)"}]}}),
+        ];
+        let body: String = lines.iter().map(|l| l.to_string() + "\n").collect();
+        let files = vec![dir.session("s1", "p", &body)];
+
+        let opts = |tool: Option<&str>| ResultsOpts {
+            queries: vec!["5432".into()],
+            is_regex: false,
+            and_mode: false,
+            tool: tool.map(String::from),
+            project: None,
+            after: None,
+            before: None,
+            limit: 0,
+            context: 40,
+            max_tokens: 0,
+        };
+        let mut em = Emitter::capturing(0);
+        run(&opts(None), &files, &mut em).unwrap();
+        let records = em.into_records();
+        assert_eq!(records.len(), 3);
+        let (read, bash) = (&records[0], &records[1]);
+        assert_eq!((read["tool"].as_str(), read["source"].as_str()), (Some("Read"), Some("tool_use_result")));
+        assert_eq!(read["input"]["file_path"], "db.toml");
+        assert_eq!((bash["line"].as_u64(), bash["call_line"].as_u64()), (Some(2), Some(1)));
+        assert_eq!(bash["input"]["command"], "cargo test");
+        let snippet = bash["snippets"][0].as_str().unwrap();
+        assert!(snippet.contains("refused on :5432\nok") && snippet.chars().count() == 40, "{:?}", snippet);
+        assert_eq!(bash["result_bytes"].as_u64(), Some(log.len() as u64));
+
+        let mut em = Emitter::capturing(0);
+        run(&opts(Some("Bash")), &files, &mut em).unwrap();
+        assert_eq!(em.into_records().len(), 2);
+    }
+}
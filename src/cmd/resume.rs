@@ -0,0 +1,261 @@
+/// smc resume — find a session and continue it with `claude --resume`.
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::cmd::search::SearchOpts;
+use crate::cmd::tag::TagStore;
+use crate::models::{Timestamp, UserKind};
+use crate::output::Emitter;
+use crate::util::discover::{self, SessionFile};
+use crate::util::text;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ResumeOpts {
+    /// Session ID prefix or tag, or search terms when neither matches.
+    pub query: Vec<String>,
+    /// Only print the resolved session and command; don't exec.
+    pub dry_run: bool,
+    /// The `claude` executable to run.
+    pub claude_bin: String,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct ResumeRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: String,
+    project: String,
+    /// How the session was found: `id`, `tag`, or `search`.
+    resolved_by: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_timestamp: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_timestamp: Option<Timestamp>,
+    messages: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<String>,
+    command: Vec<String>,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ResumeOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let (file, resolved_by) = resolve(&opts.query, files, &TagStore::load()?)?;
+    let mut rec = summarize(file, resolved_by)?;
+    rec.command = vec![opts.claude_bin.clone(), "--resume".into(), file.session_id.clone()];
+
+    if let Some(cwd) = &rec.cwd {
+        if !Path::new(cwd).is_dir() {
+            em.warn(Some(cwd), "project directory no longer exists; resuming from the current directory");
+            rec.cwd = None;
+        }
+    }
+
+    em.emit(&rec)?;
+    em.flush()?;
+    if opts.dry_run {
+        return Ok(());
+    }
+
+    let mut cmd = std::process::Command::new(&rec.command[0]);
+    cmd.args(&rec.command[1..]);
+    if let Some(cwd) = &rec.cwd {
+        cmd.current_dir(cwd);
+    }
+    exec(cmd).with_context(|| format!("cannot run {}", opts.claude_bin))
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// A single argument that prefixes a session ID resolves directly, then one
+/// naming a tag (the newest tagged session on ties); anything else is a
+/// search, and the session with the most hits (newest hit on ties) wins.
+fn resolve<'a>(query: &[String], files: &'a [SessionFile], tags: &TagStore) -> Result<(&'a SessionFile, &'static str)> {
+    anyhow::ensure!(!query.is_empty(), "resume needs a session ID, tag, or search terms");
+    if let [word] = query {
+        if files.iter().any(|f| f.session_id.starts_with(word.as_str())) {
+            return Ok((discover::find_session(files, word)?, "id"));
+        }
+        let tagged = tags.sessions_with(word);
+        let newest = files
+            .iter()
+            .filter(|f| tagged.contains(&f.session_id.as_str()))
+            .max_by_key(|f| std::fs::metadata(&f.path).and_then(|m| m.modified()).ok());
+        if let Some(file) = newest {
+            return Ok((file, "tag"));
+        }
+    }
+
+    // Every hit counts toward the ranking; only one line of each is kept.
+    let opts = SearchOpts::builder()
+        .queries(query.to_vec())
+        .and_mode(true)
+        .max_results(0)
+        .truncate(1)
+        .build();
+    let mut cap = Emitter::capturing(0);
+    crate::cmd::search::run(&opts, files, &mut cap)?;
+
+    let mut ranked: HashMap<String, (usize, Option<Timestamp>)> = HashMap::new();
+    for hit in cap.into_records().iter().filter(|r| r["type"] == "match") {
+        let Some(id) = hit["session_id"].as_str() else { continue };
+        let ts = hit["timestamp"].as_str().map(Timestamp::new);
+        let entry = ranked.entry(id.to_string()).or_insert((0, None));
+        entry.0 += 1;
+        entry.1 = entry.1.take().max(ts);
+    }
+    let best = ranked
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1))
+        .map(|(id, _)| id)
+        .with_context(|| format!("no session found matching '{}'", query.join(" ")))?;
+
+    let file = files.iter().find(|f| f.session_id == best).expect("hit came from these files");
+    Ok((file, "search"))
+}
+
+fn summarize(file: &SessionFile, resolved_by: &'static str) -> Result<ResumeRecord> {
+    let records = crate::cmd::parse_records(file)?;
+    let mut rec = ResumeRecord {
+        record_type: "resume",
+        session_id: file.session_id.clone(),
+        project: file.project_name.clone(),
+        resolved_by,
+        cwd: None,
+        git_branch: None,
+        first_timestamp: None,
+        last_timestamp: None,
+        messages: 0,
+        preview: None,
+        command: Vec::new(),
+    };
+
+    for msg in records.iter().filter_map(|r| r.as_message()) {
+        rec.messages += 1;
+        if rec.first_timestamp.is_none() {
+            rec.first_timestamp = msg.timestamp.clone();
+        }
+        if msg.timestamp.is_some() {
+            rec.last_timestamp = msg.timestamp.clone();
+        }
+        // The latest cwd/branch is where the conversation left off.
+        if msg.cwd.is_some() {
+            rec.cwd = msg.cwd.clone();
+        }
+        if msg.git_branch.is_some() {
+            rec.git_branch = msg.git_branch.clone();
+        }
+        if rec.preview.is_none() && msg.user_kind() == Some(UserKind::Human) {
+            rec.preview = Some(text::oneline(&msg.text_no_thinking(), 200));
+        }
+    }
+    Ok(rec)
+}
+
+/// Replace this process on Unix; elsewhere, run to completion and exit with its status.
+#[cfg(unix)]
+fn exec(mut cmd: std::process::Command) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    Err(cmd.exec().into())
+}
+
+#[cfg(not(unix))]
+fn exec(mut cmd: std::process::Command) -> Result<()> {
+    let status = cmd.status()?;
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn user(text: &str, ts: &str, cwd: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{}","cwd":"{}","message":{{"role":"user","content":"{}"}}}}"#,
+            ts, cwd, text
+        ) + "\n"
+    }
+
+    fn resolved<'a>(query: &[&str], files: &'a [SessionFile], tags: &TagStore) -> (&'a str, &'static str) {
+        let query: Vec<String> = query.iter().map(|q| q.to_string()).collect();
+        let (file, by) = resolve(&query, files, tags).unwrap();
+        (&file.session_id, by)
+    }
+
+    #[test]
+    fn prefix_then_tag_then_search() {
+        let dir = TempDir::new("resume-resolve");
+        let files = vec![
+            dir.session("aaa111", "p", &user("fix the needle parser", "2026-01-01T00:00:00Z", "/r")),
+            dir.session("bbb222", "p", &user("unrelated", "2026-01-02T00:00:00Z", "/r")),
+        ];
+        let mut tags = TagStore::default();
+        tags.add("bbb222", "wip");
+
+        assert_eq!(resolved(&["aaa"], &files, &tags), ("aaa111", "id"));
+        assert_eq!(resolved(&["WIP"], &files, &tags), ("bbb222", "tag"));
+        assert_eq!(resolved(&["needle", "parser"], &files, &tags), ("aaa111", "search"));
+        assert!(resolve(&["needle".into(), "nowhere".into()], &files, &tags).is_err());
+        // Tags of sessions that are gone fall through to search.
+        tags.add("ccc333", "needle");
+        assert_eq!(resolved(&["needle"], &files, &tags), ("aaa111", "search"));
+    }
+
+    #[test]
+    fn most_hits_win_newest_breaks_ties() {
+        let dir = TempDir::new("resume-rank");
+        let many: String = (0..300).map(|i| user("needle", &format!("2026-01-01T00:{:02}:00Z", i % 60), "/r")).collect();
+        let files = vec![
+            // Past the old 200-hit cap: every hit still counts.
+            dir.session("aaa111", "p", &many),
+            dir.session("bbb222", "p", &(user("needle", "2026-02-01T00:00:00Z", "/r").repeat(2))),
+            dir.session("ccc333", "p", &(user("needle", "2026-03-01T00:00:00Z", "/r").repeat(301))),
+        ];
+        let tags = TagStore::default();
+        assert_eq!(resolved(&["needle"], &files, &tags), ("ccc333", "search"));
+
+        let files = vec![
+            dir.session("ddd444", "q", &user("tie", "2026-01-01T00:00:00Z", "/r")),
+            dir.session("eee555", "q", &user("tie", "2026-01-05T00:00:00Z", "/r")),
+            dir.session("fff666", "q", &user("tie", "2026-01-03T00:00:00Z", "/r")),
+        ];
+        assert_eq!(resolved(&["tie"], &files, &tags), ("eee555", "search"));
+    }
+
+    #[test]
+    fn dry_run_emits_command_without_missing_cwd() {
+        let dir = TempDir::new("resume-run");
+        let cwd = dir.join("gone");
+        let body = user("first question", "2026-01-01T00:00:00Z", "/r")
+            + &user("second", "2026-01-01T01:00:00Z", cwd.to_str().unwrap());
+        let files = vec![dir.session("aaa111", "p", &body)];
+        let opts = ResumeOpts { query: vec!["aaa".into()], dry_run: true, claude_bin: "my-claude".into() };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+
+        let recs = em.into_records();
+        assert_eq!(recs.iter().filter(|r| r["type"] == "warning").count(), 1);
+        let rec = recs.iter().find(|r| r["type"] == "resume").unwrap();
+        assert_eq!(rec["command"], serde_json::json!(["my-claude", "--resume", "aaa111"]));
+        assert_eq!(rec["messages"], 2);
+        assert_eq!(rec["preview"], "first question");
+        assert_eq!(rec["last_timestamp"], "2026-01-01T01:00:00Z");
+        assert!(rec.get("cwd").is_none());
+    }
+}
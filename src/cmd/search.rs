@@ -1,23 +1,47 @@
 /// smc search — parallel full-text search across Claude Code conversation logs.
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use rayon::prelude::*;
-use regex::Regex;
-use serde::Serialize;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::value::{to_raw_value, RawValue};
 
-use crate::models::Record;
+use crate::models::{Record, RecordRef, RoleFilter, Timestamp, ToolFilter, Usage};
 use crate::output::Emitter;
 use crate::util::discover::SessionFile;
+use crate::util::progress::{Progress, Silent};
+use crate::util::redact::{self, Redactor};
+use crate::util::{clipboard, html, plugin, policy, stem, text};
+use crate::util::time::{self, DateRange, TimeFormat};
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+/// Search options. Start from [`SearchOpts::builder`] (or `Default`, which
+/// matches the CLI defaults); new fields may be added in minor releases.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct SearchOpts {
     pub queries: Vec<String>,
     pub is_regex: bool,
+    /// Match plain terms as whole words after English stemming, so
+    /// "deploying" finds "deployed" and "deployment". Ignored with `is_regex`.
+    pub stem: bool,
     pub and_mode: bool,
+    /// Match sessions, not messages: every term must appear somewhere in
+    /// the session, and each term's first hit there is returned.
+    /// `max_results` then counts sessions.
+    pub session_all: bool,
+    /// Extra words each query term also matches, keyed by term (ignoring
+    /// case): `auth` → `authentication`, `login`. Empty = no expansion.
+    pub synonyms: BTreeMap<String, Vec<String>>,
+    /// A [`RoleFilter`] spec, e.g. `user,asst` or `any-human`.
     pub role: Option<String>,
+    /// A [`ToolFilter`] spec, e.g. `Bash,Edit` or `~mcp__`.
     pub tool: Option<String>,
     pub project: Option<String>,
     pub after: Option<String>,
@@ -29,12 +53,171 @@ pub struct SearchOpts {
     pub no_thinking: bool,
     pub max_results: usize,
     pub include_smc: bool,
+    /// Only messages holding smc's own output (implies `include_smc`).
+    pub only_smc: bool,
     pub exclude_session: Option<String>,
+    /// Max chars of message text per hit (0 = no truncation).
+    pub truncate: usize,
+    /// Emit compact one-line records (session, line, role, 100-char preview).
+    pub oneline: bool,
+    /// Chars of context per `snippets` entry, centred on a match (0 = no
+    /// snippets unless `all_occurrences` is set).
+    pub snippet_chars: usize,
+    /// One snippet per match position in the message, not just the first.
+    pub all_occurrences: bool,
+    /// Add cwd, uuid, parent_uuid, model, and token usage to each hit.
+    pub rich: bool,
+    pub time_format: TimeFormat,
+    /// Add a `file://` URL to each hit.
+    pub links: bool,
+    /// Scrub secrets from hit text (matching still runs on the original).
+    pub redact: Option<Redactor>,
+    /// Also put the hits on the system clipboard as a markdown list,
+    /// wrapped in smc tags.
+    pub copy: bool,
+    /// Copy the list without the smc tags.
+    pub no_tag: bool,
+    /// Also write the hits to this markdown file, as the clipboard list
+    /// under a heading with the query and time. strftime tokens in the path
+    /// (`%Y-%m-%d`) are filled in from the local time.
+    pub md: Option<PathBuf>,
+    /// Append to `md` instead of replacing it, so repeated searches build
+    /// up a log.
+    pub md_append: bool,
+    /// Also write one markdown file per session with hits into this dir.
+    pub md_split: Option<PathBuf>,
+    /// Messages shown either side of each hit in `md_split` files.
+    pub md_context: usize,
+    /// Also write the hits to this file as a standalone HTML report, with
+    /// each full message in a collapsible block and a text filter box.
+    pub html: Option<PathBuf>,
+    /// External command the hits are piped through (JSONL in, JSONL out).
+    pub post_filter: Option<String>,
     /// Hard cap on output tokens (0 = unlimited).
     pub max_tokens: usize,
 }
 
-pub const SMC_TAG: &str = "<smc-cc-cli>";
+impl Default for SearchOpts {
+    fn default() -> Self {
+        Self {
+            queries: Vec::new(),
+            is_regex: false,
+            stem: false,
+            and_mode: false,
+            session_all: false,
+            synonyms: BTreeMap::new(),
+            role: None,
+            tool: None,
+            project: None,
+            after: None,
+            before: None,
+            branch: None,
+            file: None,
+            tool_input: false,
+            thinking_only: false,
+            no_thinking: false,
+            max_results: 50,
+            include_smc: false,
+            only_smc: false,
+            exclude_session: None,
+            truncate: 500,
+            oneline: false,
+            snippet_chars: 0,
+            all_occurrences: false,
+            rich: false,
+            time_format: TimeFormat::default(),
+            links: false,
+            redact: None,
+            copy: false,
+            no_tag: false,
+            md: None,
+            md_append: false,
+            md_split: None,
+            md_context: 2,
+            html: None,
+            post_filter: None,
+            max_tokens: 0,
+        }
+    }
+}
+
+impl SearchOpts {
+    pub fn builder() -> SearchOptsBuilder {
+        SearchOptsBuilder(Self::default())
+    }
+}
+
+/// Chained setters over [`SearchOpts::default`]; one per field, same name.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptsBuilder(SearchOpts);
+
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.0.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+impl SearchOptsBuilder {
+    /// Add one search term (repeatable).
+    pub fn query(mut self, q: impl Into<String>) -> Self {
+        self.0.queries.push(q.into());
+        self
+    }
+
+    setters! {
+        queries: Vec<String>,
+        is_regex: bool,
+        stem: bool,
+        and_mode: bool,
+        session_all: bool,
+        synonyms: BTreeMap<String, Vec<String>>,
+        role: Option<String>,
+        tool: Option<String>,
+        project: Option<String>,
+        after: Option<String>,
+        before: Option<String>,
+        branch: Option<String>,
+        file: Option<String>,
+        tool_input: bool,
+        thinking_only: bool,
+        no_thinking: bool,
+        max_results: usize,
+        include_smc: bool,
+        only_smc: bool,
+        exclude_session: Option<String>,
+        truncate: usize,
+        oneline: bool,
+        snippet_chars: usize,
+        all_occurrences: bool,
+        rich: bool,
+        time_format: TimeFormat,
+        links: bool,
+        redact: Option<Redactor>,
+        copy: bool,
+        no_tag: bool,
+        md: Option<PathBuf>,
+        md_append: bool,
+        md_split: Option<PathBuf>,
+        md_context: usize,
+        html: Option<PathBuf>,
+        post_filter: Option<String>,
+        max_tokens: usize,
+    }
+
+    pub fn build(self) -> SearchOpts {
+        self.0
+    }
+}
+
+pub use crate::util::policy::SMC_TAG;
+
+/// Snippet width when `all_occurrences` is set without `snippet_chars`.
+pub const DEFAULT_SNIPPET_CHARS: usize = 150;
 
 // ── Records ────────────────────────────────────────────────────────────────
 
@@ -47,13 +230,124 @@ struct SearchRecord {
     line: usize,
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    timestamp: Option<String>,
+    timestamp: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age: Option<String>,
     matched_query: String,
     text: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tool_names: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     git_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    snippets: Vec<Snippet>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    rich: Option<RichFields>,
+}
+
+/// Text around one match position.
+#[derive(Serialize, Debug)]
+struct Snippet {
+    /// Char offset of the match in the message text.
+    offset: usize,
+    text: String,
+}
+
+/// Metadata `--json-rich` adds to a hit. Read by a second parse of the hit's
+/// line only, so the scan itself stays on the borrowed fast path.
+#[derive(Serialize, Debug)]
+struct RichFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    /// Shared by every line of one API response, which all repeat its usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<Usage>,
+}
+
+impl RichFields {
+    fn parse(line: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Line {
+            cwd: Option<String>,
+            uuid: Option<String>,
+            parent_uuid: Option<String>,
+            message: Option<Msg>,
+        }
+        #[derive(Deserialize)]
+        struct Msg {
+            id: Option<String>,
+            model: Option<String>,
+            usage: Option<Usage>,
+        }
+
+        let l: Line = serde_json::from_str(line).ok()?;
+        let (model, message_id, usage) = l.message.map_or((None, None, None), |m| (m.model, m.id, m.usage));
+        Some(Self { cwd: l.cwd, uuid: l.uuid, parent_uuid: l.parent_uuid, model, message_id, usage })
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct OnelineMatch<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: &'a str,
+    line: usize,
+    role: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<&'a Timestamp>,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+}
+
+impl SearchRecord {
+    fn oneline(&self) -> OnelineMatch<'_> {
+        OnelineMatch {
+            record_type: "match",
+            session_id: &self.session_id,
+            line: self.line,
+            role: &self.role,
+            timestamp: self.timestamp.as_ref(),
+            text: text::oneline(&self.text, 100),
+            url: self.url.as_deref(),
+        }
+    }
+}
+
+/// One markdown list item for `--copy`.
+fn markdown(v: &serde_json::Value) -> String {
+    let field = |k: &str| match &v[k] {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    let date = field("timestamp").get(..10).unwrap_or_default().to_string();
+    let quoted = field("text").trim().replace('\n', "\n  > ");
+    // `--oneline` records carry no project.
+    let project = match field("project") {
+        p if p.is_empty() => String::new(),
+        p => format!("**{}** ", p),
+    };
+    format!(
+        "- {}`{}:{}` {} {}\n  > {}\n",
+        project,
+        field("session_id"),
+        field("line"),
+        field("role"),
+        date,
+        quoted
+    )
 }
 
 #[derive(Serialize, Debug)]
@@ -63,122 +357,308 @@ struct SearchSummary {
     query: String,
     count: usize,
     files_scanned: usize,
+    /// Sessions matched under `session_all`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sessions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    clipboard: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    md_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    md_files: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html_file: Option<String>,
     elapsed_ms: u128,
 }
 
+#[derive(Serialize, Debug)]
+struct MdFileRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    path: String,
+    session_id: String,
+    hits: usize,
+}
+
 // ── Matcher ────────────────────────────────────────────────────────────────
 
+/// How query terms are compared with message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchMode {
+    /// Case-insensitive substring.
+    Plain,
+    Regex,
+    /// Whole words after English stemming; see [`stem`].
+    Stem,
+}
+
+impl MatchMode {
+    fn of(opts: &SearchOpts) -> Self {
+        match (opts.is_regex, opts.stem) {
+            (true, _) => Self::Regex,
+            (false, true) => Self::Stem,
+            (false, false) => Self::Plain,
+        }
+    }
+}
+
 struct Matcher {
+    mode: MatchMode,
     regexes: Vec<Regex>,
     plains: Vec<String>,
+    /// Case-insensitive literal regexes for `plains`, used to locate matches
+    /// (lowercasing can shift byte offsets, so `plains` can't be used).
+    locators: Vec<Regex>,
+    /// The stemmed words of each of `plains`, in `Stem` mode.
+    stems: Vec<Vec<String>>,
+    /// Each query's term and its synonyms, as a range of the lists above.
+    groups: Vec<Range<usize>>,
     and_mode: bool,
 }
 
+/// A message's text, prepared once for every term to be checked against.
+struct Prepared<'a> {
+    text: &'a str,
+    /// The text in lowercase, in `Plain` mode.
+    lower: String,
+    /// Its stemmed words, in `Stem` mode.
+    words: Vec<stem::Word>,
+}
+
 impl Matcher {
-    fn new(queries: &[String], is_regex: bool, and_mode: bool) -> Result<Self> {
-        if is_regex {
-            let regexes = queries
-                .iter()
-                .map(|q| Regex::new(q))
-                .collect::<std::result::Result<Vec<_>, _>>()?;
-            Ok(Self { regexes, plains: vec![], and_mode })
-        } else {
-            Ok(Self {
-                regexes: vec![],
-                plains: queries.iter().map(|q| q.to_lowercase()).collect(),
-                and_mode,
-            })
+    /// Each query also matches its `synonyms` entry, looked up ignoring
+    /// case; synonyms are literal words even in regex mode.
+    fn new(
+        queries: &[String],
+        synonyms: &BTreeMap<String, Vec<String>>,
+        mode: MatchMode,
+        and_mode: bool,
+    ) -> Result<Self> {
+        let literal = |q: &str| RegexBuilder::new(&regex::escape(q)).case_insensitive(true).build();
+        let mut m = Self {
+            mode,
+            regexes: vec![],
+            plains: vec![],
+            locators: vec![],
+            stems: vec![],
+            groups: vec![],
+            and_mode,
+        };
+        for q in queries {
+            let start = m.groups.last().map_or(0, |g| g.end);
+            let extra = synonyms.iter().find(|(k, _)| k.eq_ignore_ascii_case(q)).map_or(&[][..], |(_, v)| v);
+            if mode == MatchMode::Regex {
+                m.regexes.push(Regex::new(q)?);
+                for syn in extra {
+                    m.regexes.push(literal(syn)?);
+                }
+            } else {
+                for term in std::iter::once(q).chain(extra) {
+                    m.plains.push(term.to_lowercase());
+                    m.locators.push(literal(term)?);
+                    if mode == MatchMode::Stem {
+                        let words = stem::term(term);
+                        anyhow::ensure!(!words.is_empty(), "'{}' has no words to stem — drop --stem", term);
+                        m.stems.push(words);
+                    }
+                }
+            }
+            m.groups.push(start..start + 1 + extra.len());
         }
+        Ok(m)
     }
 
-    fn first_match(&self, text: &str) -> Option<String> {
-        if self.and_mode {
-            return self.all_match(text);
+    fn prepare<'a>(&self, text: &'a str) -> Prepared<'a> {
+        match self.mode {
+            MatchMode::Plain => Prepared { text, lower: text.to_lowercase(), words: vec![] },
+            MatchMode::Regex => Prepared { text, lower: String::new(), words: vec![] },
+            MatchMode::Stem => Prepared { text, lower: String::new(), words: stem::words(text) },
         }
-        if !self.regexes.is_empty() {
-            for re in &self.regexes {
-                if let Some(m) = re.find(text) {
-                    return Some(m.as_str().to_string());
-                }
+    }
+
+    /// Byte ranges of every match of any term, in order of position.
+    fn positions(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans: Vec<(usize, usize)> = match self.mode {
+            MatchMode::Stem => {
+                let words = stem::words(text);
+                self.stems.iter().flat_map(|t| stem::find_all(&words, t).map(|r| (r.start, r.end))).collect()
             }
-        } else {
-            let lower = text.to_lowercase();
-            for q in &self.plains {
-                if lower.contains(q.as_str()) {
-                    return Some(q.clone());
-                }
+            MatchMode::Plain | MatchMode::Regex => {
+                let res = if self.regexes.is_empty() { &self.locators } else { &self.regexes };
+                res.iter()
+                    .flat_map(|re| re.find_iter(text).filter(|m| !m.is_empty()).map(|m| (m.start(), m.end())))
+                    .collect()
             }
+        };
+        spans.sort_unstable();
+        spans
+    }
+
+    fn first_match(&self, text: &str) -> Option<String> {
+        // No terms: every message matches (streaming use only; run() rejects this).
+        if self.groups.is_empty() {
+            return Some(String::new());
+        }
+        if self.and_mode {
+            return self.all_match(text);
         }
-        None
+        let text = self.prepare(text);
+        (0..self.groups.last().map_or(0, |g| g.end)).find_map(|i| self.term_match(i, &text))
     }
 
     fn all_match(&self, text: &str) -> Option<String> {
-        if !self.regexes.is_empty() {
-            let mut hits = Vec::new();
-            for re in &self.regexes {
-                match re.find(text) {
-                    Some(m) => hits.push(m.as_str().to_string()),
-                    None => return None,
-                }
-            }
-            Some(hits.join(" + "))
-        } else {
-            let lower = text.to_lowercase();
-            for q in &self.plains {
-                if !lower.contains(q.as_str()) {
-                    return None;
-                }
-            }
-            Some(self.plains.join(" + "))
+        let text = self.prepare(text);
+        let mut hits = Vec::new();
+        for group in &self.groups {
+            hits.push(group.clone().find_map(|i| self.term_match(i, &text))?);
         }
+        Some(hits.join(" + "))
+    }
+
+    /// Term `i` in `text`: the regex match, the words a stemmed term
+    /// matched, or the plain term itself.
+    fn term_match(&self, i: usize, text: &Prepared) -> Option<String> {
+        match self.mode {
+            MatchMode::Regex => self.regexes[i].find(text.text).map(|m| m.as_str().to_string()),
+            MatchMode::Stem => stem::find_all(&text.words, &self.stems[i]).next().map(|r| text.text[r].to_string()),
+            MatchMode::Plain => text.lower.contains(self.plains[i].as_str()).then(|| self.plains[i].clone()),
+        }
+    }
+}
+
+/// The parts of [`SearchOpts`] that are parsed or compiled, done once per run.
+struct Compiled {
+    matcher: Matcher,
+    roles: Option<RoleFilter>,
+    tools: Option<ToolFilter>,
+    range: DateRange,
+}
+
+impl Compiled {
+    fn new(opts: &SearchOpts) -> Result<Self> {
+        Self::with_queries(opts, &opts.queries, opts.and_mode)
+    }
+
+    /// One per term, for `session_all`.
+    fn each_term(opts: &SearchOpts) -> Result<Vec<Self>> {
+        opts.queries.iter().map(|q| Self::with_queries(opts, std::slice::from_ref(q), false)).collect()
+    }
+
+    fn with_queries(opts: &SearchOpts, queries: &[String], and_mode: bool) -> Result<Self> {
+        Ok(Self {
+            matcher: Matcher::new(queries, &opts.synonyms, MatchMode::of(opts), and_mode)?,
+            roles: opts.role.as_deref().map(RoleFilter::parse).transpose()?,
+            tools: opts.tool.as_deref().map(ToolFilter::parse).transpose()?,
+            range: time::parse_range(opts.after.as_deref(), opts.before.as_deref())?,
+        })
     }
 }
 
 // ── run ────────────────────────────────────────────────────────────────────
 
 pub fn run<W: Write>(opts: &SearchOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    run_with_progress(opts, files, em, &Silent)
+}
+
+/// [`run`], reporting per-file scan progress to `progress`.
+pub fn run_with_progress<W: Write>(
+    opts: &SearchOpts,
+    files: &[SessionFile],
+    em: &mut Emitter<W>,
+    progress: &dyn Progress,
+) -> Result<()> {
     anyhow::ensure!(!opts.queries.is_empty(), "search query cannot be empty");
 
     let start = std::time::Instant::now();
-    let matcher = Matcher::new(&opts.queries, opts.is_regex, opts.and_mode)?;
+    let compiled = Compiled::new(opts)?;
+    let terms = if opts.session_all { Some(Compiled::each_term(opts)?) } else { None };
 
-    let filtered: Vec<&SessionFile> = files
-        .iter()
-        .filter(|f| {
-            if let Some(proj) = &opts.project {
-                if !f.project_name.to_lowercase().contains(&proj.to_lowercase()) {
-                    return false;
-                }
-            }
-            if let Some(exc) = &opts.exclude_session {
-                if f.session_id.starts_with(exc.as_str()) {
-                    return false;
-                }
-            }
-            true
-        })
-        .collect();
+    let filtered: Vec<&SessionFile> = files.iter().filter(|f| file_selected(f, opts)).collect();
 
     let hit_count = AtomicUsize::new(0);
     let max = opts.max_results;
 
-    let results: Vec<Vec<SearchRecord>> = filtered
+    progress.start(filtered.len(), filtered.iter().map(|f| f.size_bytes).sum());
+    let mut results: Vec<Vec<SearchRecord>> = filtered
         .par_iter()
         .map(|file| {
-            if max > 0 && hit_count.load(Ordering::Relaxed) >= max {
-                return vec![];
+            let hits = if max > 0 && hit_count.load(Ordering::Relaxed) >= max {
+                vec![]
+            } else if let Some(terms) = &terms {
+                search_session(file, terms, opts)
+            } else {
+                search_file(file, &compiled, opts, &hit_count, max)
+            };
+            if terms.is_some() && !hits.is_empty() {
+                hit_count.fetch_add(1, Ordering::Relaxed);
             }
-            search_file(file, &matcher, opts, &hit_count, max)
+            progress.inc(file.size_bytes);
+            hits
         })
         .collect();
+    progress.finish();
+    let mut sessions = None;
+    if terms.is_some() {
+        results.retain(|hits| !hits.is_empty());
+        if max > 0 {
+            results.truncate(max);
+        }
+        sessions = Some(results.len());
+    } else if max > 0 {
+        // Files already past the early-stop check can overshoot `max`.
+        let mut room = max;
+        for hits in &mut results {
+            hits.truncate(room);
+            room -= hits.len();
+        }
+    }
+
+    // Hits are emitted as serialized, so their fields keep the struct's
+    // order. The clipboard, markdown, and HTML outputs below read parsed
+    // copies of what was emitted; a post-search plugin may have reshaped
+    // those, so they treat every field as optional.
+    let mut raw: Vec<Box<RawValue>> = results
+        .iter()
+        .flatten()
+        .map(|rec| if opts.oneline { to_raw_value(&rec.oneline()) } else { to_raw_value(rec) })
+        .collect::<Result<_, _>>()?;
+    if let Some(cmd) = &opts.post_filter {
+        let piped = plugin::pipe(cmd, &raw)?;
+        for w in &piped.warnings {
+            em.warn(None, w);
+        }
+        raw = piped.records;
+    }
+    let out: Vec<serde_json::Value> = raw.iter().map(|r| serde_json::from_str(r.get())).collect::<Result<_, _>>()?;
 
     let mut count = 0usize;
-    'outer: for hits in &results {
-        for rec in hits {
-            if !em.emit(rec)? {
-                break 'outer;
-            }
-            count += 1;
+    let mut md = String::new();
+    for (line, rec) in raw.iter().zip(&out) {
+        if !em.emit(line)? {
+            break;
+        }
+        count += 1;
+        if opts.copy || opts.md.is_some() {
+            md.push_str(&markdown(rec));
+        }
+    }
+    let clipboard = if opts.copy { Some(clipboard::copy(&policy::clipboard_text(&md, opts.no_tag))?) } else { None };
+    let md_file = opts.md.as_ref().map(|p| write_md(p, opts, &md)).transpose()?;
+    let html_file = match &opts.html {
+        Some(path) => {
+            let report = render_html(&out[..count], &filtered, opts)?;
+            std::fs::write(path, report).with_context(|| format!("writing {}", path.display()))?;
+            Some(path.display().to_string())
+        }
+        None => None,
+    };
+
+    let mut md_files = None;
+    if let Some(dir) = &opts.md_split {
+        let written = write_md_split(dir, &out[..count], &filtered, opts)?;
+        md_files = Some(written.len());
+        for rec in &written {
+            em.emit(rec)?;
         }
     }
 
@@ -187,6 +667,11 @@ pub fn run<W: Write>(opts: &SearchOpts, files: &[SessionFile], em: &mut Emitter<
         query: opts.queries.join(", "),
         count,
         files_scanned: filtered.len(),
+        sessions,
+        clipboard,
+        md_file,
+        html_file,
+        md_files,
         elapsed_ms: start.elapsed().as_millis(),
     };
     em.emit(&summary)?;
@@ -195,11 +680,279 @@ pub fn run<W: Write>(opts: &SearchOpts, files: &[SessionFile], em: &mut Emitter<
     Ok(())
 }
 
+// ── Markdown file ──────────────────────────────────────────────────────────
+
+/// `md`: the hit list under a `## <query> — <time>` heading, written to the
+/// dated path (appended with `md_append`). Returns the path written.
+fn write_md(template: &Path, opts: &SearchOpts, list: &str) -> Result<String> {
+    let now = chrono::Local::now();
+    let path = time::strftime(&template.to_string_lossy(), &now)?;
+    if let Some(dir) = Path::new(&path).parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let body = if list.is_empty() { "_No hits._\n" } else { list };
+    let section = format!("## {} — {}\n\n{}\n", opts.queries.join(", "), now.format("%Y-%m-%d %H:%M"), body);
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(opts.md_append)
+        .truncate(!opts.md_append)
+        .open(&path)
+        .with_context(|| format!("cannot write {}", path))?;
+    f.write_all(section.as_bytes())?;
+    Ok(path)
+}
+
+// ── Markdown split ─────────────────────────────────────────────────────────
+
+/// `md_split`: one `<session_id>.md` per session with hits, every hit shown
+/// with `md_context` messages either side. Hits without `session_id` and
+/// `line` are skipped.
+fn write_md_split(
+    dir: &Path,
+    hits: &[serde_json::Value],
+    files: &[&SessionFile],
+    opts: &SearchOpts,
+) -> Result<Vec<MdFileRecord>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+
+    let mut by_session: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for h in hits {
+        let (Some(id), Some(line)) = (h["session_id"].as_str(), h["line"].as_u64()) else { continue };
+        by_session.entry(id).or_default().push(line as usize);
+    }
+
+    let mut written = Vec::new();
+    for (id, mut lines) in by_session {
+        let Some(file) = files.iter().find(|f| f.session_id == id) else { continue };
+        lines.sort_unstable();
+        lines.dedup();
+        let path = dir.join(format!("{}.md", id));
+        std::fs::write(&path, render_md_split(file, &lines, opts)?)
+            .with_context(|| format!("writing {}", path.display()))?;
+        written.push(MdFileRecord {
+            record_type: "md_file",
+            path: path.display().to_string(),
+            session_id: id.to_string(),
+            hits: lines.len(),
+        });
+    }
+    Ok(written)
+}
+
+/// A session's hits (1-based lines) with their context. Overlapping windows
+/// are merged; a `---` rule separates windows that aren't adjacent.
+fn render_md_split(file: &SessionFile, hit_lines: &[usize], opts: &SearchOpts) -> Result<String> {
+    use std::io::BufRead;
+    let f = std::fs::File::open(&file.path).with_context(|| format!("opening {}", file.path.display()))?;
+    let mut messages: Vec<(usize, Record)> = Vec::new();
+    for (n, line) in std::io::BufReader::new(f).lines().enumerate() {
+        let Ok(line) = line else { continue };
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+        if record.is_message() {
+            messages.push((n + 1, record));
+        }
+    }
+    let hits: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, (n, _))| hit_lines.binary_search(n).is_ok())
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut md = format!(
+        "# {} — {}\n\nQuery: `{}` · {} hit{}\n",
+        file.project_name,
+        file.session_id,
+        opts.queries.join(", "),
+        hits.len(),
+        if hits.len() == 1 { "" } else { "s" }
+    );
+    let mut end: Option<usize> = None;
+    for &i in &hits {
+        // Start after whatever the previous hit's window already showed.
+        let from = i.saturating_sub(opts.md_context).max(end.unwrap_or(0));
+        let to = (i + opts.md_context + 1).min(messages.len());
+        if end != Some(from) {
+            md.push_str("\n---\n");
+        }
+        for (j, (line, record)) in messages.iter().enumerate().take(to).skip(from) {
+            let Some(msg) = record.as_message() else { continue };
+            let is_hit = hits.binary_search(&j).is_ok();
+            let ts = msg.timestamp.as_ref().map_or(String::new(), |t| format!(" · {}", t.as_str()));
+            md.push_str(&format!(
+                "\n### Line {} · {}{}{}\n\n",
+                line,
+                record.role(),
+                ts,
+                if is_hit { " · match" } else { "" }
+            ));
+            let text = text::preview(&redact::apply(opts.redact.as_ref(), &msg.full_content()), opts.truncate);
+            let text = text.trim();
+            if is_hit {
+                md.push_str(text);
+            } else {
+                md.push_str("> ");
+                md.push_str(&text.replace('\n', "\n> "));
+            }
+            md.push('\n');
+        }
+        end = Some(to);
+    }
+    Ok(md)
+}
+
+// ── HTML report ────────────────────────────────────────────────────────────
+
+/// `html`: the filters, then one block per hit with its preview and the
+/// full message behind a `<details>`.
+fn render_html(hits: &[serde_json::Value], files: &[&SessionFile], opts: &SearchOpts) -> Result<String> {
+    let full = full_messages(hits, files, opts)?;
+    let field = |v: &serde_json::Value, k: &str| match &v[k] {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+
+    let mut filters: Vec<(&str, String)> = vec![("Query", opts.queries.join(", "))];
+    let mode = [
+        (opts.is_regex, "regex"),
+        (opts.stem && !opts.is_regex, "stemmed"),
+        (opts.and_mode, "all terms"),
+        (opts.session_all, "all terms per session"),
+        (!opts.synonyms.is_empty(), "synonyms"),
+    ];
+    let mode: Vec<&str> = mode.iter().filter(|(on, _)| *on).map(|(_, name)| *name).collect();
+    if !mode.is_empty() {
+        filters.push(("Match", mode.join(", ")));
+    }
+    for (name, value) in [
+        ("Role", &opts.role),
+        ("Tool", &opts.tool),
+        ("Project", &opts.project),
+        ("After", &opts.after),
+        ("Before", &opts.before),
+        ("Branch", &opts.branch),
+        ("File", &opts.file),
+    ] {
+        if let Some(v) = value {
+            filters.push((name, v.clone()));
+        }
+    }
+    filters.push(("Hits", format!("{} ({} session files scanned)", hits.len(), files.len())));
+    filters.push(("Generated", chrono::Local::now().format("%Y-%m-%d %H:%M").to_string()));
+
+    let mut body = String::from("<table class=\"filters\">\n");
+    for (name, value) in &filters {
+        body.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", name, html::escape(value)));
+    }
+    body.push_str("</table>\n<input type=\"search\" id=\"q\" placeholder=\"Filter hits…\">\n");
+
+    for hit in hits {
+        let (session, line) = (field(hit, "session_id"), field(hit, "line"));
+        let date = field(hit, "timestamp").get(..16).unwrap_or_default().replace('T', " ");
+        body.push_str(&format!(
+            "<div class=\"msg hit role-{role}\">\n<h3>{role} <span class=\"meta\">{project} · {session}:{line} · {date} · \
+             matched {matched}</span></h3>\n<pre>{text}</pre>\n",
+            role = html::escape(&field(hit, "role")),
+            project = html::escape(&field(hit, "project")),
+            session = html::escape(&session),
+            line = html::escape(&line),
+            date = html::escape(&date),
+            matched = html::escape(&field(hit, "matched_query")),
+            text = html::escape(field(hit, "text").trim()),
+        ));
+        if let Some(text) = line.parse().ok().and_then(|n: usize| full.get(&(session, n))) {
+            body.push_str(&format!(
+                "<details><summary>Full message</summary>\n<pre>{}</pre>\n</details>\n",
+                html::escape(text.trim())
+            ));
+        }
+        body.push_str("</div>\n");
+    }
+
+    Ok(format!(
+        "<!doctype html>\n<html><head><meta charset=\"utf-8\"><title>smc search: {title}</title>\
+         <style>{style}</style></head>\n<body><h1>smc search: {title}</h1>\n{body}<script>{script}</script>\n\
+         </body></html>\n",
+        title = html::escape(&opts.queries.join(", ")),
+        style = html::STYLE,
+        body = body,
+        script = HTML_FILTER_SCRIPT,
+    ))
+}
+
+/// Hides hits whose text doesn't contain the filter box's words.
+const HTML_FILTER_SCRIPT: &str = r#"
+const q = document.getElementById('q'), hits = [...document.querySelectorAll('.hit')];
+q.addEventListener('input', () => {
+  const words = q.value.toLowerCase().split(/\s+/).filter(Boolean);
+  for (const h of hits) h.hidden = !words.every(w => h.textContent.toLowerCase().includes(w));
+});
+"#;
+
+/// The full, redacted text of each hit's message, keyed by session and
+/// 1-based line. Each session file is read once.
+fn full_messages(
+    hits: &[serde_json::Value],
+    files: &[&SessionFile],
+    opts: &SearchOpts,
+) -> Result<HashMap<(String, usize), String>> {
+    use std::io::BufRead;
+    let mut by_session: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for h in hits {
+        let (Some(id), Some(line)) = (h["session_id"].as_str(), h["line"].as_u64()) else { continue };
+        by_session.entry(id).or_default().push(line as usize);
+    }
+
+    let mut out = HashMap::new();
+    for (id, mut lines) in by_session {
+        let Some(file) = files.iter().find(|f| f.session_id == id) else { continue };
+        lines.sort_unstable();
+        let f = std::fs::File::open(&file.path).with_context(|| format!("opening {}", file.path.display()))?;
+        for (n, line) in std::io::BufReader::new(f).lines().enumerate() {
+            if lines.binary_search(&(n + 1)).is_err() {
+                continue;
+            }
+            let Ok(line) = line else { continue };
+            let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+            let Some(msg) = record.as_message() else { continue };
+            out.insert((id.to_string(), n + 1), redact::apply(opts.redact.as_ref(), &msg.full_content()).into_owned());
+        }
+    }
+    Ok(out)
+}
+
+// ── Streaming ──────────────────────────────────────────────────────────────
+
+/// Search's filters applied one line at a time, for consumers that see lines
+/// as they are appended (`smc watch`). With no terms every message matches.
+pub struct LineSearch<'a> {
+    opts: &'a SearchOpts,
+    compiled: Compiled,
+}
+
+impl<'a> LineSearch<'a> {
+    pub fn new(opts: &'a SearchOpts) -> Result<Self> {
+        Ok(Self { opts, compiled: Compiled::new(opts)? })
+    }
+
+    /// The record `run` would emit for this line (`line` is 1-based), if any.
+    pub fn check(&self, file: &SessionFile, line: usize, raw: &str) -> Option<serde_json::Value> {
+        if !file_selected(file, self.opts) {
+            return None;
+        }
+        let hit = match_line(file, line.checked_sub(1)?, raw, &self.compiled, self.opts)?;
+        let v = if self.opts.oneline { serde_json::to_value(hit.oneline()) } else { serde_json::to_value(&hit) };
+        v.ok()
+    }
+}
+
 // ── Per-file search ────────────────────────────────────────────────────────
 
 fn search_file(
     file: &SessionFile,
-    matcher: &Matcher,
+    compiled: &Compiled,
     opts: &SearchOpts,
     hit_count: &AtomicUsize,
     max: usize,
@@ -220,96 +973,187 @@ fn search_file(
             continue;
         }
 
-        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
-        let Some(msg) = record.as_message() else { continue };
+        if let Some(hit) = match_line(file, line_num, &line, compiled, opts) {
+            hit_count.fetch_add(1, Ordering::Relaxed);
+            hits.push(hit);
+        }
+    }
 
-        // -- filters --
+    hits
+}
 
-        if let Some(role) = &opts.role {
-            if record.role() != role.as_str() {
-                continue;
-            }
-        }
+/// `session_all`: the first hit of each term in `file`, in line order, or
+/// nothing unless every term has one. A message holding several terms is
+/// one hit, its `matched_query` listing them.
+fn search_session(file: &SessionFile, terms: &[Compiled], opts: &SearchOpts) -> Vec<SearchRecord> {
+    let Ok(f) = std::fs::File::open(&file.path) else { return Vec::new() };
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
 
-        if let Some(tool_name) = &opts.tool {
-            let tools = msg.tool_names();
-            if !tools.iter().any(|t| t.to_lowercase().contains(&tool_name.to_lowercase())) {
-                continue;
-            }
+    let mut found: Vec<Option<SearchRecord>> = terms.iter().map(|_| None).collect();
+    use std::io::BufRead;
+    for (line_num, line) in reader.lines().enumerate() {
+        let Ok(line) = line else { continue };
+        if line.trim().is_empty() {
+            continue;
         }
-
-        if let Some(after) = &opts.after {
-            if let Some(ts) = &msg.timestamp {
-                if ts.as_str() < after.as_str() {
-                    continue;
-                }
+        for (slot, term) in found.iter_mut().zip(terms) {
+            if slot.is_none() {
+                *slot = match_line(file, line_num, &line, term, opts);
             }
         }
-
-        if let Some(before) = &opts.before {
-            if let Some(ts) = &msg.timestamp {
-                if ts.as_str() > before.as_str() {
-                    continue;
-                }
-            }
+        if found.iter().all(Option::is_some) {
+            break;
         }
+    }
 
-        if let Some(branch) = &opts.branch {
-            match &msg.git_branch {
-                Some(gb) if gb.to_lowercase().contains(&branch.to_lowercase()) => {}
-                _ => continue,
-            }
+    let Some(mut hits) = found.into_iter().collect::<Option<Vec<SearchRecord>>>() else { return Vec::new() };
+    hits.sort_by_key(|h| h.line);
+    hits.dedup_by(|later, first| {
+        let same = later.line == first.line;
+        if same {
+            first.matched_query = format!("{} + {}", first.matched_query, later.matched_query);
         }
+        same
+    });
+    hits
+}
 
-        if let Some(file_path) = &opts.file {
-            if !msg.touches_file(file_path) {
-                continue;
-            }
+fn file_selected(f: &SessionFile, opts: &SearchOpts) -> bool {
+    if let Some(proj) = &opts.project {
+        if !f.project_name.to_lowercase().contains(&proj.to_lowercase()) {
+            return false;
         }
+    }
+    if let Some(exc) = &opts.exclude_session {
+        if f.session_id.starts_with(exc.as_str()) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Apply every search filter to one JSONL line (`line_num` is 0-based).
+fn match_line(
+    file: &SessionFile,
+    line_num: usize,
+    line: &str,
+    compiled: &Compiled,
+    opts: &SearchOpts,
+) -> Option<SearchRecord> {
+    let Compiled { matcher, roles, tools, range } = compiled;
+    let msg = serde_json::from_str::<RecordRef>(line).ok()?;
+    let role = msg.role()?;
 
-        // -- select search text --
+    // -- filters --
 
-        let text = if opts.thinking_only {
-            msg.thinking_content()
-        } else if opts.no_thinking {
-            msg.text_no_thinking()
-        } else if opts.tool_input {
-            msg.tool_input_content()
-        } else {
-            msg.full_content()
-        };
+    if roles.as_ref().is_some_and(|r| !r.matches(role, msg.has_tool_result())) {
+        return None;
+    }
 
-        if text.is_empty() {
-            continue;
+    if tools.as_ref().is_some_and(|t| !msg.tool_names().iter().any(|name| t.matches(name))) {
+        return None;
+    }
+
+    if !time::in_range(msg.time(), range.0, range.1) {
+        return None;
+    }
+
+    if let Some(branch) = &opts.branch {
+        match msg.git_branch.as_deref() {
+            Some(gb) if gb.to_lowercase().contains(&branch.to_lowercase()) => {}
+            _ => return None,
         }
+    }
 
-        if !opts.include_smc && text.contains(SMC_TAG) {
-            continue;
+    if let Some(file_path) = &opts.file {
+        if !msg.touches_file(file_path) {
+            return None;
         }
+    }
 
-        // -- match --
+    // -- select search text --
 
-        if let Some(matched) = matcher.first_match(&text) {
-            hit_count.fetch_add(1, Ordering::Relaxed);
+    let text = if opts.thinking_only {
+        msg.thinking_content()
+    } else if opts.no_thinking {
+        msg.text_no_thinking()
+    } else if opts.tool_input {
+        msg.tool_input_content()
+    } else {
+        msg.full_content()
+    };
 
-            let preview: String = text.chars().take(500).collect();
+    if text.is_empty() {
+        return None;
+    }
 
-            hits.push(SearchRecord {
-                record_type: "match",
-                project: file.project_name.clone(),
-                session_id: file.session_id.clone(),
-                line: line_num + 1,
-                role: record.role().to_string(),
-                timestamp: msg.timestamp.clone(),
-                matched_query: matched,
-                text: preview,
-                tool_names: msg.tool_names().into_iter().map(String::from).collect(),
-                git_branch: msg.git_branch.clone(),
-            });
+    if opts.only_smc {
+        if !policy::is_smc_output(&text) {
+            return None;
         }
+    } else if policy::skips(opts.include_smc, &text) {
+        return None;
     }
 
-    hits
+    // -- match --
+
+    let matched = matcher.first_match(&text)?;
+    let preview = text::preview(&redact::apply(opts.redact.as_ref(), &text), opts.truncate);
+    let snippets = if opts.oneline { Vec::new() } else { snippets(&text, matcher, opts) };
+
+    let mut timestamp = msg.timestamp.as_deref().map(Timestamp::new);
+    let age = opts.time_format.render(&mut timestamp);
+
+    Some(SearchRecord {
+        record_type: "match",
+        project: file.project_name.clone(),
+        session_id: file.session_id.clone(),
+        line: line_num + 1,
+        role: role.to_string(),
+        timestamp,
+        age,
+        matched_query: matched,
+        text: preview,
+        tool_names: msg.tool_names().into_iter().map(String::from).collect(),
+        git_branch: msg.git_branch.as_deref().map(String::from),
+        url: opts.links.then(|| format!("{}#L{}", file.file_url(), line_num + 1)),
+        snippets,
+        rich: if opts.rich { RichFields::parse(line) } else { None },
+    })
+}
+
+/// `snippets` for one hit: a window of `snippet_chars` around the first
+/// match, or around every match with `all_occurrences`. A match already
+/// inside the previous window doesn't start a new one.
+fn snippets(text: &str, matcher: &Matcher, opts: &SearchOpts) -> Vec<Snippet> {
+    let width = match (opts.snippet_chars, opts.all_occurrences) {
+        (0, false) => return Vec::new(),
+        (0, true) => DEFAULT_SNIPPET_CHARS,
+        (n, _) => n,
+    };
+    let mut spans = matcher.positions(text);
+    if !opts.all_occurrences {
+        spans.truncate(1);
+    }
+
+    let mut out = Vec::new();
+    let mut covered = 0;
+    for (start, end) in spans {
+        if start < covered {
+            continue;
+        }
+        let before = text[..start].chars().count();
+        let len = text[start..end].chars().count();
+        let lead = width.saturating_sub(len) / 2;
+        let from = text[..start].char_indices().rev().take(lead).last().map_or(start, |(i, _)| i);
+        let window: String = text[from..].chars().take(width.max(len)).collect();
+        covered = from + window.len();
+        out.push(Snippet {
+            offset: before,
+            text: redact::apply(opts.redact.as_ref(), &window).into_owned(),
+        });
+    }
+    out
 }
 
 // ── Tests ──────────────────────────────────────────────────────────────────
@@ -317,10 +1161,174 @@ fn search_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::testutil::TempDir;
+    use crate::util::tokens;
+
+    #[test]
+    fn rich_fields_from_hit_line() {
+        let line = r#"{"type":"assistant","uuid":"a1","parentUuid":null,"cwd":"/w","message":{"role":"assistant","model":"m","content":"hi","usage":{"input_tokens":3}}}"#;
+        let v = serde_json::to_value(RichFields::parse(line).unwrap()).unwrap();
+        assert_eq!(v["uuid"], "a1");
+        assert_eq!(v["cwd"], "/w");
+        assert_eq!(v["model"], "m");
+        assert_eq!(v["usage"]["input_tokens"], 3);
+        assert!(v.get("parent_uuid").is_none());
+    }
+
+    #[test]
+    fn snippets_around_each_occurrence() {
+        let text = format!("{}needle{}NEEDLE tail", "a".repeat(50), "b".repeat(50));
+        let m = Matcher::new(&["needle".into()], &BTreeMap::new(), MatchMode::Plain, false).unwrap();
+
+        let opts = SearchOpts::builder().snippet_chars(10).build();
+        let s = snippets(&text, &m, &opts);
+        assert_eq!(s.len(), 1);
+        assert_eq!((s[0].offset, s[0].text.as_str()), (50, "aaneedlebb"));
+
+        let opts = SearchOpts::builder().snippet_chars(10).all_occurrences(true).build();
+        let s = snippets(&text, &m, &opts);
+        assert_eq!(s.iter().map(|s| s.offset).collect::<Vec<_>>(), [50, 106]);
+        assert_eq!(s[1].text, "bbNEEDLE t");
+
+        // Overlapping matches share one window.
+        let m = Matcher::new(&["ab".into()], &BTreeMap::new(), MatchMode::Plain, false).unwrap();
+        let opts = SearchOpts::builder().all_occurrences(true).build();
+        assert_eq!(snippets("ab ab ab", &m, &opts).len(), 1);
+        assert!(snippets("ab", &m, &SearchOpts::default()).is_empty());
+    }
+
+    #[test]
+    fn md_split_merges_context_windows() {
+        let dir = TempDir::new("md-split");
+        let body: String = (1..=7)
+            .map(|i| format!(r#"{{"type":"user","message":{{"role":"user","content":"msg {}"}}}}"#, i) + "\n")
+            .collect();
+        let file = dir.session("s1", "p", &body);
+
+        let opts = SearchOpts::builder().query("msg").md_context(1).build();
+        let md = render_md_split(&file, &[2, 3, 7], &opts).unwrap();
+        let lines: Vec<&str> = md.lines().filter(|l| l.starts_with("### ") || *l == "---").collect();
+        assert_eq!(
+            lines,
+            [
+                "---",
+                "### Line 1 · user",
+                "### Line 2 · user · match",
+                "### Line 3 · user · match",
+                "### Line 4 · user",
+                "---",
+                "### Line 6 · user",
+                "### Line 7 · user · match",
+            ]
+        );
+        assert!(md.contains("> msg 1\n") && md.contains("\nmsg 2\n"));
+
+        // Only the hits that fit --max-tokens get a file.
+        let files = [file, dir.session("s2", "p", &body)];
+        let mut em = Emitter::capturing(0);
+        run(&SearchOpts::builder().query("msg 1").build(), &files, &mut em).unwrap();
+        let first = String::from_utf8(em.into_bytes()).unwrap();
+        let budget = tokens::approx_line(first.lines().next().unwrap().len());
+        let split = dir.join("split");
+        let opts = SearchOpts::builder().query("msg 1").md_split(Some(split.clone())).build();
+        let mut em = Emitter::capturing(budget);
+        run(&opts, &files, &mut em).unwrap();
+        assert!(split.join("s1.md").exists());
+        assert!(!split.join("s2.md").exists());
+    }
+
+    #[test]
+    fn session_all_needs_every_term_in_the_session() {
+        let dir = TempDir::new("session-all");
+        let file = |id: &str, msgs: &[&str]| {
+            let body: String = msgs
+                .iter()
+                .map(|m| format!(r#"{{"type":"user","message":{{"role":"user","content":"{}"}}}}"#, m) + "\n")
+                .collect();
+            dir.session(id, "p", &body)
+        };
+        let files = vec![
+            file("s1", &["set up sqlx", "unrelated", "now the migrations", "sqlx again"]),
+            file("s2", &["only sqlx here"]),
+            file("s3", &["sqlx migrations in one go"]),
+        ];
+
+        let opts = SearchOpts::builder().query("sqlx").query("migrations").session_all(true).build();
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+        let hits: Vec<(&str, u64, &str)> = records[..records.len() - 1]
+            .iter()
+            .map(|r| (r["session_id"].as_str().unwrap(), r["line"].as_u64().unwrap(), r["matched_query"].as_str().unwrap()))
+            .collect();
+        assert_eq!(hits, [("s1", 1, "sqlx"), ("s1", 3, "migrations"), ("s3", 1, "sqlx + migrations")]);
+        assert_eq!(records.last().unwrap()["sessions"], 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn post_filter_keeps_field_order() {
+        let dir = TempDir::new("post-filter");
+        let body = r#"{"type":"user","message":{"role":"user","content":"deploy it"}}"#;
+        let files = [dir.session("s1", "p", body)];
+
+        let opts = SearchOpts::builder().query("deploy").post_filter(Some("cat".into())).build();
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let out = String::from_utf8(em.into_bytes()).unwrap();
+        assert!(out.starts_with(r#"{"type":"match","project":"p","session_id":"s1","line":1,"role":"user""#), "{}", out);
+    }
+
+    #[test]
+    fn html_report_has_filters_and_full_messages() {
+        let dir = TempDir::new("search-html");
+        let long = format!("<b>needle</b> {}", "word ".repeat(200));
+        let body = format!(r#"{{"type":"user","message":{{"role":"user","content":"{}"}}}}"#, long) + "\n";
+        let file = dir.session("s1", "p", &body);
+
+        let report = dir.join("report.html");
+        let opts = SearchOpts::builder().query("needle").truncate(40).role(Some("user".into())).html(Some(report.clone())).build();
+        let mut em = Emitter::capturing(0);
+        run(&opts, std::slice::from_ref(&file), &mut em).unwrap();
+        assert_eq!(em.into_records().last().unwrap()["html_file"], report.display().to_string());
+
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(html.contains("<tr><th>Role</th><td>user</td></tr>"));
+        assert!(html.contains("<details><summary>Full message</summary>\n<pre>&lt;b&gt;needle&lt;/b&gt; word word"));
+        assert!(html.contains(&format!("{}</pre>", "word ".repeat(200).trim())));
+        assert!(!html.contains("<b>needle"));
+    }
+
+    #[test]
+    fn md_file_is_dated_and_appends() {
+        let dir = TempDir::new("md-file");
+        let template = dir.join("log-%Y.md");
+        let opts = SearchOpts::builder().query("oauth").md_append(true).build();
+        let first = write_md(&template, &opts, "- hit one\n").unwrap();
+        let second = write_md(&template, &opts, "").unwrap();
+        assert_eq!(first, second);
+        assert!(first.ends_with(&format!("log-{}.md", chrono::Local::now().format("%Y"))));
+
+        let md = std::fs::read_to_string(&first).unwrap();
+        assert_eq!(md.matches("## oauth — ").count(), 2);
+        assert!(md.contains("- hit one\n") && md.contains("_No hits._"));
+
+        let opts = SearchOpts { md_append: false, ..opts };
+        write_md(&template, &opts, "- hit two\n").unwrap();
+        assert!(!std::fs::read_to_string(&first).unwrap().contains("hit one"));
+    }
+
+    #[test]
+    fn builder_starts_from_cli_defaults() {
+        let opts = SearchOpts::builder().query("a").query("b").and_mode(true).build();
+        assert_eq!(opts.queries, ["a", "b"]);
+        assert!(opts.and_mode);
+        assert_eq!((opts.max_results, opts.truncate), (50, 500));
+    }
 
     #[test]
     fn matcher_plain_or() {
-        let m = Matcher::new(&["foo".into(), "bar".into()], false, false).unwrap();
+        let m = Matcher::new(&["foo".into(), "bar".into()], &BTreeMap::new(), MatchMode::Plain, false).unwrap();
         assert!(m.first_match("hello foo world").is_some());
         assert!(m.first_match("hello bar world").is_some());
         assert!(m.first_match("hello baz world").is_none());
@@ -328,15 +1336,39 @@ mod tests {
 
     #[test]
     fn matcher_plain_and() {
-        let m = Matcher::new(&["foo".into(), "bar".into()], false, true).unwrap();
+        let m = Matcher::new(&["foo".into(), "bar".into()], &BTreeMap::new(), MatchMode::Plain, true).unwrap();
         assert!(m.first_match("foo and bar").is_some());
         assert!(m.first_match("foo only").is_none());
     }
 
     #[test]
     fn matcher_regex() {
-        let m = Matcher::new(&["fn\\s+\\w+".into()], true, false).unwrap();
+        let m = Matcher::new(&["fn\\s+\\w+".into()], &BTreeMap::new(), MatchMode::Regex, false).unwrap();
         assert!(m.first_match("pub fn main()").is_some());
         assert!(m.first_match("no function here").is_none());
     }
+
+    #[test]
+    fn matcher_stem() {
+        let m = Matcher::new(&["deploying".into()], &BTreeMap::new(), MatchMode::Stem, false).unwrap();
+        assert_eq!(m.first_match("We deployed it.").as_deref(), Some("deployed"));
+        assert_eq!(m.first_match("after the Deployment").as_deref(), Some("Deployment"));
+        assert!(m.first_match("redeploy").is_none());
+        assert_eq!(m.positions("deploys, deployed"), [(0, 7), (9, 17)]);
+        assert!(Matcher::new(&["--".into()], &BTreeMap::new(), MatchMode::Stem, false).is_err());
+    }
+
+    #[test]
+    fn matcher_synonyms() {
+        let synonyms = BTreeMap::from([("auth".to_string(), vec!["login".to_string(), "OAuth".to_string()])]);
+        let m = Matcher::new(&["Auth".into(), "bug".into()], &synonyms, MatchMode::Plain, true).unwrap();
+        assert_eq!(m.first_match("the LOGIN bug").as_deref(), Some("login + bug"));
+        assert!(m.first_match("the login page").is_none());
+        assert_eq!(m.positions("oauth bug"), [(0, 5), (1, 5), (6, 9)]);
+
+        let m = Matcher::new(&["a+uth".into()], &synonyms, MatchMode::Regex, false).unwrap();
+        assert_eq!(m.first_match("no match"), None);
+        let m = Matcher::new(&["auth".into()], &synonyms, MatchMode::Regex, false).unwrap();
+        assert_eq!(m.first_match("Login failed").as_deref(), Some("Login"));
+    }
 }
@@ -1,20 +1,52 @@
 /// smc sessions — list conversation sessions with metadata.
 use std::io::Write;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use serde::Serialize;
 
-use crate::models::Record;
+use crate::cmd::stats::format_bytes;
+use crate::models::Timestamp;
 use crate::output::Emitter;
-use crate::util::discover::SessionFile;
+use crate::session::{self, ListOpts};
+use crate::util::discover::{self, SessionFile};
+use crate::util::time::{self, TimeFormat};
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct SessionsOpts {
     pub limit: usize,
     pub project: Option<String>,
     pub after: Option<String>,
     pub before: Option<String>,
+    pub time_format: TimeFormat,
+    /// Add a `file://` URL to each session.
+    pub links: bool,
+    /// Summary cache file (`None` = parse every file).
+    pub cache: Option<PathBuf>,
+    pub preview: PreviewMode,
+}
+
+/// Which part of the conversation `preview` shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PreviewMode {
+    /// The first user message.
+    #[default]
+    First,
+    /// The final exchange: last prompt typed, plus the assistant's reply.
+    Last,
+}
+
+impl PreviewMode {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            _ => anyhow::bail!("unknown preview mode '{}' — use: first, last", s),
+        }
+    }
 }
 
 // ── Records ────────────────────────────────────────────────────────────────
@@ -28,100 +60,75 @@ struct SessionRecord {
     size_bytes: u64,
     size_human: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    timestamp: Option<String>,
+    timestamp: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_activity: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_age: Option<String>,
+    /// First to last message, e.g. `"1h 5m"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     preview: Option<String>,
+    /// Assistant side of the final exchange (`--preview last`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply: Option<String>,
     msg_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
 }
 
 // ── run ────────────────────────────────────────────────────────────────────
 
 pub fn run<W: Write>(opts: &SessionsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
     let start = std::time::Instant::now();
-
-    let filtered: Vec<&SessionFile> = files
-        .iter()
-        .filter(|f| {
-            if let Some(proj) = &opts.project {
-                if !f.project_name.to_lowercase().contains(&proj.to_lowercase()) {
-                    return false;
-                }
+    let list_opts = ListOpts {
+        project: opts.project.clone(),
+        after: opts.after.clone(),
+        before: opts.before.clone(),
+        cache: opts.cache.clone(),
+    };
+    let mut entries: Vec<SessionRecord> = session::list(files, &list_opts)?
+        .into_iter()
+        .map(|s| {
+            let duration_secs = s.duration_secs();
+            let (preview, reply) = match opts.preview {
+                PreviewMode::First => (s.preview, None),
+                PreviewMode::Last => (s.last_prompt, s.last_reply),
+            };
+            SessionRecord {
+                record_type: "session",
+                url: opts.links.then(|| discover::file_url(&s.path)),
+                size_human: format_bytes(s.size_bytes),
+                session_id: s.session_id,
+                project: s.project,
+                size_bytes: s.size_bytes,
+                timestamp: s.timestamp,
+                age: None,
+                last_activity: s.last_timestamp,
+                last_age: None,
+                duration: duration_secs.map(time::duration),
+                duration_secs,
+                preview,
+                reply,
+                msg_count: s.msg_count,
             }
-            true
         })
         .collect();
 
-    let mut entries: Vec<SessionRecord> = Vec::new();
-
-    for file in &filtered {
-        let Ok(f) = std::fs::File::open(&file.path) else { continue };
-        let reader = std::io::BufReader::new(f);
-
-        let mut first_timestamp = None;
-        let mut first_user_msg = None;
-        let mut msg_count = 0u32;
-
-        use std::io::BufRead;
-        for line in reader.lines() {
-            let Ok(line) = line else { continue };
-            if line.trim().is_empty() {
-                continue;
-            }
-            let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
-
-            if let Some(msg) = record.as_message() {
-                msg_count += 1;
-                if first_timestamp.is_none() {
-                    first_timestamp = msg.timestamp.clone();
-                }
-                if first_user_msg.is_none() && matches!(record, Record::User(_)) {
-                    let text = msg.text_content();
-                    first_user_msg = Some(text.chars().take(120).collect::<String>());
-                }
-            }
-
-            if first_timestamp.is_some() && first_user_msg.is_some() && msg_count > 5 {
-                break;
-            }
-        }
-
-        // date filters
-        if let Some(after) = &opts.after {
-            if let Some(ts) = &first_timestamp {
-                if ts.as_str() < after.as_str() {
-                    continue;
-                }
-            }
-        }
-        if let Some(before) = &opts.before {
-            if let Some(ts) = &first_timestamp {
-                if ts.as_str() > before.as_str() {
-                    continue;
-                }
-            }
-        }
-
-        entries.push(SessionRecord {
-            record_type: "session",
-            session_id: file.session_id.clone(),
-            project: file.project_name.clone(),
-            size_bytes: file.size_bytes,
-            size_human: file.size_human(),
-            timestamp: first_timestamp,
-            preview: first_user_msg,
-            msg_count,
-        });
-    }
-
-    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-
     let show = if opts.limit > 0 {
         std::cmp::min(opts.limit, entries.len())
     } else {
         entries.len()
     };
 
-    for entry in entries.iter().take(show) {
+    for entry in entries.iter_mut().take(show) {
+        entry.age = opts.time_format.render(&mut entry.timestamp);
+        entry.last_age = opts.time_format.render(&mut entry.last_activity);
         if !em.emit(entry)? {
             break;
         }
@@ -138,3 +145,53 @@ pub fn run<W: Write>(opts: &SessionsOpts, files: &[SessionFile], em: &mut Emitte
     em.flush()?;
     Ok(())
 }
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn records(preview: PreviewMode, files: &[SessionFile]) -> Vec<serde_json::Value> {
+        let opts = SessionsOpts {
+            limit: 0,
+            project: None,
+            after: None,
+            before: None,
+            time_format: TimeFormat::default(),
+            links: false,
+            cache: None,
+            preview,
+        };
+        let mut em = Emitter::capturing(0);
+        run(&opts, files, &mut em).unwrap();
+        em.into_records().into_iter().filter(|r| r["type"] == "session").collect()
+    }
+
+    #[test]
+    fn last_exchange_activity_and_duration() {
+        let dir = TempDir::new("sessions");
+        let files = vec![dir.session(
+            "s1",
+            "alpha",
+            concat!(
+                r#"{"type":"user","timestamp":"2025-01-01T09:00:00Z","message":{"role":"user","content":"first question"}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-01T09:01:00Z","message":{"role":"assistant","content":[{"type":"text","text":"first answer"}]}}"#, "\n",
+                r#"{"type":"user","timestamp":"2025-01-01T10:04:00Z","message":{"role":"user","content":"last question"}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:05:00Z","message":{"role":"assistant","content":[{"type":"text","text":"last answer"}]}}"#, "\n",
+            ),
+        )];
+
+        let first = &records(PreviewMode::First, &files)[0];
+        assert_eq!(first["preview"], "first question");
+        assert!(first.get("reply").is_none());
+        assert_eq!(first["last_activity"], "2025-01-01T10:05:00Z");
+        assert_eq!((first["duration"].as_str(), first["duration_secs"].as_u64()), (Some("1h 5m"), Some(3900)));
+        assert_eq!(first["msg_count"], 4);
+
+        let last = &records(PreviewMode::Last, &files)[0];
+        assert_eq!((last["preview"].as_str(), last["reply"].as_str()), (Some("last question"), Some("last answer")));
+        assert!(PreviewMode::parse("middle").is_err());
+    }
+}
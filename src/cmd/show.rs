@@ -1,79 +1,235 @@
 /// smc show — pretty-print a conversation as JSONL message records.
-use std::io::Write;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
 
 use anyhow::Result;
 use serde::Serialize;
 
-use crate::models::{ContentBlock, MessageContent, Record};
+use crate::models::{ContentBlock, MessageContent, Record, RoleFilter, Timestamp};
 use crate::output::Emitter;
+use crate::session::{self, Message};
 use crate::util::discover::SessionFile;
+use crate::util::redact::{self, Redactor};
+use crate::util::{diff, text};
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct ShowOpts {
     pub session: String,
-    pub thinking: bool,
+    /// `Collapse` fills the `thinking` field, `Inline` quotes thinking in
+    /// `text`, `Omit` drops it.
+    pub thinking: ThinkingMode,
     pub from: Option<usize>,
     pub to: Option<usize>,
+    /// Max chars of each tool input preview (0 = no truncation).
+    pub truncate: usize,
+    /// Emit compact one-line records (index, role, 100-char preview).
+    pub oneline: bool,
+    /// A [`RoleFilter`] spec, e.g. `user,asst` or `any-human`.
+    pub role: Option<String>,
+    /// Scrub secrets from text, thinking, and tool input.
+    pub redact: Option<Redactor>,
+    /// Drop sidechain (sub-agent) messages instead of nesting them under
+    /// the `Task` call that spawned them.
+    pub hide_sidechains: bool,
+    /// Non-message records to show in sequence, after the message they follow.
+    pub include: Vec<ExtraKind>,
     pub max_tokens: usize,
 }
 
+/// A non-message record `show --include` can list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraKind {
+    /// Hook and tool progress events.
+    Progress,
+    /// File-history snapshots taken before edits.
+    FileHistory,
+    /// Conversation summaries written at compaction.
+    Summary,
+}
+
+impl ExtraKind {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "progress" => Ok(Self::Progress),
+            "file-history" => Ok(Self::FileHistory),
+            "summary" => Ok(Self::Summary),
+            _ => anyhow::bail!("unknown record type '{}' — use: progress, file-history, summary", s),
+        }
+    }
+
+    /// The JSONL `type` of these records.
+    fn record_type(self) -> &'static str {
+        match self {
+            Self::Progress => "progress",
+            Self::FileHistory => "file-history-snapshot",
+            Self::Summary => "summary",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Progress => "progress",
+            Self::FileHistory => "file-history",
+            Self::Summary => "summary",
+        }
+    }
+}
+
+/// How renderers treat thinking blocks: `collapse` keeps them apart from the
+/// text (a `<details>` block in markdown, the `thinking` field in `show`),
+/// `inline` quotes them in the text, `omit` drops them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThinkingMode {
+    #[default]
+    Collapse,
+    Inline,
+    Omit,
+}
+
+impl ThinkingMode {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "collapse" => Ok(Self::Collapse),
+            "inline" => Ok(Self::Inline),
+            "omit" => Ok(Self::Omit),
+            _ => anyhow::bail!("unknown thinking mode '{}' — use: collapse, inline, omit", s),
+        }
+    }
+}
+
+/// `s` as a markdown blockquote.
+pub fn blockquote(s: &str) -> String {
+    s.lines()
+        .map(|l| if l.is_empty() { ">".to_string() } else { format!("> {}", l) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // ── Records ────────────────────────────────────────────────────────────────
 
 #[derive(Serialize, Debug)]
-struct MessageOut {
+pub struct MessageOut {
     #[serde(rename = "type")]
     record_type: &'static str,
     index: usize,
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    timestamp: Option<String>,
+    timestamp: Option<Timestamp>,
     text: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tool_calls: Vec<ToolCallOut>,
     #[serde(skip_serializing_if = "Option::is_none")]
     thinking: Option<String>,
+    #[serde(flatten)]
+    sidechain: SidechainOut,
+}
+
+#[derive(Serialize, Debug)]
+pub struct OnelineOut<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    index: usize,
+    role: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<&'a Timestamp>,
+    text: String,
+    #[serde(flatten)]
+    sidechain: SidechainOut,
+}
+
+/// Marks a message written by a sub-agent.
+#[derive(Serialize, Debug)]
+pub struct SidechainOut {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    sidechain: bool,
+    /// `tool_use` id of the `Task` call that spawned the sub-agent; the
+    /// message follows that call's message in `smc show`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spawned_by: Option<String>,
+}
+
+/// A non-message record shown by `--include`; renderers show these dimmed,
+/// apart from the conversation.
+#[derive(Serialize, Debug)]
+struct ExtraOut {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    kind: &'static str,
+    /// 1-based JSONL line.
+    line: usize,
+    /// Index of the message it follows; absent before the first message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<Timestamp>,
+    text: String,
 }
 
 #[derive(Serialize, Debug)]
 struct ToolCallOut {
     name: String,
     input_preview: String,
+    /// Line diff of old_string → new_string for Edit / MultiEdit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<String>,
 }
 
 // ── run ────────────────────────────────────────────────────────────────────
 
 pub fn run<W: Write>(opts: &ShowOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
-    let records = crate::cmd::parse_records(file)?;
+    let session = session::load(file)?;
+    let roles = opts.role.as_deref().map(RoleFilter::parse).transpose()?;
 
-    let mut index = 0usize;
-    for record in &records {
-        if !record.is_message() {
-            continue;
+    // Sub-agent messages go right after the message with their `Task` call.
+    let spawned_by = if opts.hide_sidechains { HashMap::new() } else { session.spawned_by() };
+    let mut spawned: HashMap<&str, Vec<&Message>> = HashMap::new();
+    for msg in &session.messages {
+        if let Some(task) = spawned_by.get(&msg.index) {
+            spawned.entry(task).or_default().push(msg);
         }
+    }
+    let ordered = session.messages.iter().filter(|m| !spawned_by.contains_key(&m.index)).flat_map(|m| {
+        let children = tool_use_ids(m).into_iter().filter_map(|id| spawned.get(id)).flatten();
+        std::iter::once(m).chain(children.copied())
+    });
 
-        let in_range = match (opts.from, opts.to) {
-            (Some(f), Some(t)) => index >= f && index <= t,
-            (Some(f), None) => index >= f,
-            (None, Some(t)) => index <= t,
-            (None, None) => true,
-        };
-
-        if in_range {
-            let msg = record.as_message().unwrap();
-            let out = build_message_out(record, msg, index, opts.thinking);
-            if !em.emit(&out)? {
-                break;
+    let mut extras = if opts.include.is_empty() { HashMap::new() } else { read_extras(file, opts)? };
+    if opts.from.map_or(true, |f| f == 0) {
+        for extra in extras.remove(&None).unwrap_or_default() {
+            if !em.emit(&extra)? {
+                em.flush()?;
+                return Ok(());
             }
         }
+    }
 
-        index += 1;
-
-        if let Some(t) = opts.to {
-            if index > t {
+    'messages: for msg in ordered {
+        if opts.from.is_some_and(|f| msg.index < f) || opts.to.is_some_and(|t| msg.index > t) {
+            continue;
+        }
+        let shown = !(opts.hide_sidechains && msg.record.is_sidechain) && role_selected(roles.as_ref(), msg);
+        if shown {
+            let sidechain = SidechainOut {
+                sidechain: msg.record.is_sidechain,
+                spawned_by: spawned_by.get(&msg.index).cloned(),
+            };
+            let ok = if opts.oneline {
+                em.emit(&OnelineOut { sidechain, ..build_oneline(msg, opts.redact.as_ref()) })?
+            } else {
+                em.emit(&MessageOut { sidechain, ..build_message_out(msg, opts) })?
+            };
+            if !ok {
                 break;
             }
         }
+        for extra in extras.remove(&Some(msg.index)).unwrap_or_default() {
+            if !em.emit(&extra)? {
+                break 'messages;
+            }
+        }
     }
 
     em.flush()?;
@@ -82,32 +238,122 @@ pub fn run<W: Write>(opts: &ShowOpts, file: &SessionFile, em: &mut Emitter<W>) -
 
 // ── Helpers ────────────────────────────────────────────────────────────────
 
-fn build_message_out(
-    record: &Record,
-    msg: &crate::models::MessageRecord,
-    index: usize,
-    include_thinking: bool,
-) -> MessageOut {
+/// The `--include` records of `file`, keyed by the index of the message
+/// they follow (`None` before the first one). Messages are counted the way
+/// [`session::load`] counts them.
+fn read_extras(file: &SessionFile, opts: &ShowOpts) -> Result<HashMap<Option<usize>, Vec<ExtraOut>>> {
+    let f = std::fs::File::open(&file.path)?;
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+    let mut out: HashMap<Option<usize>, Vec<ExtraOut>> = HashMap::new();
+    let mut messages = 0usize;
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = line?;
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+        if record.is_message() {
+            messages += 1;
+            continue;
+        }
+        let Ok(v) = serde_json::from_str::<serde_json::Value>(&line) else { continue };
+        let Some(kind) = opts.include.iter().copied().find(|k| v["type"] == k.record_type()) else { continue };
+        let after = messages.checked_sub(1);
+        let timestamp = v["timestamp"].as_str().or_else(|| v["snapshot"]["timestamp"].as_str()).map(Timestamp::new);
+        out.entry(after).or_default().push(ExtraOut {
+            record_type: "extra",
+            kind: kind.name(),
+            line: line_num + 1,
+            after,
+            timestamp,
+            text: text::oneline(&redact::apply(opts.redact.as_ref(), &extra_text(kind, &v)), 200),
+        });
+    }
+    Ok(out)
+}
+
+/// One line describing a non-message record.
+fn extra_text(kind: ExtraKind, v: &serde_json::Value) -> String {
+    match kind {
+        ExtraKind::Progress => {
+            let data = &v["data"];
+            let what = data["type"].as_str().unwrap_or("progress");
+            let detail = ["hookName", "command", "output", "message"].iter().find_map(|k| data[k].as_str());
+            match detail {
+                Some(d) => format!("{}: {}", what, d),
+                None => what.to_string(),
+            }
+        }
+        ExtraKind::FileHistory => {
+            let files: Vec<&str> = v["snapshot"]["trackedFileBackups"]
+                .as_object()
+                .map(|m| m.keys().map(String::as_str).collect())
+                .unwrap_or_default();
+            let update = if v["isSnapshotUpdate"] == true { " (update)" } else { "" };
+            format!("snapshot{} of {} file(s): {}", update, files.len(), files.join(", "))
+        }
+        ExtraKind::Summary => v["summary"].as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Whether `m` passes an optional `--role` filter.
+pub fn role_selected(roles: Option<&RoleFilter>, m: &Message) -> bool {
+    roles.map_or(true, |r| r.matches(m.role, m.record.has_tool_result()))
+}
+
+fn tool_use_ids(m: &Message) -> Vec<&str> {
+    let MessageContent::Blocks(blocks) = &m.record.message.content else { return Vec::new() };
+    blocks
+        .iter()
+        .filter_map(|b| match b {
+            ContentBlock::ToolUse { id: Some(id), .. } => Some(id.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compact record for `--oneline`.
+pub fn build_oneline<'a>(m: &'a Message, redactor: Option<&Redactor>) -> OnelineOut<'a> {
+    let msg = &m.record;
+    let mut text = msg.text_no_thinking();
+    if text.is_empty() {
+        let tools = msg.tool_names();
+        if !tools.is_empty() {
+            text = format!("[tools: {}]", tools.join(", "));
+        }
+    }
+    OnelineOut {
+        record_type: "message",
+        index: m.index,
+        role: m.role,
+        timestamp: msg.timestamp.as_ref(),
+        text: text::oneline(&redact::apply(redactor, &text), 100),
+        sidechain: SidechainOut { sidechain: msg.is_sidechain, spawned_by: None },
+    }
+}
+
+/// Full message record: text, tool calls with diffs, thinking per `opts.thinking`.
+pub fn build_message_out(m: &Message, opts: &ShowOpts) -> MessageOut {
+    let msg = &m.record;
     let mut text_parts = Vec::new();
     let mut tool_calls = Vec::new();
     let mut thinking_text = None;
+    let clean = |s: &str| redact::apply(opts.redact.as_ref(), s).into_owned();
 
     match &msg.message.content {
-        MessageContent::Text(s) => text_parts.push(s.clone()),
+        MessageContent::Text(s) => text_parts.push(clean(s)),
         MessageContent::Blocks(blocks) => {
             for block in blocks {
                 match block {
-                    ContentBlock::Text { text } => text_parts.push(text.clone()),
-                    ContentBlock::Thinking { thinking } => {
-                        if include_thinking {
-                            thinking_text = Some(thinking.clone());
-                        }
-                    }
+                    ContentBlock::Text { text } => text_parts.push(clean(text)),
+                    ContentBlock::Thinking { thinking } => match opts.thinking {
+                        ThinkingMode::Collapse => thinking_text = Some(clean(thinking)),
+                        ThinkingMode::Inline => text_parts.push(blockquote(&clean(thinking))),
+                        ThinkingMode::Omit => {}
+                    },
                     ContentBlock::ToolUse { name, input, .. } => {
-                        let preview: String = input.to_string().chars().take(200).collect();
+                        let preview = text::preview(&clean(&input.to_string()), opts.truncate);
                         tool_calls.push(ToolCallOut {
                             name: name.clone(),
                             input_preview: preview,
+                            diff: diff::edit_diff(name, input).map(|d| clean(&d)),
                         });
                     }
                     _ => {}
@@ -118,11 +364,133 @@ fn build_message_out(
 
     MessageOut {
         record_type: "message",
-        index,
-        role: record.role().to_string(),
+        index: m.index,
+        role: m.role.to_string(),
         timestamp: msg.timestamp.clone(),
         text: text_parts.join("\n"),
         tool_calls,
         thinking: thinking_text,
+        sidechain: SidechainOut { sidechain: msg.is_sidechain, spawned_by: None },
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+    use crate::models::MessageRecord;
+
+    #[test]
+    fn thinking_modes() {
+        let line = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"hmm\nok"},{"type":"text","text":"done"}]}}"#;
+        let m = Message { index: 0, role: "assistant", record: serde_json::from_str::<MessageRecord>(line).unwrap() };
+        let out = |thinking| {
+            let opts = ShowOpts {
+                session: String::new(),
+                thinking,
+                from: None,
+                to: None,
+                truncate: 0,
+                oneline: false,
+                role: None,
+                redact: None,
+                hide_sidechains: false,
+                include: Vec::new(),
+                max_tokens: 0,
+            };
+            serde_json::to_value(build_message_out(&m, &opts)).unwrap()
+        };
+        let collapsed = out(ThinkingMode::Collapse);
+        assert_eq!((&collapsed["text"], &collapsed["thinking"]), (&"done".into(), &"hmm\nok".into()));
+        assert_eq!(out(ThinkingMode::Inline)["text"], "> hmm\n> ok\ndone");
+        assert!(out(ThinkingMode::Omit).get("thinking").is_none());
+    }
+
+    #[test]
+    fn nests_sidechains_under_their_task() {
+        let dir = TempDir::new("show");
+        let body = [
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Task","input":{"prompt":"a"}},{"type":"tool_use","id":"t2","name":"Task","input":{"prompt":"b"}}]}}"#,
+            r#"{"type":"user","isSidechain":true,"uuid":"b1","message":{"role":"user","content":"b"}}"#,
+            r#"{"type":"user","isSidechain":true,"uuid":"a1","message":{"role":"user","content":"a"}}"#,
+            r#"{"type":"assistant","isSidechain":true,"uuid":"b2","parentUuid":"b1","message":{"role":"assistant","content":"done b"}}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":"both done"}}"#,
+        ]
+        .join("\n");
+        let file = dir.session("s1", "p", &body);
+        let shown = |hide_sidechains| {
+            let opts = ShowOpts {
+                session: "s1".into(),
+                thinking: ThinkingMode::Omit,
+                from: None,
+                to: None,
+                truncate: 0,
+                oneline: true,
+                role: None,
+                redact: None,
+                hide_sidechains,
+                include: Vec::new(),
+                max_tokens: 0,
+            };
+            let mut em = Emitter::capturing(0);
+            run(&opts, &file, &mut em).unwrap();
+            em.into_records()
+        };
+
+        let records = shown(false);
+        let order: Vec<u64> = records.iter().map(|r| r["index"].as_u64().unwrap()).collect();
+        assert_eq!(order, [0, 2, 1, 3, 4]);
+        assert_eq!((records[1]["sidechain"].as_bool(), records[1]["spawned_by"].as_str()), (Some(true), Some("t1")));
+        assert_eq!(records[3]["spawned_by"], "t2");
+        assert!(records[4].get("sidechain").is_none());
+        assert_eq!(shown(true).len(), 2);
+    }
+
+    #[test]
+    fn includes_non_message_records_in_sequence() {
+        let dir = TempDir::new("show-extra");
+        let body = [
+            r#"{"type":"summary","summary":"Fix the deploy script","leafUuid":"a1"}"#,
+            r#"{"type":"user","message":{"role":"user","content":"go"}}"#,
+            r#"{"type":"progress","timestamp":"2025-03-01T10:00:00Z","data":{"type":"hook_progress","hookName":"PostToolUse:Bash"}}"#,
+            r#"{"type":"file-history-snapshot","snapshot":{"trackedFileBackups":{"a.rs":{}},"timestamp":"2025-03-01T10:00:01Z"}}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":"done"}}"#,
+        ]
+        .join("\n");
+        let file = dir.session("s1", "p", &body);
+        let shown = |include: Vec<ExtraKind>, from| {
+            let opts = ShowOpts {
+                session: "s1".into(),
+                thinking: ThinkingMode::Omit,
+                from,
+                to: None,
+                truncate: 0,
+                oneline: true,
+                role: None,
+                redact: None,
+                hide_sidechains: false,
+                include,
+                max_tokens: 0,
+            };
+            let mut em = Emitter::capturing(0);
+            run(&opts, &file, &mut em).unwrap();
+            em.into_records()
+        };
+
+        let all = shown(vec![ExtraKind::Summary, ExtraKind::Progress, ExtraKind::FileHistory], None);
+        let kinds: Vec<&str> = all.iter().map(|r| r["kind"].as_str().unwrap_or("message")).collect();
+        assert_eq!(kinds, ["summary", "message", "progress", "file-history", "message"]);
+        assert_eq!(all[0]["text"], "Fix the deploy script");
+        assert!(all[0].get("after").is_none());
+        assert_eq!((all[2]["line"].as_u64(), all[2]["after"].as_u64()), (Some(3), Some(0)));
+        assert_eq!(all[2]["text"], "hook_progress: PostToolUse:Bash");
+        assert_eq!(all[3]["text"], "snapshot of 1 file(s): a.rs");
+        assert_eq!(all[3]["timestamp"], "2025-03-01T10:00:01Z");
+
+        assert_eq!(shown(vec![ExtraKind::Progress], Some(1)).len(), 1);
+        assert_eq!(shown(Vec::new(), None).len(), 2);
+        assert!(ExtraKind::parse("snapshots").is_err());
     }
 }
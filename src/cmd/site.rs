@@ -0,0 +1,257 @@
+/// smc site — generate a browsable static HTML site of the whole corpus.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::models::{ContentBlock, MessageContent, Timestamp, UserKind};
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::html::{self, escape};
+use crate::util::redact::{self, Redactor};
+use crate::util::{diff, text};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct SiteOpts {
+    pub out_dir: String,
+    pub project: Option<String>,
+    /// Scrub secrets from every page and the search index.
+    pub redact: Option<Redactor>,
+    pub max_tokens: usize,
+}
+
+/// Per-session cap on text fed into the client-side search index.
+const INDEX_TEXT_CHARS: usize = 4000;
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct SiteDone {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    out_dir: String,
+    sessions: usize,
+    projects: usize,
+    elapsed_ms: u128,
+}
+
+/// One entry in `search-index.js`.
+#[derive(Serialize, Debug)]
+struct IndexEntry {
+    #[serde(skip)]
+    first_ts: Option<Timestamp>,
+    id: String,
+    project: String,
+    date: String,
+    title: String,
+    text: String,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &SiteOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+    let out = Path::new(&opts.out_dir);
+    std::fs::create_dir_all(out.join("sessions"))
+        .with_context(|| format!("cannot create {}", out.display()))?;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let rendered: Vec<(&SessionFile, Result<IndexEntry>)> = filtered
+        .par_iter()
+        .map(|file| (*file, write_session(file, out, opts.redact.as_ref())))
+        .collect();
+
+    let mut by_project: BTreeMap<&str, Vec<IndexEntry>> = BTreeMap::new();
+    for (file, res) in rendered {
+        match res {
+            Ok(entry) => by_project.entry(&file.project_name).or_default().push(entry),
+            Err(e) => em.warn(Some(&file.path.display().to_string()), &format!("{:#}", e)),
+        }
+    }
+
+    let mut projects: Vec<(&str, Vec<IndexEntry>)> = by_project.into_iter().collect();
+    for (_, sessions) in &mut projects {
+        sessions.sort_by(|a, b| b.first_ts.cmp(&a.first_ts));
+    }
+    projects.sort_by(|a, b| b.1.first().map(|s| &s.first_ts).cmp(&a.1.first().map(|s| &s.first_ts)));
+
+    let session_count: usize = projects.iter().map(|(_, s)| s.len()).sum();
+    std::fs::write(out.join("index.html"), render_index(&projects))?;
+
+    let entries: Vec<&IndexEntry> = projects.iter().flat_map(|(_, s)| s.iter()).collect();
+    let index_js = format!("window.SMC_INDEX = {};\n", serde_json::to_string(&entries)?);
+    std::fs::write(out.join("search-index.js"), index_js)?;
+
+    em.emit(&SiteDone {
+        record_type: "site",
+        out_dir: out.display().to_string(),
+        sessions: session_count,
+        projects: projects.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+
+    em.flush()?;
+    Ok(())
+}
+
+// ── Session page ───────────────────────────────────────────────────────────
+
+fn write_session(file: &SessionFile, out: &Path, redactor: Option<&Redactor>) -> Result<IndexEntry> {
+    let records = crate::cmd::parse_records(file)?;
+
+    let mut first_ts: Option<Timestamp> = None;
+    let mut title = String::new();
+    let mut index_text = String::new();
+    let mut body = String::new();
+
+    for record in &records {
+        let Some(msg) = record.as_message() else { continue };
+        if first_ts.is_none() {
+            first_ts = msg.timestamp.clone();
+        }
+        if msg.user_kind() == Some(UserKind::Human) {
+            let t = msg.text_no_thinking();
+            let t = redact::apply(redactor, &t);
+            if title.is_empty() {
+                title = text::oneline(&t, 120);
+            }
+            if index_text.chars().count() < INDEX_TEXT_CHARS {
+                index_text.push_str(&t);
+                index_text.push('\n');
+            }
+        }
+
+        let role = record.role();
+        let ts = msg.timestamp.as_ref().map_or("", Timestamp::as_str);
+        body.push_str(&format!(
+            "<div class=\"msg role-{}\"><h3>{}</h3><div class=\"meta\">{}</div>\n",
+            role,
+            role.to_uppercase(),
+            escape(ts)
+        ));
+        render_content(&msg.message.content, redactor, &mut body);
+        body.push_str("</div>\n");
+    }
+
+    let date = first_ts.as_ref().map(Timestamp::date).unwrap_or_default();
+    let heading = if title.is_empty() { file.session_id.clone() } else { title.clone() };
+    let page = format!(
+        "<!doctype html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title><style>{style}</style></head>\n\
+         <body><p><a href=\"../index.html\">← index</a></p>\n<h1>{title}</h1>\n\
+         <p class=\"meta\">{project} · {date} · {id} · {size}</p>\n{body}</body></html>\n",
+        title = escape(&heading),
+        style = html::STYLE,
+        project = escape(&file.project_name),
+        date = escape(&date),
+        id = escape(&file.session_id),
+        size = file.size_human(),
+        body = body,
+    );
+    std::fs::write(out.join("sessions").join(format!("{}.html", file.session_id)), page)?;
+
+    Ok(IndexEntry {
+        first_ts,
+        id: file.session_id.clone(),
+        project: file.project_name.clone(),
+        date,
+        title,
+        text: text::preview(&index_text, INDEX_TEXT_CHARS),
+    })
+}
+
+fn render_content(content: &MessageContent, redactor: Option<&Redactor>, body: &mut String) {
+    let esc = |s: &str| escape(&redact::apply(redactor, s));
+    match content {
+        MessageContent::Text(s) => body.push_str(&format!("<pre>{}</pre>\n", esc(s))),
+        MessageContent::Blocks(blocks) => {
+            for block in blocks {
+                match block {
+                    ContentBlock::Text { text } => {
+                        body.push_str(&format!("<pre>{}</pre>\n", esc(text)));
+                    }
+                    ContentBlock::Thinking { thinking } => {
+                        body.push_str(&format!(
+                            "<details><summary>Thinking</summary><pre>{}</pre></details>\n",
+                            esc(thinking)
+                        ));
+                    }
+                    ContentBlock::ToolUse { name, input, .. } => {
+                        let shown = diff::edit_diff(name, input).unwrap_or_else(|| {
+                            serde_json::to_string_pretty(input).unwrap_or_else(|_| input.to_string())
+                        });
+                        body.push_str(&format!(
+                            "<details><summary>Tool: {}</summary><pre>{}</pre></details>\n",
+                            escape(name),
+                            esc(&shown)
+                        ));
+                    }
+                    ContentBlock::ToolResult { content: Some(c), .. } => {
+                        let s = text::preview(&c.to_string(), 2000);
+                        body.push_str(&format!(
+                            "<details><summary>Result</summary><pre>{}</pre></details>\n",
+                            esc(&s)
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+// ── Index page ─────────────────────────────────────────────────────────────
+
+fn render_index(projects: &[(&str, Vec<IndexEntry>)]) -> String {
+    let mut body = String::new();
+    for (name, sessions) in projects {
+        body.push_str(&format!("<h2>{}</h2>\n<table>\n", escape(name)));
+        for e in sessions {
+            body.push_str(&format!(
+                "<tr><td class=\"meta\">{}</td><td><a href=\"sessions/{}.html\">{}</a></td></tr>\n",
+                escape(&e.date),
+                escape(&e.id),
+                escape(if e.title.is_empty() { &e.id } else { &e.title })
+            ));
+        }
+        body.push_str("</table>\n");
+    }
+
+    format!(
+        "<!doctype html>\n<html><head><meta charset=\"utf-8\"><title>Claude conversations</title>\
+         <style>{style}</style><script src=\"search-index.js\"></script></head>\n\
+         <body><h1>Claude conversations</h1>\n\
+         <input type=\"search\" id=\"q\" placeholder=\"Search session titles and prompts…\">\n\
+         <ul id=\"results\"></ul>\n<div id=\"listing\">\n{body}</div>\n<script>{script}</script>\n</body></html>\n",
+        style = html::STYLE,
+        body = body,
+        script = SEARCH_SCRIPT,
+    )
+}
+
+const SEARCH_SCRIPT: &str = r#"
+const q = document.getElementById('q'), results = document.getElementById('results'), listing = document.getElementById('listing');
+const esc = s => s.replace(/[&<>"']/g, c => ({'&':'&amp;','<':'&lt;','>':'&gt;','"':'&quot;',"'":'&#39;'}[c]));
+q.addEventListener('input', () => {
+  const term = q.value.trim().toLowerCase();
+  listing.hidden = term.length > 0;
+  if (!term) { results.innerHTML = ''; return; }
+  results.innerHTML = window.SMC_INDEX
+    .filter(e => (e.title + '\n' + e.text + '\n' + e.project).toLowerCase().includes(term))
+    .slice(0, 200)
+    .map(e => `<li><span class="meta">${esc(e.date)} · ${esc(e.project)}</span> <a href="sessions/${encodeURIComponent(e.id)}.html">${esc(e.title || e.id)}</a></li>`)
+    .join('');
+});
+"#;
@@ -0,0 +1,222 @@
+/// smc split — cut one session file in two at a message boundary.
+///
+/// The original file is left alone; the head and tail are written as new
+/// JSONL files, every line copied byte for byte. The cut moves forward
+/// past tool results so a `tool_use` and its result stay in the same part.
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::models::Record;
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct SplitOpts {
+    pub session: String,
+    pub at: SplitAt,
+    /// Directory the two parts are written to.
+    pub out: PathBuf,
+    /// Overwrite existing part files.
+    pub force: bool,
+    pub max_tokens: usize,
+}
+
+/// Where the tail starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitAt {
+    /// 1-based JSONL line, as search hits and `smc context` report it.
+    Line(usize),
+    /// Message index, as `smc show --from` counts it.
+    Message(usize),
+}
+
+/// There is no sensible split point to default to: line 1 leaves an empty
+/// head, which [`run`] rejects.
+impl Default for SplitAt {
+    fn default() -> Self {
+        Self::Line(1)
+    }
+}
+
+impl SplitAt {
+    /// `line:N`, `msg:N`, or a bare `N` (a line).
+    pub fn parse(s: &str) -> Result<Self> {
+        let (kind, n) = s.split_once(':').unwrap_or(("line", s));
+        let n: usize = n.parse().map_err(|_| anyhow::anyhow!("invalid split point '{}' — use: line:N, msg:N", s))?;
+        match kind {
+            "line" => Ok(Self::Line(n)),
+            "msg" => Ok(Self::Message(n)),
+            _ => anyhow::bail!("invalid split point '{}' — use: line:N, msg:N", s),
+        }
+    }
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct SplitRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: String,
+    /// First line of the tail, after moving past tool results.
+    at_line: usize,
+    head: PartRecord,
+    tail: PartRecord,
+}
+
+#[derive(Serialize, Debug)]
+struct PartRecord {
+    path: String,
+    lines: usize,
+    messages: usize,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &SplitOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
+    let lines = scan(file)?;
+    let at = cut_line(&lines, opts.at)?;
+
+    let name = |part: &str| opts.out.join(format!("{}.{}.jsonl", file.session_id, part));
+    let (head_path, tail_path) = (name("head"), name("tail"));
+    for path in [&head_path, &tail_path] {
+        anyhow::ensure!(opts.force || !path.exists(), "{} exists — use --force to overwrite", path.display());
+    }
+    std::fs::create_dir_all(&opts.out)?;
+
+    let f = std::fs::File::open(&file.path)?;
+    let mut reader = std::io::BufReader::with_capacity(256 * 1024, f);
+    let create = |path: &PathBuf| {
+        let f = std::fs::File::create(path).with_context(|| format!("cannot write {}", path.display()))?;
+        anyhow::Ok(std::io::BufWriter::new(f))
+    };
+    let mut head = create(&head_path)?;
+    let mut tail = create(&tail_path)?;
+    let mut buf = Vec::new();
+    let mut line_num = 0;
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        line_num += 1;
+        if line_num < at {
+            head.write_all(&buf)?;
+        } else {
+            tail.write_all(&buf)?;
+        }
+        buf.clear();
+    }
+    head.flush()?;
+    tail.flush()?;
+
+    let messages_before = lines[..at - 1].iter().filter(|l| l.message).count();
+    let messages_total = lines.iter().filter(|l| l.message).count();
+    em.emit(&SplitRecord {
+        record_type: "split",
+        session_id: file.session_id.clone(),
+        at_line: at,
+        head: PartRecord {
+            path: head_path.display().to_string(),
+            lines: at - 1,
+            messages: messages_before,
+        },
+        tail: PartRecord {
+            path: tail_path.display().to_string(),
+            lines: lines.len() - (at - 1),
+            messages: messages_total - messages_before,
+        },
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// What the cut needs to know about each line.
+struct LineInfo {
+    message: bool,
+    /// A message that can open a part: anything but a tool result.
+    clean_start: bool,
+}
+
+fn scan(file: &SessionFile) -> Result<Vec<LineInfo>> {
+    let f = std::fs::File::open(&file.path)?;
+    let mut reader = std::io::BufReader::with_capacity(256 * 1024, f);
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        let record = serde_json::from_slice::<Record>(&buf).ok();
+        let msg = record.as_ref().and_then(Record::as_message);
+        lines.push(LineInfo { message: msg.is_some(), clean_start: msg.is_some_and(|m| !m.is_tool_result()) });
+        buf.clear();
+    }
+    Ok(lines)
+}
+
+/// 1-based line the tail starts at: the requested point, moved forward to
+/// the next message that isn't a tool result. Both parts must keep at
+/// least one message.
+fn cut_line(lines: &[LineInfo], at: SplitAt) -> Result<usize> {
+    let start = match at {
+        SplitAt::Line(n) => n.max(1),
+        SplitAt::Message(n) => {
+            let idx = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, l)| l.message)
+                .nth(n)
+                .map(|(i, _)| i)
+                .ok_or_else(|| anyhow::anyhow!("session has no message {}", n))?;
+            idx + 1
+        }
+    };
+    let at = (start..=lines.len())
+        .find(|&n| lines[n - 1].clean_start)
+        .ok_or_else(|| anyhow::anyhow!("no message to start the tail at or after line {}", start))?;
+    anyhow::ensure!(lines[..at - 1].iter().any(|l| l.message), "nothing to keep before line {}", at);
+    Ok(at)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn splits_past_tool_results() {
+        let dir = TempDir::new("split");
+        let body = concat!(
+            r#"{"type":"user","message":{"role":"user","content":"one"}}"#, "\n",
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","name":"Bash","input":{}}]}}"#, "\n",
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","content":"ok"}]}}"#, "\n",
+            r#"{"type":"file-history-snapshot","snapshot":{}}"#, "\n",
+            r#"{"type":"assistant","message":{"role":"assistant","content":"two"}}"#, "\n",
+            r#"{"type":"user","message":{"role":"user","content":"three"}}"#,
+        );
+        let file = dir.session("s1", "p", body);
+
+        let out = dir.join("parts");
+        let opts = SplitOpts { session: "s1".into(), at: SplitAt::Message(2), out: out.clone(), force: false, max_tokens: 0 };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &file, &mut em).unwrap();
+        let rec = &em.into_records()[0];
+        assert_eq!(rec["at_line"], 5);
+        assert_eq!((rec["head"]["messages"].as_u64(), rec["tail"]["messages"].as_u64()), (Some(3), Some(2)));
+
+        let head = std::fs::read_to_string(out.join("s1.head.jsonl")).unwrap();
+        let tail = std::fs::read_to_string(out.join("s1.tail.jsonl")).unwrap();
+        assert_eq!(head + &tail, body);
+        assert!(run(&opts, &file, &mut Emitter::capturing(0)).is_err());
+
+        assert_eq!(SplitAt::parse("msg:3").unwrap(), SplitAt::Message(3));
+        assert_eq!(SplitAt::parse("40").unwrap(), SplitAt::Line(40));
+        assert!(SplitAt::parse("byte:4").is_err());
+        let lines = scan(&file).unwrap();
+        assert!(cut_line(&lines, SplitAt::Line(1)).is_err());
+    }
+}
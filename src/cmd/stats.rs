@@ -1,19 +1,30 @@
-/// smc stats — aggregate statistics across all conversation logs.
-use std::collections::HashMap;
+/// smc stats — aggregate statistics across all conversation logs, or a
+/// drill-down into one project.
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 
 use anyhow::Result;
+use rayon::prelude::*;
 use serde::Serialize;
 
+use crate::models::{Timestamp, UsageDedup};
 use crate::output::Emitter;
+use crate::session::{self, Session};
 use crate::util::discover::SessionFile;
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct StatsOpts {
+    /// Drill into this project (exact name, or a substring matching one).
+    pub project: Option<String>,
     pub max_tokens: usize,
 }
 
+/// Entries in each ranked list of the project drill-down.
+const TOP: usize = 10;
+
 // ── Records ────────────────────────────────────────────────────────────────
 
 #[derive(Serialize, Debug)]
@@ -35,9 +46,60 @@ struct ProjectStat {
     size_human: String,
 }
 
+#[derive(Serialize, Debug, Default)]
+struct ProjectStatsRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: String,
+    sessions: usize,
+    size_bytes: u64,
+    size_human: String,
+    messages: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_activity: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_activity: Option<Timestamp>,
+    tokens: TokenTotals,
+    /// Sessions and bytes by month of the session's first message, with the
+    /// running size total.
+    by_month: Vec<MonthStat>,
+    tools: Vec<Ranked>,
+    /// Files most often named in tool calls.
+    top_files: Vec<Ranked>,
+    /// Days with the most messages.
+    busiest_days: Vec<Ranked>,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct TokenTotals {
+    input: u64,
+    output: u64,
+    cache_creation: u64,
+    cache_read: u64,
+    total: u64,
+    by_model: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize, Debug)]
+struct MonthStat {
+    month: String,
+    sessions: usize,
+    size_bytes: u64,
+    cumulative_bytes: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct Ranked {
+    key: String,
+    count: u64,
+}
+
 // ── run ────────────────────────────────────────────────────────────────────
 
-pub fn run<W: Write>(_opts: &StatsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+pub fn run<W: Write>(opts: &StatsOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    if let Some(query) = &opts.project {
+        return run_project(query, files, em);
+    }
     let total_size: u64 = files.iter().map(|f| f.size_bytes).sum();
 
     let mut projects: HashMap<String, (usize, u64)> = HashMap::new();
@@ -48,7 +110,7 @@ pub fn run<W: Write>(_opts: &StatsOpts, files: &[SessionFile], em: &mut Emitter<
     }
 
     let mut sorted: Vec<_> = projects.into_iter().collect();
-    sorted.sort_by(|a, b| b.1 .1.cmp(&a.1 .1));
+    sorted.sort_by_key(|(_, (_, size))| std::cmp::Reverse(*size));
 
     let project_stats: Vec<ProjectStat> = sorted
         .iter()
@@ -75,8 +137,112 @@ pub fn run<W: Write>(_opts: &StatsOpts, files: &[SessionFile], em: &mut Emitter<
     Ok(())
 }
 
+fn run_project<W: Write>(query: &str, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let project = resolve_project(query, files)?;
+    let files: Vec<&SessionFile> = files.iter().filter(|f| f.project_name == project).collect();
+    let sessions: Vec<Session> = files.par_iter().map(|f| session::load(f)).collect::<Result<_>>()?;
+
+    let mut rec = ProjectStatsRecord {
+        record_type: "project_stats",
+        sessions: files.len(),
+        size_bytes: files.iter().map(|f| f.size_bytes).sum(),
+        ..Default::default()
+    };
+    rec.size_human = format_bytes(rec.size_bytes);
+
+    let mut months: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    let mut tools: HashMap<String, u64> = HashMap::new();
+    let mut paths: HashMap<String, u64> = HashMap::new();
+    let mut days: HashMap<String, u64> = HashMap::new();
+    for (file, s) in files.iter().zip(&sessions) {
+        rec.messages += s.messages.len();
+        if let Some(first) = s.first_timestamp() {
+            let date = first.date();
+            let month = months.entry(date.get(..7).unwrap_or(&date).to_string()).or_default();
+            month.0 += 1;
+            month.1 += file.size_bytes;
+            if rec.first_activity.as_ref().map_or(true, |t| first < t) {
+                rec.first_activity = Some(first.clone());
+            }
+        }
+        if let Some(last) = s.last_timestamp() {
+            if rec.last_activity.as_ref().map_or(true, |t| last > t) {
+                rec.last_activity = Some(last.clone());
+            }
+        }
+        let mut dedup = UsageDedup::default();
+        for m in &s.messages {
+            if let Some(ts) = &m.record.timestamp {
+                *days.entry(ts.date()).or_default() += 1;
+            }
+            let Some(usage) = dedup.first(&m.record) else { continue };
+            let t = &mut rec.tokens;
+            t.input += usage.input_tokens;
+            t.output += usage.output_tokens;
+            t.cache_creation += usage.cache_creation_input_tokens;
+            t.cache_read += usage.cache_read_input_tokens;
+            t.total += usage.total();
+            let model = m.record.message.model.clone().unwrap_or_else(|| "unknown".into());
+            *t.by_model.entry(model).or_default() += usage.total();
+        }
+        for call in s.tool_calls() {
+            *tools.entry(call.name.to_string()).or_default() += 1;
+            let path = call.input.get("file_path").or_else(|| call.input.get("notebook_path"));
+            if let Some(p) = path.and_then(|v| v.as_str()) {
+                *paths.entry(p.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut cumulative = 0;
+    rec.by_month = months
+        .into_iter()
+        .map(|(month, (sessions, size_bytes))| {
+            cumulative += size_bytes;
+            MonthStat { month, sessions, size_bytes, cumulative_bytes: cumulative }
+        })
+        .collect();
+    rec.tools = ranked(tools, usize::MAX);
+    rec.top_files = ranked(paths, TOP);
+    rec.busiest_days = ranked(days, TOP);
+    rec.project = project;
+
+    em.emit(&rec)?;
+    em.flush()?;
+    Ok(())
+}
+
 // ── Helpers ────────────────────────────────────────────────────────────────
 
+/// The project `query` names: an exact match, else the only project
+/// containing it (case-insensitive).
+pub fn resolve_project(query: &str, files: &[SessionFile]) -> Result<String> {
+    if files.iter().any(|f| f.project_name == query) {
+        return Ok(query.to_string());
+    }
+    let q = query.to_lowercase();
+    let mut names: Vec<&str> = files
+        .iter()
+        .map(|f| f.project_name.as_str())
+        .filter(|n| n.to_lowercase().contains(&q))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    match names.as_slice() {
+        [one] => Ok(one.to_string()),
+        [] => anyhow::bail!("no project matching '{}'", query),
+        many => anyhow::bail!("'{}' matches several projects: {}", query, many.join(", ")),
+    }
+}
+
+/// Highest counts first, ties by key.
+fn ranked(counts: HashMap<String, u64>, limit: usize) -> Vec<Ranked> {
+    let mut v: Vec<Ranked> = counts.into_iter().map(|(key, count)| Ranked { key, count }).collect();
+    v.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    v.truncate(limit);
+    v
+}
+
 pub fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{}B", bytes)
@@ -88,3 +254,39 @@ pub fn format_bytes(bytes: u64) -> String {
         format!("{:.2}GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
     }
 }
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn project_drill_down() {
+        let dir = TempDir::new("stats");
+        let file = |id: &str, project: &str, body: &str| dir.session(id, project, body);
+        let edit = r#"{"type":"assistant","timestamp":"2025-02-03T10:00:00Z","message":{"role":"assistant","model":"m1","usage":{"input_tokens":5,"output_tokens":2},"content":[{"type":"tool_use","id":"t","name":"Edit","input":{"file_path":"/w/a.rs"}}]}}"#;
+        let ask = r#"{"type":"user","timestamp":"2025-01-09T10:00:00Z","message":{"role":"user","content":"hi"}}"#;
+        let files = vec![
+            file("s1", "webapp", &format!("{}\n{}\n", ask, edit)),
+            file("s2", "webapp", &format!("{}\n", edit)),
+            file("s3", "webtools", &format!("{}\n", ask)),
+        ];
+
+        assert!(resolve_project("web", &files).is_err());
+        assert_eq!(resolve_project("TOOLS", &files).unwrap(), "webtools");
+
+        let mut em = Emitter::capturing(0);
+        run(&StatsOpts { project: Some("webapp".into()), max_tokens: 0 }, &files, &mut em).unwrap();
+        let r = &em.into_records()[0];
+        assert_eq!((r["sessions"].as_u64(), r["messages"].as_u64()), (Some(2), Some(3)));
+        assert_eq!(r["tokens"]["total"], 14);
+        assert_eq!(r["tokens"]["by_model"]["m1"], 14);
+        let months: Vec<&str> = r["by_month"].as_array().unwrap().iter().map(|m| m["month"].as_str().unwrap()).collect();
+        assert_eq!(months, ["2025-01", "2025-02"]);
+        assert_eq!(r["by_month"][1]["cumulative_bytes"], r["size_bytes"]);
+        assert_eq!(r["top_files"][0]["key"], "/w/a.rs");
+        assert_eq!(r["busiest_days"][0]["key"], "2025-02-03");
+    }
+}
@@ -0,0 +1,134 @@
+/// smc tag — name sessions so `smc resume <tag>` can find them again.
+///
+/// Tags live in `~/.smc/tags.json`, keyed by session ID; Claude Code's own
+/// records carry none.
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+use crate::util::state;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct TagOpts {
+    /// Tags to add (or remove); none just lists the session's tags.
+    pub tags: Vec<String>,
+    pub remove: bool,
+}
+
+const STATE_FILE: &str = "tags.json";
+
+// ── State ──────────────────────────────────────────────────────────────────
+
+/// Session ID → its tags. Tags are compared case-insensitively and stored
+/// lowercased.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct TagStore {
+    #[serde(default)]
+    sessions: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TagStore {
+    fn path() -> PathBuf {
+        state::smc_home_path().join(STATE_FILE)
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).with_context(|| format!("cannot parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = state::smc_home()?.join(STATE_FILE);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("cannot write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("cannot write {}", path.display()))
+    }
+
+    pub fn add(&mut self, session_id: &str, tag: &str) {
+        self.sessions.entry(session_id.to_string()).or_default().insert(tag.to_lowercase());
+    }
+
+    fn remove(&mut self, session_id: &str, tag: &str) {
+        if let Some(tags) = self.sessions.get_mut(session_id) {
+            tags.remove(&tag.to_lowercase());
+            if tags.is_empty() {
+                self.sessions.remove(session_id);
+            }
+        }
+    }
+
+    /// IDs of the sessions carrying `tag`.
+    pub fn sessions_with(&self, tag: &str) -> Vec<&str> {
+        let tag = tag.to_lowercase();
+        self.sessions.iter().filter(|(_, tags)| tags.contains(&tag)).map(|(id, _)| id.as_str()).collect()
+    }
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct TagsRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: &'a str,
+    project: &'a str,
+    tags: Vec<&'a str>,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &TagOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
+    let mut store = TagStore::load()?;
+    if !opts.tags.is_empty() {
+        for tag in &opts.tags {
+            anyhow::ensure!(!tag.trim().is_empty(), "tags cannot be empty");
+            if opts.remove {
+                store.remove(&file.session_id, tag);
+            } else {
+                store.add(&file.session_id, tag.trim());
+            }
+        }
+        store.save()?;
+    }
+
+    em.emit(&TagsRecord {
+        record_type: "tags",
+        session_id: &file.session_id,
+        project: &file.project_name,
+        tags: store.sessions.get(&file.session_id).into_iter().flatten().map(String::as_str).collect(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_ignore_case_and_drop_empty_sessions() {
+        let mut store = TagStore::default();
+        store.add("s1", "WIP");
+        store.add("s2", "wip");
+        store.add("s2", "auth");
+        assert_eq!(store.sessions_with("Wip"), ["s1", "s2"]);
+        store.remove("s1", "wip");
+        assert_eq!(store.sessions_with("wip"), ["s2"]);
+        assert!(!store.sessions.contains_key("s1"));
+    }
+}
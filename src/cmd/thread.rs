@@ -0,0 +1,184 @@
+/// smc thread — one conversation across a session and its resumptions.
+///
+/// Finds the resume chain with [`session::chain`], drops the prefix each
+/// resumed session repeats (same message uuid), and emits the remaining
+/// messages in time order as `show` records, with a `boundary` record
+/// wherever the conversation moves to another session file.
+use std::collections::HashSet;
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cmd::show::{self, ShowOpts};
+use crate::models::{RoleFilter, Timestamp};
+use crate::output::Emitter;
+use crate::session::{self, Message};
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ThreadOpts {
+    /// Rendering options; `from` / `to` index the merged thread.
+    pub show: ShowOpts,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct ThreadRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    /// Chain members, oldest first.
+    sessions: Vec<String>,
+    messages: usize,
+    /// Repeated messages dropped from resumed sessions.
+    duplicates: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct BoundaryRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: &'a str,
+    /// Thread index of the first message from this session.
+    index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<&'a Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<&'a str>,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ThreadOpts, files: &[SessionFile], file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
+    anyhow::ensure!(opts.show.include.is_empty(), "--include is not supported by smc thread — use smc show");
+    let roles = opts.show.role.as_deref().map(RoleFilter::parse).transpose()?;
+    let chain = session::chain(files, file);
+    let sessions = chain.iter().map(|f| session::load(f)).collect::<Result<Vec<_>>>()?;
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut duplicates = 0usize;
+    // (session index, sort time, message); messages without a timestamp
+    // inherit the previous one so they stay next to their neighbours.
+    let mut merged: Vec<(usize, Option<DateTime<Utc>>, Message)> = Vec::new();
+    for (i, s) in sessions.into_iter().enumerate() {
+        let mut last_time = None;
+        for m in s.messages {
+            if let Some(uuid) = &m.record.uuid {
+                if !seen.insert(uuid.clone()) {
+                    duplicates += 1;
+                    continue;
+                }
+            }
+            last_time = m.record.timestamp.as_ref().and_then(|t| t.utc).or(last_time);
+            merged.push((i, last_time, m));
+        }
+    }
+    merged.sort_by_key(|(i, t, _)| (*t, *i));
+
+    let cont = em.emit(&ThreadRecord {
+        record_type: "thread",
+        sessions: chain.iter().map(|f| f.session_id.clone()).collect(),
+        messages: merged.len(),
+        duplicates,
+    })?;
+    if !cont {
+        return em.flush();
+    }
+
+    let mut current: Option<usize> = None;
+    for (index, (i, _, mut m)) in merged.into_iter().enumerate() {
+        m.index = index;
+        if opts.show.from.is_some_and(|f| index < f) {
+            continue;
+        }
+        if opts.show.to.is_some_and(|t| index > t) {
+            break;
+        }
+        if !show::role_selected(roles.as_ref(), &m) || (opts.show.hide_sidechains && m.record.is_sidechain) {
+            continue;
+        }
+        if current != Some(i) {
+            let ok = em.emit(&BoundaryRecord {
+                record_type: "boundary",
+                session_id: &chain[i].session_id,
+                index,
+                timestamp: m.record.timestamp.as_ref(),
+                previous: current.map(|p| chain[p].session_id.as_str()),
+            })?;
+            if !ok {
+                break;
+            }
+            current = Some(i);
+        }
+        let ok = if opts.show.oneline {
+            em.emit(&show::build_oneline(&m, opts.show.redact.as_ref()))?
+        } else {
+            em.emit(&show::build_message_out(&m, &opts.show))?
+        };
+        if !ok {
+            break;
+        }
+    }
+
+    em.flush()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn msg(uuid: &str, ts: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","uuid":"{}","timestamp":"2025-01-01T{}:00Z","message":{{"role":"user","content":"{}"}}}}"#,
+            uuid, ts, text
+        ) + "\n"
+    }
+
+    #[test]
+    fn merges_resumed_sessions_without_repeats() {
+        let dir = TempDir::new("thread");
+        let file = |id: &str, project: &str, body: String| dir.session(id, project, &body);
+        let files = vec![
+            file("orig", "p", msg("u1", "10:00", "one") + &msg("u2", "10:01", "two")),
+            file("resumed", "p", msg("u1", "10:00", "one") + &msg("u2", "10:01", "two") + &msg("u3", "11:00", "three")),
+            file("other", "p", msg("x1", "10:30", "unrelated")),
+            file("elsewhere", "q", msg("u1", "10:00", "one")),
+        ];
+
+        let opts = ThreadOpts {
+            show: ShowOpts {
+                session: "resumed".into(),
+                thinking: show::ThinkingMode::Omit,
+                from: None,
+                to: None,
+                truncate: 0,
+                oneline: true,
+                role: None,
+                redact: None,
+                hide_sidechains: false,
+                include: Vec::new(),
+                max_tokens: 0,
+            },
+        };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &files[1], &mut em).unwrap();
+        let records = em.into_records();
+
+        assert_eq!(records[0]["sessions"], serde_json::json!(["orig", "resumed"]));
+        assert_eq!(records[0]["duplicates"], 2);
+        let kinds: Vec<String> = records[1..]
+            .iter()
+            .map(|r| format!("{}:{}", r["type"].as_str().unwrap(), r["text"].as_str().or(r["session_id"].as_str()).unwrap()))
+            .collect();
+        assert_eq!(kinds, ["boundary:orig", "message:one", "message:two", "boundary:resumed", "message:three"]);
+        assert_eq!(records[4]["previous"], "orig");
+    }
+}
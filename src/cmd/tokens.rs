@@ -0,0 +1,68 @@
+/// smc tokens — API token usage of one session.
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::models::UsageDedup;
+use crate::output::Emitter;
+use crate::session;
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct TokensOpts {
+    pub session: String,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug, Default)]
+struct TokensRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    session_id: String,
+    project: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_input_tokens: u64,
+    cache_read_input_tokens: u64,
+    total: u64,
+    /// API responses that reported usage.
+    api_calls: usize,
+    /// Total tokens per model.
+    by_model: BTreeMap<String, u64>,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(_opts: &TokensOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
+    let session = session::load(file)?;
+
+    let mut rec = TokensRecord {
+        record_type: "tokens",
+        session_id: session.session_id,
+        project: session.project,
+        ..Default::default()
+    };
+    let mut dedup = UsageDedup::default();
+    for m in &session.messages {
+        let Some(usage) = dedup.first(&m.record) else { continue };
+        rec.input_tokens += usage.input_tokens;
+        rec.output_tokens += usage.output_tokens;
+        rec.cache_creation_input_tokens += usage.cache_creation_input_tokens;
+        rec.cache_read_input_tokens += usage.cache_read_input_tokens;
+        rec.total += usage.total();
+        rec.api_calls += 1;
+        let model = m.record.message.model.clone().unwrap_or_else(|| "unknown".into());
+        *rec.by_model.entry(model).or_default() += usage.total();
+    }
+
+    em.emit(&rec)?;
+    em.flush()?;
+    Ok(())
+}
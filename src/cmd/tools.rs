@@ -4,13 +4,19 @@ use std::io::Write;
 use anyhow::Result;
 use serde::Serialize;
 
+use crate::models::{Timestamp, ToolFilter};
 use crate::output::Emitter;
+use crate::session;
 use crate::util::discover::SessionFile;
 
 // ── Opts ───────────────────────────────────────────────────────────────────
 
+#[derive(Default)]
+#[non_exhaustive]
 pub struct ToolsOpts {
     pub session: String,
+    /// A [`ToolFilter`] spec, e.g. `Bash,Edit` or `~mcp__`.
+    pub tool: Option<String>,
     pub max_tokens: usize,
 }
 
@@ -21,7 +27,7 @@ struct ToolRecord {
     #[serde(rename = "type")]
     record_type: &'static str,
     #[serde(skip_serializing_if = "Option::is_none")]
-    timestamp: Option<String>,
+    timestamp: Option<Timestamp>,
     role: String,
     tool_name: String,
     input_preview: String,
@@ -29,37 +35,27 @@ struct ToolRecord {
 
 // ── run ────────────────────────────────────────────────────────────────────
 
-pub fn run<W: Write>(_opts: &ToolsOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
-    let records = crate::cmd::parse_records(file)?;
+pub fn run<W: Write>(opts: &ToolsOpts, file: &SessionFile, em: &mut Emitter<W>) -> Result<()> {
+    let tools = opts.tool.as_deref().map(ToolFilter::parse).transpose()?;
+    let session = session::load(file)?;
     let start = std::time::Instant::now();
 
     let mut count = 0usize;
-    'outer: for record in &records {
-        let Some(msg) = record.as_message() else { continue };
-
-        let tools = msg.tool_names();
-        if tools.is_empty() {
+    for call in session.tool_calls() {
+        if tools.as_ref().is_some_and(|t| !t.matches(call.name)) {
             continue;
         }
-
-        if let crate::models::MessageContent::Blocks(blocks) = &msg.message.content {
-            for block in blocks {
-                if let crate::models::ContentBlock::ToolUse { name, input, .. } = block {
-                    let preview: String = input.to_string().chars().take(200).collect();
-                    let rec = ToolRecord {
-                        record_type: "tool_call",
-                        timestamp: msg.timestamp.clone(),
-                        role: record.role().to_string(),
-                        tool_name: name.clone(),
-                        input_preview: preview,
-                    };
-                    if !em.emit(&rec)? {
-                        break 'outer;
-                    }
-                    count += 1;
-                }
-            }
+        let rec = ToolRecord {
+            record_type: "tool_call",
+            timestamp: call.timestamp.cloned(),
+            role: call.role.to_string(),
+            tool_name: call.name.to_string(),
+            input_preview: call.input.to_string().chars().take(200).collect(),
+        };
+        if !em.emit(&rec)? {
+            break;
         }
+        count += 1;
     }
 
     let summary = crate::output::SummaryRecord {
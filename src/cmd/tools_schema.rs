@@ -0,0 +1,246 @@
+/// smc tools-schema — the observed shape of each tool's input across the
+/// corpus: every key path, how often it appears, and the JSON types seen.
+///
+/// Paths use `.` for object keys and `[]` for array elements, so
+/// `edits[].old_string` is a key of the objects inside the `edits` array.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::ToolFilter;
+use crate::output::Emitter;
+use crate::session;
+use crate::util::discover::SessionFile;
+
+/// Longest example value kept per path.
+const EXAMPLE_CHARS: usize = 80;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ToolsSchemaOpts {
+    /// A [`ToolFilter`] spec, e.g. `Bash,Edit` or `~mcp__`.
+    pub tool: Option<String>,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct ToolSchemaRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    tool: String,
+    /// Calls inspected.
+    calls: u64,
+    /// Key paths in sorted order, so nested paths follow their parent.
+    fields: Vec<FieldRecord>,
+}
+
+#[derive(Serialize, Debug)]
+struct FieldRecord {
+    path: String,
+    /// Calls whose input has this path.
+    present: u64,
+    pct: f64,
+    /// Calls per JSON type seen at this path.
+    types: BTreeMap<&'static str, u64>,
+    /// A scalar value seen here, truncated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    example: Option<String>,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ToolsSchemaOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let tools = opts.tool.as_deref().map(ToolFilter::parse).transpose()?;
+    let start = std::time::Instant::now();
+
+    let schemas: Mutex<HashMap<String, Schema>> = Mutex::new(HashMap::new());
+    files.par_iter().for_each(|file| {
+        let Ok(s) = session::load(file) else { return };
+        let mut local: HashMap<String, Schema> = HashMap::new();
+        for call in s.tool_calls() {
+            if tools.as_ref().is_some_and(|t| !t.matches(call.name)) {
+                continue;
+            }
+            local.entry(call.name.to_string()).or_default().observe(call.input);
+        }
+        let mut global = schemas.lock().unwrap();
+        for (name, schema) in local {
+            global.entry(name).or_default().merge(schema);
+        }
+    });
+
+    let mut schemas: Vec<(String, Schema)> = schemas.into_inner().unwrap().into_iter().collect();
+    schemas.sort_by(|a, b| b.1.calls.cmp(&a.1.calls).then_with(|| a.0.cmp(&b.0)));
+
+    let count = schemas.len();
+    for (tool, schema) in schemas {
+        let calls = schema.calls;
+        let rec = ToolSchemaRecord {
+            record_type: "tool_schema",
+            tool,
+            calls,
+            fields: schema
+                .fields
+                .into_iter()
+                .map(|(path, f)| FieldRecord {
+                    path,
+                    present: f.present,
+                    pct: (f.present as f64 / calls as f64 * 1000.0).round() / 10.0,
+                    types: f.types,
+                    example: f.example,
+                })
+                .collect(),
+        };
+        if !em.emit(&rec)? {
+            break;
+        }
+    }
+
+    em.emit(&crate::output::SummaryRecord {
+        record_type: "summary",
+        count,
+        files_scanned: Some(files.len()),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct Schema {
+    calls: u64,
+    fields: BTreeMap<String, Field>,
+}
+
+#[derive(Default)]
+struct Field {
+    present: u64,
+    types: BTreeMap<&'static str, u64>,
+    example: Option<String>,
+}
+
+impl Schema {
+    fn observe(&mut self, input: &Value) {
+        self.calls += 1;
+        // Array elements can repeat a path; each call counts once per path and type.
+        let mut seen: HashSet<(String, &'static str)> = HashSet::new();
+        let mut examples: Vec<(String, String)> = Vec::new();
+        if let Value::Object(map) = input {
+            for (k, v) in map {
+                walk(k.clone(), v, &mut seen, &mut examples);
+            }
+        }
+        let mut paths: HashSet<&str> = HashSet::new();
+        for (path, ty) in &seen {
+            let field = self.fields.entry(path.clone()).or_default();
+            *field.types.entry(*ty).or_default() += 1;
+            if paths.insert(path) {
+                field.present += 1;
+            }
+        }
+        for (path, example) in examples {
+            let field = self.fields.entry(path).or_default();
+            field.example.get_or_insert(example);
+        }
+    }
+
+    fn merge(&mut self, other: Schema) {
+        self.calls += other.calls;
+        for (path, f) in other.fields {
+            let field = self.fields.entry(path).or_default();
+            field.present += f.present;
+            for (ty, n) in f.types {
+                *field.types.entry(ty).or_default() += n;
+            }
+            if field.example.is_none() {
+                field.example = f.example;
+            }
+        }
+    }
+}
+
+fn walk(path: String, v: &Value, seen: &mut HashSet<(String, &'static str)>, examples: &mut Vec<(String, String)>) {
+    let ty = match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    match v {
+        Value::Object(map) => {
+            for (k, child) in map {
+                walk(format!("{}.{}", path, k), child, seen, examples);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                walk(format!("{}[]", path), item, seen, examples);
+            }
+        }
+        Value::Null => {}
+        Value::String(s) => examples.push((path.clone(), s.chars().take(EXAMPLE_CHARS).collect())),
+        _ => examples.push((path.clone(), v.to_string())),
+    }
+    seen.insert((path, ty));
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn key_paths_types_and_presence() {
+        let dir = TempDir::new("tools-schema");
+        let call = |name: &str, input: &str| {
+            format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[{{"type":"tool_use","name":"{}","input":{}}}]}}}}"#,
+                name, input
+            ) + "\n"
+        };
+        let body = call("Bash", r#"{"command":"ls","timeout":5000}"#)
+            + &call("Bash", r#"{"command":"pwd"}"#)
+            + &call("MultiEdit", r#"{"edits":[{"old_string":"a"},{"old_string":"b","replace_all":true}]}"#)
+            + &call("Read", r#"{"file_path":"/x"}"#);
+        let files = vec![dir.session("s1", "p", &body)];
+
+        let opts = ToolsSchemaOpts { tool: Some("Bash,MultiEdit".into()), max_tokens: 0 };
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+
+        assert_eq!(records.len(), 3);
+        let bash = &records[0];
+        assert_eq!((bash["tool"].as_str(), bash["calls"].as_u64()), (Some("Bash"), Some(2)));
+        assert_eq!(bash["fields"][0]["path"], "command");
+        assert_eq!(bash["fields"][0]["present"], 2);
+        assert_eq!(bash["fields"][0]["example"], "ls");
+        assert_eq!(bash["fields"][1]["types"]["integer"], 1);
+        assert_eq!(bash["fields"][1]["pct"], 50.0);
+
+        let paths: Vec<(&str, u64)> = records[1]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["path"].as_str().unwrap(), f["present"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(paths, [("edits", 1), ("edits[]", 1), ("edits[].old_string", 1), ("edits[].replace_all", 1)]);
+        assert_eq!(records[2]["count"], 2);
+    }
+}
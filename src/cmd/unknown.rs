@@ -0,0 +1,199 @@
+/// smc unknown — sample the records smc doesn't model, grouped by `type`.
+///
+/// A line whose `type` isn't one [`Record`] knows deserializes to
+/// `Record::Unknown` and is skipped by every other command. Listing a few
+/// of each makes a new Claude Code record type easy to spot and add to
+/// `models.rs`.
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cmd::freq::TypeTag;
+use crate::models::Record;
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct UnknownOpts {
+    pub project: Option<String>,
+    /// Sample lines per type.
+    pub samples: usize,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct UnknownType {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    /// The records' `type` value.
+    key: String,
+    count: usize,
+    sessions: usize,
+    samples: Vec<Sample>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct Sample {
+    project: String,
+    session_id: String,
+    line: usize,
+    /// The line as stored.
+    record: Value,
+}
+
+#[derive(Serialize, Debug)]
+struct UnknownSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    count: usize,
+    types: usize,
+    files_scanned: usize,
+    elapsed_ms: u128,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &UnknownOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let scanned: Vec<HashMap<String, Found>> = filtered.par_iter().map(|file| scan(file, opts.samples)).collect();
+
+    let mut types: HashMap<String, UnknownType> = HashMap::new();
+    for found in scanned {
+        for (key, f) in found {
+            let t = types.entry(key.clone()).or_insert_with(|| UnknownType {
+                record_type: "unknown_type",
+                key,
+                count: 0,
+                sessions: 0,
+                samples: Vec::new(),
+            });
+            t.count += f.count;
+            t.sessions += 1;
+            let room = opts.samples.saturating_sub(t.samples.len());
+            t.samples.extend(f.samples.into_iter().take(room));
+        }
+    }
+    let mut types: Vec<UnknownType> = types.into_values().collect();
+    types.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+
+    let count = types.iter().map(|t| t.count).sum();
+    let n_types = types.len();
+    for t in &types {
+        if !em.emit(t)? {
+            break;
+        }
+    }
+
+    em.emit(&UnknownSummary {
+        record_type: "summary",
+        count,
+        types: n_types,
+        files_scanned: filtered.len(),
+        elapsed_ms: start.elapsed().as_millis(),
+    })?;
+    em.flush()?;
+    Ok(())
+}
+
+// ── Scanning ───────────────────────────────────────────────────────────────
+
+/// Unknown records of one type in one file.
+#[derive(Default)]
+struct Found {
+    count: usize,
+    samples: Vec<Sample>,
+}
+
+/// Unknown records of `file` by type, keeping the first `samples` of each.
+fn scan(file: &SessionFile, samples: usize) -> HashMap<String, Found> {
+    let mut found: HashMap<String, Found> = HashMap::new();
+    let Ok(f) = std::fs::File::open(&file.path) else { return found };
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+
+    // Whether a type is unknown depends only on the tag, so each one is
+    // fully parsed once.
+    let mut unknown: HashMap<String, bool> = HashMap::new();
+    for (line_num, line) in reader.lines().enumerate() {
+        let Ok(line) = line else { continue };
+        let Ok(TypeTag { kind: Some(key) }) = serde_json::from_str::<TypeTag>(&line) else { continue };
+        let is_unknown = *unknown
+            .entry(key.clone())
+            .or_insert_with(|| matches!(serde_json::from_str::<Record>(&line), Ok(Record::Unknown)));
+        if !is_unknown {
+            continue;
+        }
+        let f = found.entry(key).or_default();
+        f.count += 1;
+        if f.samples.len() < samples {
+            let Ok(record) = serde_json::from_str::<Value>(&line) else { continue };
+            f.samples.push(Sample {
+                project: file.project_name.clone(),
+                session_id: file.session_id.clone(),
+                line: line_num + 1,
+                record,
+            });
+        }
+    }
+    found
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn samples_unknown_records_by_type() {
+        let dir = TempDir::new("unknown");
+        let file = |id: &str, lines: &[&str]| {
+            let body = lines.join("\n");
+            dir.session(id, "p", &body)
+        };
+        let files = vec![
+            file(
+                "s1",
+                &[
+                    r#"{"type":"user","message":{"role":"user","content":"hi"}}"#,
+                    r#"{"type":"summary","summary":"one","leafUuid":"a"}"#,
+                    r#"{"type":"summary","summary":"two","leafUuid":"b"}"#,
+                    r#"{"type":"progress","data":{}}"#,
+                    r#"{"uuid":"no type"}"#,
+                ],
+            ),
+            file("s2", &[r#"{"type":"custom-title","title":"x"}"#, r#"{"type":"summary","summary":"three"}"#]),
+        ];
+        let opts = UnknownOpts { project: None, samples: 2, max_tokens: 0 };
+
+        let mut em = Emitter::capturing(0);
+        run(&opts, &files, &mut em).unwrap();
+        let records = em.into_records();
+        assert_eq!(records.len(), 3);
+        let summary = &records[0];
+        assert_eq!((summary["key"].as_str(), summary["count"].as_u64()), (Some("summary"), Some(3)));
+        assert_eq!(summary["sessions"], 2);
+        assert_eq!(summary["samples"].as_array().unwrap().len(), 2);
+        assert_eq!(summary["samples"][0]["record"]["summary"], "one");
+        assert_eq!((records[1]["key"].as_str(), records[1]["samples"][0]["line"].as_u64()), (Some("custom-title"), Some(1)));
+        assert_eq!((records[2]["count"].as_u64(), records[2]["types"].as_u64()), (Some(4), Some(2)));
+    }
+}
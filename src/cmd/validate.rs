@@ -0,0 +1,240 @@
+/// smc validate — report every JSONL line that fails to deserialize.
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::models::Record;
+use crate::output::Emitter;
+use crate::util::discover::SessionFile;
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct ValidateOpts {
+    pub project: Option<String>,
+    pub max_tokens: usize,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct ParseErrorRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    project: &'a str,
+    session_id: &'a str,
+    file: String,
+    line: usize,
+    kind: &'a str,
+    error: &'a str,
+}
+
+#[derive(Serialize, Debug)]
+struct ValidateSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    count: usize,
+    files_scanned: usize,
+    lines_scanned: usize,
+    kinds: BTreeMap<String, usize>,
+    elapsed_ms: u128,
+}
+
+/// What `--strict` adds after its warnings when any line failed.
+#[derive(Serialize, Debug)]
+struct StrictSummary {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    count: usize,
+    files_scanned: usize,
+    kinds: BTreeMap<String, usize>,
+}
+
+/// One line that failed to parse as a [`Record`].
+#[derive(Debug)]
+pub struct ParseIssue {
+    pub line: usize,
+    pub kind: String,
+    pub error: String,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &ValidateOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let start = std::time::Instant::now();
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let scanned: Vec<(usize, Vec<ParseIssue>)> = filtered
+        .par_iter()
+        .map(|file| scan(file).unwrap_or_else(|e| {
+            (0, vec![ParseIssue { line: 0, kind: "io".into(), error: e.to_string() }])
+        }))
+        .collect();
+
+    let mut kinds: BTreeMap<String, usize> = BTreeMap::new();
+    let mut count = 0usize;
+    let mut lines_scanned = 0usize;
+    let mut budget_left = true;
+
+    for (file, (lines, issues)) in filtered.iter().zip(&scanned) {
+        lines_scanned += lines;
+        for issue in issues {
+            *kinds.entry(issue.kind.clone()).or_default() += 1;
+            count += 1;
+            if budget_left {
+                budget_left = em.emit(&ParseErrorRecord {
+                    record_type: "parse_error",
+                    project: &file.project_name,
+                    session_id: &file.session_id,
+                    file: file.path.display().to_string(),
+                    line: issue.line,
+                    kind: &issue.kind,
+                    error: &issue.error,
+                })?;
+            }
+        }
+    }
+
+    let summary = ValidateSummary {
+        record_type: "summary",
+        count,
+        files_scanned: filtered.len(),
+        lines_scanned,
+        kinds,
+        elapsed_ms: start.elapsed().as_millis(),
+    };
+    em.emit(&summary)?;
+
+    em.flush()?;
+    Ok(())
+}
+
+/// Emit one inline warning per unparseable line (used by global `--strict`),
+/// then, if there were any, a `parse_summary` record with totals by kind.
+/// A pass of its own: the command that follows reads the files again.
+pub fn warn_issues<W: Write>(files: &[&SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let mut kinds: BTreeMap<String, usize> = BTreeMap::new();
+    for file in files {
+        let path = file.path.display().to_string();
+        match scan(file) {
+            Ok((_, issues)) => {
+                for issue in issues {
+                    em.warn(Some(&path), &format!("line {}: {}", issue.line, issue.error));
+                    *kinds.entry(issue.kind).or_default() += 1;
+                }
+            }
+            Err(e) => {
+                em.warn(Some(&path), &e.to_string());
+                *kinds.entry("io".into()).or_default() += 1;
+            }
+        }
+    }
+    if !kinds.is_empty() {
+        em.emit(&StrictSummary {
+            record_type: "parse_summary",
+            count: kinds.values().sum(),
+            files_scanned: files.len(),
+            kinds,
+        })?;
+    }
+    em.flush()
+}
+
+// ── Scanning ───────────────────────────────────────────────────────────────
+
+/// Parse every non-empty line; returns (lines scanned, issues).
+pub fn scan(file: &SessionFile) -> Result<(usize, Vec<ParseIssue>)> {
+    let f = std::fs::File::open(&file.path)?;
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+
+    let mut lines = 0usize;
+    let mut issues = Vec::new();
+
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                issues.push(ParseIssue { line: line_num + 1, kind: "io".into(), error: e.to_string() });
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        lines += 1;
+        if let Err(e) = serde_json::from_str::<Record>(&line) {
+            issues.push(ParseIssue { line: line_num + 1, kind: error_kind(&e), error: e.to_string() });
+        }
+    }
+
+    Ok((lines, issues))
+}
+
+/// Collapse a serde error into a groupable kind: position info and the
+/// "expected one of …" tail are dropped.
+fn error_kind(e: &serde_json::Error) -> String {
+    let msg = e.to_string();
+    let msg = msg.split(" at line ").next().unwrap_or(&msg);
+    let msg = msg.split(", expected").next().unwrap_or(msg);
+    msg.to_string()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn kind_of(line: &str) -> String {
+        error_kind(&serde_json::from_str::<Record>(line).unwrap_err())
+    }
+
+    #[test]
+    fn syntax_errors_drop_position() {
+        assert_eq!(kind_of("{oops"), "key must be a string");
+    }
+
+    #[test]
+    fn data_errors_keep_field_name() {
+        assert_eq!(kind_of(r#"{"type":"user"}"#), "missing field `message`");
+        assert_eq!(kind_of(r#"{"type":"system"}"#), "missing field `message`");
+        assert_eq!(kind_of(r#"{"type":"system","subtype":"api_error"}"#), "missing field `error`");
+        assert_eq!(
+            kind_of(r#"{"type":"system","subtype":"api_error","error":{},"retryAttempt":"2"}"#),
+            "invalid type: string \"2\""
+        );
+    }
+
+    #[test]
+    fn strict_warnings_end_with_totals_by_kind() {
+        let dir = TempDir::new("strict");
+        let body = "{oops\n{\"type\":\"user\"}\n{\"type\":\"user\"}\n";
+        let file = dir.session("s1", "p", body);
+
+        let mut em = Emitter::capturing(0);
+        warn_issues(&[&file], &mut em).unwrap();
+        let records = em.into_records();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0]["type"], "warning");
+        let summary = &records[3];
+        assert_eq!((summary["type"].as_str(), summary["count"].as_u64()), (Some("parse_summary"), Some(3)));
+        assert_eq!(summary["kinds"]["missing field `message`"], 2);
+        assert_eq!(summary["kinds"]["key must be a string"], 1);
+
+        let mut em = Emitter::capturing(0);
+        warn_issues(&[], &mut em).unwrap();
+        assert!(em.into_records().is_empty());
+    }
+}
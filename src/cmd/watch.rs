@@ -0,0 +1,194 @@
+/// smc watch — `tail -f` across every session.
+///
+/// Existing content is skipped: each known file starts with a cursor at its
+/// current end, and files created later are read from the top. Filesystem
+/// notifications trigger a read of whatever was appended, and each complete
+/// line goes through search's filters (see `search::LineSearch`). Runs until
+/// interrupted or the token budget is spent.
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use crate::cmd::search::{LineSearch, SearchOpts};
+use crate::output::Emitter;
+use crate::util::discover::{self, SessionFile};
+use crate::util::tail::{self, Cursor};
+
+// ── Opts ───────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+#[non_exhaustive]
+pub struct WatchOpts {
+    /// Filters and record shape; `queries` may be empty to stream everything.
+    pub search: SearchOpts,
+    /// Claude projects directory to watch.
+    pub dir: PathBuf,
+    /// Only sessions whose ID starts with this.
+    pub session: Option<String>,
+}
+
+// ── Records ────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Debug)]
+struct WatchStart {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    dir: String,
+    sessions: usize,
+}
+
+// ── run ────────────────────────────────────────────────────────────────────
+
+pub fn run<W: Write>(opts: &WatchOpts, files: &[SessionFile], em: &mut Emitter<W>) -> Result<()> {
+    let search = LineSearch::new(&opts.search)?;
+    let mut tails = Tails::new(opts, files);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("cannot start filesystem watcher")?;
+    watcher
+        .watch(&opts.dir, RecursiveMode::Recursive)
+        .with_context(|| format!("cannot watch {}", opts.dir.display()))?;
+
+    em.emit(&WatchStart {
+        record_type: "watch",
+        dir: opts.dir.display().to_string(),
+        sessions: tails.known.values().filter(|t| t.is_some()).count(),
+    })?;
+    em.flush()?;
+
+    let mut emitted = 0usize;
+    for event in rx {
+        let event = match event {
+            Ok(e) => e,
+            Err(e) => {
+                em.warn(None, &format!("watch error: {}", e));
+                em.flush()?;
+                continue;
+            }
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            continue;
+        }
+        for path in &event.paths {
+            for hit in tails.drain(path, &search) {
+                if !em.emit(&hit)? {
+                    em.flush()?;
+                    return Ok(());
+                }
+                emitted += 1;
+                if opts.search.max_results > 0 && emitted >= opts.search.max_results {
+                    em.flush()?;
+                    return Ok(());
+                }
+            }
+        }
+        em.flush()?;
+    }
+    Ok(())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// Per-file read positions. `None` marks a file the session filter excludes.
+struct Tails<'a> {
+    known: HashMap<PathBuf, Option<(SessionFile, Cursor)>>,
+    session: Option<&'a str>,
+}
+
+impl<'a> Tails<'a> {
+    fn new(opts: &'a WatchOpts, files: &[SessionFile]) -> Self {
+        let mut tails = Self { known: HashMap::new(), session: opts.session.as_deref() };
+        for f in files {
+            let entry = tails.selected(f).then(|| (f.clone(), Cursor::at_end(&f.path)));
+            tails.known.insert(f.path.clone(), entry);
+        }
+        tails
+    }
+
+    fn selected(&self, f: &SessionFile) -> bool {
+        self.session.map_or(true, |s| f.session_id.starts_with(s))
+    }
+
+    /// Matching records for the lines appended to `path` since the last call.
+    fn drain(&mut self, path: &Path, search: &LineSearch) -> Vec<serde_json::Value> {
+        if !self.known.contains_key(path) {
+            // Not seen at startup: a new session, so everything in it is new.
+            let Some(f) = discover::session_file(path) else { return Vec::new() };
+            let entry = self.selected(&f).then(|| (f, Cursor::default()));
+            self.known.insert(path.to_path_buf(), entry);
+        }
+        let Some(Some((file, cursor))) = self.known.get_mut(path) else { return Vec::new() };
+
+        let mut hits = Vec::new();
+        let _ = tail::read_appended(&file.path, cursor, |line, raw| {
+            hits.extend(search.check(file, line, raw));
+        });
+        hits
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    fn opts(dir: &Path, queries: &[&str]) -> WatchOpts {
+        WatchOpts {
+            search: SearchOpts::builder()
+                .queries(queries.iter().map(|q| q.to_string()).collect())
+                .max_results(0)
+                .truncate(0)
+                .build(),
+
+            dir: dir.to_path_buf(),
+            session: None,
+        }
+    }
+
+    fn user_line(text: &str) -> String {
+        format!(
+            r#"{{"type":"user","sessionId":"s1","timestamp":"2025-01-01T00:00:00Z","message":{{"role":"user","content":"{}"}}}}"#,
+            text
+        ) + "\n"
+    }
+
+    fn append(path: &Path, s: &str) {
+        let mut f = std::fs::OpenOptions::new().append(true).create(true).open(path).unwrap();
+        f.write_all(s.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn streams_only_appended_matches() {
+        let dir = TempDir::new("watch");
+        let proj = dir.join("-Users-me-GitHub-alpha");
+        std::fs::create_dir_all(&proj).unwrap();
+        let old = proj.join("s1.jsonl");
+        std::fs::write(&old, user_line("deploy yesterday")).unwrap();
+        let files = discover::discover_jsonl_files(&dir).unwrap();
+
+        let o = opts(&dir, &["deploy"]);
+        let search = LineSearch::new(&o.search).unwrap();
+        let mut tails = Tails::new(&o, &files);
+        assert!(tails.drain(&old, &search).is_empty());
+
+        append(&old, &user_line("unrelated"));
+        append(&old, &user_line("deploy now"));
+        let hits = tails.drain(&old, &search);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["line"], 3);
+        assert_eq!(hits[0]["project"], "alpha");
+
+        // A session created after startup is read from the top.
+        let new = proj.join("s2.jsonl");
+        append(&new, &user_line("deploy again"));
+        assert_eq!(tails.drain(&new, &search).len(), 1);
+        assert!(tails.drain(&proj.join("notes.txt"), &search).is_empty());
+    }
+}
@@ -7,9 +7,45 @@
 //!   util/    — token counting, JSONL discovery
 //!   output/  — `Emitter<W>`, shared record types
 //!   models/  — Claude Code JSONL record types (deserialization)
+//!   session  — typed session list / load API
 //!   cmd/     — one module per subcommand, each exposing XxxOpts + run(opts, &mut Emitter)
+//!   nonblocking — async discovery and search (feature `tokio`)
+//!
+//! ```
+//! use smc::{cmd, output::Emitter, util::discover};
+//!
+//! # let dir = std::env::temp_dir().join("smc-doctest-empty");
+//! let files = discover::discover_jsonl_files(&dir)?;
+//! let opts = cmd::search::SearchOpts::builder()
+//!     .query("authentication")
+//!     .max_results(10)
+//!     .build();
+//!
+//! let mut em = Emitter::capturing(0);
+//! cmd::search::run(&opts, &files, &mut em)?;
+//! let records = em.into_records();
+//! assert_eq!(records.last().unwrap()["type"], "summary");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Option structs are `#[non_exhaustive]`, so adding an option isn't a
+//! breaking change. [`cmd::search::SearchOpts`] has a builder whose defaults
+//! match the CLI; every other `cmd::*::XxxOpts` (and [`session::ListOpts`])
+//! starts from `Default` — empty strings, zero counts, `None` filters — and
+//! the caller sets the fields it needs:
+//!
+//! ```
+//! use smc::cmd;
+//!
+//! let mut opts = cmd::tokens::TokensOpts::default();
+//! opts.session = "394af".into();
+//! # let _ = opts;
+//! ```
 
 pub mod util;
 pub mod output;
 pub mod models;
 pub mod cmd;
+pub mod session;
+#[cfg(feature = "tokio")]
+pub mod nonblocking;
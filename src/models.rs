@@ -2,22 +2,80 @@
 ///
 /// Claude Code stores conversations as JSONL in ~/.claude/projects/.
 /// Each line is one of these record types.
-use serde::Deserialize;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
+
+use crate::util::time;
 
 // ── Top-level record ───────────────────────────────────────────────────────
 
 #[derive(Debug, Deserialize)]
-#[serde(tag = "type", rename_all = "kebab-case")]
+#[serde(from = "RawRecord")]
 pub enum Record {
     User(MessageRecord),
     Assistant(MessageRecord),
     System(MessageRecord),
+    /// `system` record with `subtype: "api_error"` — a failed API call
+    /// that Claude Code is about to retry.
+    ApiError(ApiErrorRecord),
+    FileHistorySnapshot(serde_json::Value),
+    Progress(serde_json::Value),
+    Unknown,
+}
+
+/// Wire shape: api_error events share the `system` tag with system messages
+/// and are told apart by `subtype`.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum RawRecord {
+    User(MessageRecord),
+    Assistant(MessageRecord),
+    System(RawSystem),
     FileHistorySnapshot(serde_json::Value),
     Progress(serde_json::Value),
     #[serde(other)]
     Unknown,
 }
 
+enum RawSystem {
+    Message(MessageRecord),
+    ApiError(ApiErrorRecord),
+}
+
+impl<'de> Deserialize<'de> for RawSystem {
+    /// Dispatch on `subtype` rather than on whichever shape happens to fit,
+    /// so a malformed line reports its field error, not a generic mismatch.
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let v = serde_json::Value::deserialize(d)?;
+        if v.get("subtype").and_then(|s| s.as_str()) == Some("api_error") {
+            ApiErrorRecord::deserialize(v).map(RawSystem::ApiError).map_err(D::Error::custom)
+        } else {
+            MessageRecord::deserialize(v).map(RawSystem::Message).map_err(D::Error::custom)
+        }
+    }
+}
+
+impl From<RawRecord> for Record {
+    fn from(raw: RawRecord) -> Self {
+        match raw {
+            RawRecord::User(m) => Record::User(m),
+            RawRecord::Assistant(m) => Record::Assistant(m),
+            RawRecord::System(RawSystem::Message(m)) => Record::System(m),
+            RawRecord::System(RawSystem::ApiError(e)) => Record::ApiError(e),
+            RawRecord::FileHistorySnapshot(v) => Record::FileHistorySnapshot(v),
+            RawRecord::Progress(v) => Record::Progress(v),
+            RawRecord::Unknown => Record::Unknown,
+        }
+    }
+}
+
 impl Record {
     pub fn as_message(&self) -> Option<&MessageRecord> {
         match self {
@@ -48,17 +106,196 @@ pub struct MessageRecord {
     pub uuid: Option<String>,
     pub parent_uuid: Option<serde_json::Value>,
     pub session_id: Option<String>,
-    pub timestamp: Option<String>,
+    pub timestamp: Option<Timestamp>,
     pub cwd: Option<String>,
     pub git_branch: Option<String>,
     pub version: Option<String>,
+    /// Set on synthetic assistant messages like "API Error: 529 Overloaded".
+    #[serde(default)]
+    pub is_api_error_message: bool,
+    /// Written by a sub-agent a `Task` call spawned, not the main conversation.
+    #[serde(default)]
+    pub is_sidechain: bool,
+    /// Claude Code's structured copy of a tool result (stdout and stderr,
+    /// file content, patches), on the user message carrying it.
+    pub tool_use_result: Option<serde_json::Value>,
     pub message: Message,
 }
 
+// ── API error ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorRecord {
+    pub uuid: Option<String>,
+    pub session_id: Option<String>,
+    pub timestamp: Option<Timestamp>,
+    pub level: Option<String>,
+    pub error: serde_json::Value,
+    pub cause: Option<serde_json::Value>,
+    pub retry_in_ms: Option<f64>,
+    pub retry_attempt: Option<u32>,
+    pub max_retries: Option<u32>,
+}
+
+impl ApiErrorRecord {
+    /// Error type, e.g. `overloaded_error`, `ECONNRESET`, or `http_500`.
+    pub fn kind(&self) -> String {
+        for ptr in ["/error/error/type", "/error/type"] {
+            if let Some(k) = self.error.pointer(ptr).and_then(|v| v.as_str()) {
+                if k != "error" {
+                    return k.to_string();
+                }
+            }
+        }
+        if let Some(code) = self.cause.as_ref().and_then(|c| c.get("code")).and_then(|v| v.as_str()) {
+            return code.to_string();
+        }
+        match self.status() {
+            Some(s) => format!("http_{}", s),
+            None => "unknown".into(),
+        }
+    }
+
+    pub fn status(&self) -> Option<u64> {
+        self.error.get("status").and_then(|v| v.as_u64())
+    }
+
+    /// Human-readable error message, if the payload carries one.
+    pub fn message(&self) -> Option<String> {
+        self.error
+            .pointer("/error/error/message")
+            .or_else(|| self.error.pointer("/error/message"))
+            .or_else(|| self.cause.as_ref().and_then(|c| c.get("message")))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    }
+}
+
+// ── Timestamp ──────────────────────────────────────────────────────────────
+
+/// A record timestamp, parsed once at deserialization.
+///
+/// `raw` is kept verbatim for display and serializes back out unchanged;
+/// `utc` is `None` when the string is not valid RFC 3339.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    pub raw: String,
+    pub utc: Option<DateTime<Utc>>,
+}
+
+impl Timestamp {
+    pub fn new(raw: impl Into<String>) -> Self {
+        let raw = raw.into();
+        let utc = time::parse_rfc3339(&raw);
+        Self { raw, utc }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// `YYYY-MM-DD` in UTC, falling back to the raw prefix.
+    pub fn date(&self) -> String {
+        match self.utc {
+            Some(dt) => dt.format("%Y-%m-%d").to_string(),
+            None => self.raw.get(..10).unwrap_or(&self.raw).to_string(),
+        }
+    }
+}
+
+impl Ord for Timestamp {
+    /// Chronological order; unparseable timestamps sort first, by raw string.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.utc.cmp(&other.utc).then_with(|| self.raw.cmp(&other.raw))
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d).map(Self::new)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.raw)
+    }
+}
+
+// ── Message body ───────────────────────────────────────────────────────────
+
 #[derive(Debug, Deserialize)]
 pub struct Message {
+    /// API response id (assistant messages only). Claude Code writes one
+    /// line per content block, each repeating the response's id and usage.
+    #[serde(default)]
+    pub id: Option<String>,
     pub role: String,
     pub content: MessageContent,
+    /// Assistant messages only.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Assistant messages only; see [`UsageDedup`] before summing it.
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// Token usage reported by the API for one assistant message.
+/// Missing or `null` counts read as zero.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Usage {
+    #[serde(default, deserialize_with = "null_as_zero")]
+    pub input_tokens: u64,
+    #[serde(default, deserialize_with = "null_as_zero")]
+    pub output_tokens: u64,
+    #[serde(default, deserialize_with = "null_as_zero")]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default, deserialize_with = "null_as_zero")]
+    pub cache_read_input_tokens: u64,
+}
+
+impl Usage {
+    /// Input + output + cache tokens.
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+
+    pub fn add(&mut self, other: &Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+}
+
+/// Counts each API response's usage once. The lines of one response share
+/// its `message.id` and all repeat its usage; lines without an id are
+/// counted as they come.
+#[derive(Debug, Default)]
+pub struct UsageDedup {
+    seen: HashSet<String>,
+}
+
+impl UsageDedup {
+    /// `msg`'s usage, unless an earlier line of the same response had it.
+    pub fn first<'a>(&mut self, msg: &'a MessageRecord) -> Option<&'a Usage> {
+        let usage = msg.message.usage.as_ref()?;
+        match &msg.message.id {
+            Some(id) => self.seen.insert(id.clone()).then_some(usage),
+            None => Some(usage),
+        }
+    }
+}
+
+fn null_as_zero<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+    Ok(Option::<u64>::deserialize(d)?.unwrap_or(0))
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,6 +318,9 @@ pub enum ContentBlock {
     ToolResult {
         tool_use_id: Option<String>,
         content: Option<serde_json::Value>,
+        /// Set when the tool call failed.
+        #[serde(default)]
+        is_error: bool,
     },
     #[serde(other)]
     Other,
@@ -89,6 +329,11 @@ pub enum ContentBlock {
 // ── Content extraction ─────────────────────────────────────────────────────
 
 impl MessageRecord {
+    /// Parsed UTC timestamp, if present and valid.
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.as_ref().and_then(|t| t.utc)
+    }
+
     /// All text content (text blocks + thinking + tool use/results).
     pub fn text_content(&self) -> String {
         match &self.message.content {
@@ -211,3 +456,570 @@ impl MessageRecord {
         }
     }
 }
+
+// ── User message classification ────────────────────────────────────────────
+
+/// What produced a `user` record.
+///
+/// Claude Code logs slash commands, their local output, hook-injected
+/// reminders, and tool results all under the `user` role; only `Human`
+/// is something the person actually typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserKind {
+    Human,
+    SlashCommand,
+    HookInjected,
+    ToolResult,
+}
+
+pub const COMMAND_NAME_TAG: &str = "<command-name>";
+pub const COMMAND_STDOUT_TAG: &str = "<local-command-stdout>";
+/// Opens some slash command invocations, ahead of `<command-name>`.
+pub const COMMAND_MESSAGE_TAG: &str = "<command-message>";
+pub const SYSTEM_REMINDER_TAG: &str = "<system-reminder>";
+
+impl MessageRecord {
+    /// Slash command invocation or its local output: the text opens with
+    /// one of their tags, so a prompt that quotes one stays human.
+    pub fn is_slash_command(&self) -> bool {
+        let text = self.text_no_thinking();
+        let trimmed = text.trim_start();
+        [COMMAND_NAME_TAG, COMMAND_MESSAGE_TAG, COMMAND_STDOUT_TAG]
+            .iter()
+            .any(|tag| trimmed.starts_with(tag))
+    }
+
+    /// The slash command name, e.g. `/clear`.
+    pub fn command_name(&self) -> Option<String> {
+        let text = self.text_no_thinking();
+        let start = text.find(COMMAND_NAME_TAG)? + COMMAND_NAME_TAG.len();
+        let end = text[start..].find("</command-name>")? + start;
+        Some(text[start..end].trim().to_string())
+    }
+
+    /// Content injected by a hook rather than typed: the text is made up
+    /// entirely of `<system-reminder>` blocks.
+    pub fn is_hook_injected(&self) -> bool {
+        let text = self.text_no_thinking();
+        let trimmed = text.trim_start();
+        trimmed.starts_with(SYSTEM_REMINDER_TAG) && trimmed.trim_end().ends_with("</system-reminder>")
+    }
+
+    /// Only tool_result blocks, no text.
+    pub fn is_tool_result(&self) -> bool {
+        match &self.message.content {
+            MessageContent::Blocks(blocks) => {
+                blocks.iter().any(|b| matches!(b, ContentBlock::ToolResult { .. }))
+                    && !blocks.iter().any(|b| matches!(b, ContentBlock::Text { .. }))
+            }
+            MessageContent::Text(_) => false,
+        }
+    }
+
+    /// Any tool_result block, with or without text alongside it.
+    pub fn has_tool_result(&self) -> bool {
+        match &self.message.content {
+            MessageContent::Blocks(blocks) => blocks.iter().any(|b| matches!(b, ContentBlock::ToolResult { .. })),
+            MessageContent::Text(_) => false,
+        }
+    }
+
+    /// Classify a `user` record; `None` for other roles.
+    pub fn user_kind(&self) -> Option<UserKind> {
+        if self.message.role != "user" {
+            return None;
+        }
+        Some(if self.is_tool_result() {
+            UserKind::ToolResult
+        } else if self.is_slash_command() {
+            UserKind::SlashCommand
+        } else if self.is_hook_injected() {
+            UserKind::HookInjected
+        } else {
+            UserKind::Human
+        })
+    }
+}
+
+// ── Role filter ────────────────────────────────────────────────────────────
+
+/// A `--role` value: comma-separated roles, with `asst` for `assistant` and
+/// the `any-human` pseudo-role — `user` records that carry no tool result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleFilter(Vec<RoleMatch>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RoleMatch {
+    User,
+    Assistant,
+    System,
+    AnyHuman,
+}
+
+impl RoleFilter {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let roles = s
+            .split(',')
+            .map(|r| match r.trim().to_lowercase().as_str() {
+                "user" => Ok(RoleMatch::User),
+                "assistant" | "asst" => Ok(RoleMatch::Assistant),
+                "system" => Ok(RoleMatch::System),
+                "any-human" => Ok(RoleMatch::AnyHuman),
+                other => anyhow::bail!("unknown role '{}' — use: user, assistant (asst), system, any-human", other),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self(roles))
+    }
+
+    /// Whether a message with this role passes; `has_tool_result` only
+    /// matters for `any-human`.
+    pub fn matches(&self, role: &str, has_tool_result: bool) -> bool {
+        self.0.iter().any(|m| match m {
+            RoleMatch::User => role == "user",
+            RoleMatch::Assistant => role == "assistant",
+            RoleMatch::System => role == "system",
+            RoleMatch::AnyHuman => role == "user" && !has_tool_result,
+        })
+    }
+}
+
+// ── Tool filter ────────────────────────────────────────────────────────────
+
+/// A `--tool` value: comma-separated tool names, matched exactly but
+/// ignoring case; a `~` prefix matches a substring instead, so `~mcp__`
+/// catches every MCP tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolFilter(Vec<ToolMatch>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ToolMatch {
+    Exact(String),
+    Contains(String),
+}
+
+impl ToolFilter {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let tools = s
+            .split(',')
+            .map(|t| {
+                let t = t.trim().to_lowercase();
+                match t.strip_prefix('~') {
+                    Some("") => anyhow::bail!("empty tool name after '~' in '{}'", s),
+                    Some(sub) => Ok(ToolMatch::Contains(sub.to_string())),
+                    None if t.is_empty() => anyhow::bail!("empty tool name in '{}'", s),
+                    None => Ok(ToolMatch::Exact(t)),
+                }
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self(tools))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.0.iter().any(|m| match m {
+            ToolMatch::Exact(t) => name == *t,
+            ToolMatch::Contains(t) => name.contains(t.as_str()),
+        })
+    }
+}
+
+// ── Borrowed record (search hot path) ──────────────────────────────────────
+
+/// Minimal borrow-based view of one JSONL line.
+///
+/// Only the fields search needs are deserialized; strings borrow from the
+/// input line unless they contain escapes, and tool inputs and results stay
+/// raw JSON slices of it.  Metadata we never read (uuid, cwd, version, …) is
+/// skipped entirely.  Display-heavy commands keep using the owned [`Record`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordRef<'a> {
+    #[serde(rename = "type", borrow)]
+    pub record_type: Cow<'a, str>,
+    #[serde(default, borrow)]
+    pub timestamp: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    pub git_branch: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    pub message: Option<MessageRef<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageRef<'a> {
+    #[serde(borrow)]
+    pub content: ContentRef<'a>,
+}
+
+/// A string or an array of blocks. Deserialized by hand: serde buffers
+/// untagged and internally tagged enums, and a [`RawValue`] can't be read
+/// back out of that buffer.
+#[derive(Debug)]
+pub enum ContentRef<'a> {
+    Text(Cow<'a, str>),
+    Blocks(Vec<BlockRef<'a>>),
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for ContentRef<'a> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct ContentVisitor<'a>(std::marker::PhantomData<&'a ()>);
+
+        impl<'de: 'a, 'a> Visitor<'de> for ContentVisitor<'a> {
+            type Value = ContentRef<'a>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a string or an array of content blocks")
+            }
+
+            fn visit_borrowed_str<E>(self, s: &'de str) -> Result<Self::Value, E> {
+                Ok(ContentRef::Text(Cow::Borrowed(s)))
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(ContentRef::Text(Cow::Owned(s.to_string())))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut blocks = Vec::new();
+                while let Some(block) = seq.next_element()? {
+                    blocks.push(block);
+                }
+                Ok(ContentRef::Blocks(blocks))
+            }
+        }
+
+        d.deserialize_any(ContentVisitor(std::marker::PhantomData))
+    }
+}
+
+#[derive(Debug)]
+pub enum BlockRef<'a> {
+    Text { text: Cow<'a, str> },
+    Thinking { thinking: Cow<'a, str> },
+    ToolUse { name: Cow<'a, str>, input: &'a RawValue },
+    ToolResult { content: Option<&'a RawValue> },
+    Other,
+}
+
+/// Every field any [`BlockRef`] reads, flat, so the raw values are read
+/// straight from the line rather than through serde's enum buffer.
+#[derive(Deserialize)]
+struct FlatBlockRef<'a> {
+    #[serde(rename = "type", borrow)]
+    kind: Cow<'a, str>,
+    #[serde(default, borrow)]
+    text: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    thinking: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    name: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    input: Option<&'a RawValue>,
+    #[serde(default, borrow)]
+    content: Option<&'a RawValue>,
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for BlockRef<'a> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let b = FlatBlockRef::deserialize(d)?;
+        Ok(match b.kind.as_ref() {
+            "text" => BlockRef::Text { text: b.text.unwrap_or_default() },
+            "thinking" => BlockRef::Thinking { thinking: b.thinking.unwrap_or_default() },
+            "tool_use" => BlockRef::ToolUse {
+                name: b.name.unwrap_or_default(),
+                input: b.input.unwrap_or(RawValue::NULL),
+            },
+            "tool_result" => BlockRef::ToolResult { content: b.content },
+            _ => BlockRef::Other,
+        })
+    }
+}
+
+impl<'a> RecordRef<'a> {
+    /// Message role, or `None` for non-message records.
+    pub fn role(&self) -> Option<&'static str> {
+        match self.record_type.as_ref() {
+            "user" => Some("user"),
+            "assistant" => Some("assistant"),
+            "system" => Some("system"),
+            _ => None,
+        }
+    }
+
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.as_deref().and_then(time::parse_rfc3339)
+    }
+
+    fn blocks(&self) -> &[BlockRef<'a>] {
+        match &self.message {
+            Some(MessageRef { content: ContentRef::Blocks(b) }) => b,
+            _ => &[],
+        }
+    }
+
+    fn plain_text(&self) -> Option<&str> {
+        match &self.message {
+            Some(MessageRef { content: ContentRef::Text(s) }) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Same as [`MessageRecord::has_tool_result`].
+    pub fn has_tool_result(&self) -> bool {
+        self.blocks().iter().any(|b| matches!(b, BlockRef::ToolResult { .. }))
+    }
+
+    /// Same as [`MessageRecord::tool_names`].
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.blocks()
+            .iter()
+            .filter_map(|b| match b {
+                BlockRef::ToolUse { name, .. } => Some(name.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Same as [`MessageRecord::touches_file`].
+    pub fn touches_file(&self, path: &str) -> bool {
+        let path_lower = path.to_lowercase();
+        self.blocks().iter().any(|block| match block {
+            BlockRef::ToolUse { input, .. } => input.get().to_lowercase().contains(&path_lower),
+            BlockRef::ToolResult { content: Some(c) } => c.get().to_lowercase().contains(&path_lower),
+            _ => false,
+        })
+    }
+
+    /// Same as [`MessageRecord::thinking_content`].
+    pub fn thinking_content(&self) -> String {
+        let parts: Vec<&str> = self
+            .blocks()
+            .iter()
+            .filter_map(|b| match b {
+                BlockRef::Thinking { thinking } => Some(thinking.as_ref()),
+                _ => None,
+            })
+            .collect();
+        parts.join("\n")
+    }
+
+    /// Same as [`MessageRecord::text_no_thinking`].
+    pub fn text_no_thinking(&self) -> String {
+        if let Some(s) = self.plain_text() {
+            return s.to_string();
+        }
+        let parts: Vec<&str> = self
+            .blocks()
+            .iter()
+            .filter_map(|b| match b {
+                BlockRef::Text { text } => Some(text.as_ref()),
+                _ => None,
+            })
+            .collect();
+        parts.join("\n")
+    }
+
+    /// Same as [`MessageRecord::tool_input_content`].
+    pub fn tool_input_content(&self) -> String {
+        let parts: Vec<String> = self
+            .blocks()
+            .iter()
+            .filter_map(|b| match b {
+                BlockRef::ToolUse { name, input } => Some(format!("[{}] {}", name, input)),
+                _ => None,
+            })
+            .collect();
+        parts.join("\n")
+    }
+
+    /// Same as [`MessageRecord::full_content`].
+    pub fn full_content(&self) -> String {
+        if let Some(s) = self.plain_text() {
+            return s.to_string();
+        }
+        let mut parts = Vec::new();
+        for block in self.blocks() {
+            match block {
+                BlockRef::Text { text } => parts.push(text.to_string()),
+                BlockRef::Thinking { thinking } => parts.push(thinking.to_string()),
+                BlockRef::ToolUse { name, input } => parts.push(format!("[tool: {}] {}", name, input)),
+                BlockRef::ToolResult { content: Some(c) } => parts.push(format!("[result] {}", c)),
+                _ => {}
+            }
+        }
+        parts.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_ref_borrows_plain_strings() {
+        let line = r#"{"type":"user","uuid":"x","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"hello"}}"#;
+        let r: RecordRef = serde_json::from_str(line).unwrap();
+        assert_eq!(r.role(), Some("user"));
+        assert!(matches!(r.record_type, Cow::Borrowed(_)));
+        assert_eq!(r.full_content(), "hello");
+    }
+
+    #[test]
+    fn record_ref_matches_owned_extraction() {
+        let line = r#"{"type":"assistant","message":{"role":"assistant","content":[
+            {"type":"thinking","thinking":"hmm \"quoted\""},
+            {"type":"text","text":"ok"},
+            {"type":"tool_use","id":"t","name":"Read","input":{"file_path":"/a/b.rs"}}]}}"#;
+        let owned: Record = serde_json::from_str(line).unwrap();
+        let owned = owned.as_message().unwrap();
+        let r: RecordRef = serde_json::from_str(line).unwrap();
+        assert_eq!(r.full_content(), owned.full_content());
+        assert_eq!(r.text_no_thinking(), owned.text_no_thinking());
+        assert_eq!(r.thinking_content(), owned.thinking_content());
+        assert_eq!(r.tool_input_content(), owned.tool_input_content());
+        assert_eq!(r.tool_names(), owned.tool_names());
+        assert!(r.touches_file("B.RS"));
+    }
+
+    #[test]
+    fn record_ref_keeps_tool_json_raw() {
+        let line = r#"{"type":"assistant","message":{"role":"assistant","content":[
+            {"type":"tool_use","id":"t","name":"Edit","input":{"old_string":"a\nb","file_path":"/x.rs"}},
+            {"type":"tool_result","tool_use_id":"t","content":[{"type":"text","text":"done"}]},
+            {"type":"image","source":{}}]}}"#;
+        let r: RecordRef = serde_json::from_str(line).unwrap();
+        let blocks = r.blocks();
+        let BlockRef::ToolUse { input, .. } = &blocks[0] else { panic!("{:?}", blocks[0]) };
+        assert_eq!(input.get(), r#"{"old_string":"a\nb","file_path":"/x.rs"}"#);
+        assert!(matches!(blocks[1], BlockRef::ToolResult { content: Some(c) } if c.get().starts_with(r#"[{"type""#)));
+        assert!(matches!(blocks[2], BlockRef::Other));
+        assert_eq!(r.tool_input_content(), r#"[Edit] {"old_string":"a\nb","file_path":"/x.rs"}"#);
+    }
+
+    fn user(content: &str) -> MessageRecord {
+        let line = format!(r#"{{"type":"user","message":{{"role":"user","content":{}}}}}"#, content);
+        match serde_json::from_str::<Record>(&line).unwrap() {
+            Record::User(m) => m,
+            other => panic!("expected user record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn classifies_user_messages() {
+        assert_eq!(user(r#""fix the build""#).user_kind(), Some(UserKind::Human));
+        let cmd = user(r#""<command-name>/clear</command-name>\n<command-args></command-args>""#);
+        assert_eq!(cmd.user_kind(), Some(UserKind::SlashCommand));
+        assert_eq!(cmd.command_name().as_deref(), Some("/clear"));
+        let out = user(r#""<local-command-stdout>ok</local-command-stdout>""#);
+        assert_eq!(out.user_kind(), Some(UserKind::SlashCommand));
+        let hook = user(r#""<system-reminder>be nice</system-reminder>""#);
+        assert_eq!(hook.user_kind(), Some(UserKind::HookInjected));
+        let tr = user(r#"[{"type":"tool_result","tool_use_id":"t","content":"x"}]"#);
+        assert_eq!(tr.user_kind(), Some(UserKind::ToolResult));
+    }
+
+    #[test]
+    fn typed_text_mentioning_reminder_is_human() {
+        let m = user(r#""what does <system-reminder> mean?""#);
+        assert_eq!(m.user_kind(), Some(UserKind::Human));
+    }
+
+    #[test]
+    fn typed_text_mentioning_command_tags_is_human() {
+        let m = user(r#""why is <command-name>/clear</command-name> in my transcript?""#);
+        assert_eq!(m.user_kind(), Some(UserKind::Human));
+        let m = user(r#""grep for <local-command-stdout> please""#);
+        assert_eq!(m.user_kind(), Some(UserKind::Human));
+        let cmd = user(r#""<command-message>init is analyzing</command-message>\n<command-name>/init</command-name>""#);
+        assert_eq!(cmd.user_kind(), Some(UserKind::SlashCommand));
+    }
+
+    #[test]
+    fn parses_api_error_as_own_variant() {
+        let line = r#"{"type":"system","subtype":"api_error","level":"error","timestamp":"2025-05-01T10:00:00Z",
+            "error":{"status":529,"error":{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}},
+            "retryInMs":1200.5,"retryAttempt":2,"maxRetries":10}"#;
+        let Record::ApiError(e) = serde_json::from_str::<Record>(line).unwrap() else {
+            panic!("expected ApiError");
+        };
+        assert_eq!(e.kind(), "overloaded_error");
+        assert_eq!(e.status(), Some(529));
+        assert_eq!(e.message().as_deref(), Some("Overloaded"));
+        assert_eq!(e.retry_attempt, Some(2));
+    }
+
+    #[test]
+    fn system_messages_still_parse() {
+        let line = r#"{"type":"system","message":{"role":"system","content":"hi"}}"#;
+        assert!(matches!(serde_json::from_str::<Record>(line).unwrap(), Record::System(_)));
+    }
+
+    #[test]
+    fn connection_error_kind_from_cause() {
+        let line = r#"{"type":"system","subtype":"api_error","error":{},"cause":{"code":"ECONNRESET"}}"#;
+        let Record::ApiError(e) = serde_json::from_str::<Record>(line).unwrap() else {
+            panic!("expected ApiError");
+        };
+        assert_eq!(e.kind(), "ECONNRESET");
+    }
+
+    #[test]
+    fn usage_tolerates_nulls() {
+        let line = r#"{"type":"assistant","message":{"role":"assistant","model":"claude-x","content":[],
+            "usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":null}}}"#;
+        let Record::Assistant(m) = serde_json::from_str::<Record>(line).unwrap() else {
+            panic!("expected assistant");
+        };
+        let usage = m.message.usage.unwrap();
+        assert_eq!(usage.total(), 15);
+        assert_eq!(m.message.model.as_deref(), Some("claude-x"));
+    }
+
+    #[test]
+    fn usage_counted_once_per_response() {
+        // One response split over a thinking line and a tool_use line, then another response.
+        let lines = [
+            r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant","content":[{"type":"thinking","thinking":"hm"}],"usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant","content":[{"type":"tool_use","name":"Bash","input":{}}],"usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            r#"{"type":"assistant","message":{"id":"msg_2","role":"assistant","content":"ok","usage":{"input_tokens":1,"output_tokens":1}}}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":"old","usage":{"input_tokens":2}}}"#,
+        ];
+        let mut dedup = UsageDedup::default();
+        let mut total = Usage::default();
+        for line in lines {
+            let record = serde_json::from_str::<Record>(line).unwrap();
+            if let Some(u) = dedup.first(record.as_message().unwrap()) {
+                total.add(u);
+            }
+        }
+        assert_eq!((total.input_tokens, total.output_tokens, total.total()), (13, 6, 19));
+    }
+
+    #[test]
+    fn record_ref_non_message() {
+        let r: RecordRef = serde_json::from_str(r#"{"type":"progress","data":{}}"#).unwrap();
+        assert_eq!(r.role(), None);
+        assert!(r.message.is_none());
+    }
+
+    #[test]
+    fn role_filter_lists_aliases_and_any_human() {
+        let f = RoleFilter::parse("user, asst").unwrap();
+        assert!(f.matches("user", true) && f.matches("assistant", false));
+        assert!(!f.matches("system", false));
+        let human = RoleFilter::parse("any-human").unwrap();
+        assert!(human.matches("user", false));
+        assert!(!human.matches("user", true) && !human.matches("assistant", false));
+        assert!(RoleFilter::parse("bot").is_err());
+    }
+
+    #[test]
+    fn tool_filter_exact_unless_tilde() {
+        let f = ToolFilter::parse("bash,~mcp__").unwrap();
+        assert!(f.matches("Bash") && f.matches("mcp__github__get_issue"));
+        assert!(!f.matches("BashOutput"));
+        assert!(ToolFilter::parse("~Bash").unwrap().matches("BashOutput"));
+        assert!(ToolFilter::parse("Bash,").is_err());
+        assert!(ToolFilter::parse("~").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Async variants of discovery and search, for tokio-based consumers.
+//!
+//! Directory walking uses `tokio::fs`; JSONL parsing and search are CPU-bound
+//! (search fans out over rayon), so they run under `spawn_blocking` and never
+//! stall the caller's executor; they take owned arguments so the futures are
+//! `Send + 'static` and can be spawned directly.
+//!
+//! Enabled by the `tokio` cargo feature.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::cmd::{self, search::SearchOpts};
+use crate::models::Record;
+use crate::output::Emitter;
+use crate::util::discover::{self, SessionFile};
+
+/// Async [`discover::claude_dir`].
+pub async fn claude_dir(path_override: Option<&str>) -> Result<PathBuf> {
+    let dir = discover::claude_dir_path(path_override);
+    let exists = tokio::fs::try_exists(&dir).await.unwrap_or(false);
+    anyhow::ensure!(exists, "Claude projects directory not found at {}", dir.display());
+    Ok(dir)
+}
+
+/// Async [`discover::discover_jsonl_files`]: same files, same largest-first order.
+pub async fn discover_jsonl_files(base: impl AsRef<Path>) -> Result<Vec<SessionFile>> {
+    let base = base.as_ref();
+    let mut files = Vec::new();
+    if !tokio::fs::metadata(base).await.is_ok_and(|m| m.is_dir()) {
+        return Ok(files);
+    }
+
+    let mut projects = tokio::fs::read_dir(base).await?;
+    while let Some(entry) = projects.next_entry().await? {
+        if !entry.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut sessions = tokio::fs::read_dir(entry.path()).await?;
+        while let Some(file_entry) = sessions.next_entry().await? {
+            let metadata = file_entry.metadata().await?;
+            if !metadata.is_file() {
+                continue;
+            }
+            if let Some(mut file) = discover::session_file(&file_entry.path()) {
+                file.size_bytes = metadata.len();
+                files.push(file);
+            }
+        }
+    }
+
+    files.sort_by_key(|f| std::cmp::Reverse(f.size_bytes));
+    Ok(files)
+}
+
+/// Async [`cmd::parse_records`].
+pub async fn parse_records(file: SessionFile) -> Result<Vec<Record>> {
+    tokio::task::spawn_blocking(move || cmd::parse_records(&file))
+        .await
+        .context("parse task panicked")?
+}
+
+/// Run [`cmd::search::run`] off the executor and collect its output records
+/// (hits, then the trailing `summary`).
+pub async fn search(opts: SearchOpts, files: Vec<SessionFile>) -> Result<Vec<serde_json::Value>> {
+    tokio::task::spawn_blocking(move || {
+        let mut em = Emitter::capturing(opts.max_tokens);
+        cmd::search::run(&opts, &files, &mut em)?;
+        Ok(em.into_records())
+    })
+    .await
+    .context("search task panicked")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn matches_blocking_discovery() {
+        let dir = TempDir::new("nonblocking");
+        let proj = dir.join("-Users-me-GitHub-alpha");
+        std::fs::create_dir_all(&proj).unwrap();
+        let rec = "{\"type\":\"summary\"}\n";
+        std::fs::write(proj.join("a.jsonl"), rec).unwrap();
+        std::fs::write(proj.join("b.jsonl"), rec.repeat(2)).unwrap();
+        std::fs::write(proj.join("notes.txt"), "").unwrap();
+        std::fs::write(dir.join("stray.jsonl"), "").unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let got = rt.block_on(discover_jsonl_files(&dir)).unwrap();
+        let want = discover::discover_jsonl_files(&dir).unwrap();
+        let key = |f: &SessionFile| (f.session_id.clone(), f.project_name.clone(), f.size_bytes);
+        assert_eq!(got.iter().map(key).collect::<Vec<_>>(), want.iter().map(key).collect::<Vec<_>>());
+        assert_eq!(got[0].session_id, "b");
+
+        let records = rt.block_on(parse_records(got[0].clone())).unwrap();
+        assert_eq!(records.len(), 2);
+    }
+}
@@ -0,0 +1,423 @@
+//! Typed access to sessions for library callers.
+//!
+//! [`list`] and [`load`] return plain structs; `smc sessions`, `smc show`,
+//! and `smc tools` are thin printers over them.
+
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::{ContentBlock, MessageContent, MessageRecord, Record, Timestamp};
+use crate::util::cache::{self, SummaryCache};
+use crate::util::discover::SessionFile;
+use crate::util::time;
+
+// ── list ───────────────────────────────────────────────────────────────────
+
+/// Which sessions [`list`] returns.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ListOpts {
+    /// Project name substring (case-insensitive).
+    pub project: Option<String>,
+    /// Only sessions starting at or after this date (`YYYY-MM-DD` / RFC 3339).
+    pub after: Option<String>,
+    /// Only sessions starting before this date.
+    pub before: Option<String>,
+    /// Summary cache file (`None` = parse every file).
+    pub cache: Option<PathBuf>,
+}
+
+/// One row of the session list.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub project: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Timestamp of the first message.
+    pub timestamp: Option<Timestamp>,
+    /// Timestamp of the last message.
+    pub last_timestamp: Option<Timestamp>,
+    /// First user message, truncated to 120 chars.
+    pub preview: Option<String>,
+    /// Last prompt the user typed, truncated to 120 chars.
+    pub last_prompt: Option<String>,
+    /// Last assistant text reply to `last_prompt`, truncated to 120 chars.
+    pub last_reply: Option<String>,
+    pub msg_count: u32,
+}
+
+impl SessionSummary {
+    /// Seconds from the first message to the last, telling a quick question
+    /// from an all-day session.
+    pub fn duration_secs(&self) -> Option<u64> {
+        let first = self.timestamp.as_ref()?.utc?;
+        let last = self.last_timestamp.as_ref()?.utc?;
+        Some((last - first).num_seconds().max(0) as u64)
+    }
+}
+
+/// Sessions matching `opts`, newest first.
+pub fn list(files: &[SessionFile], opts: &ListOpts) -> Result<Vec<SessionSummary>> {
+    let (after, before) = time::parse_range(opts.after.as_deref(), opts.before.as_deref())?;
+
+    let filtered: Vec<&SessionFile> = files
+        .iter()
+        .filter(|f| match &opts.project {
+            Some(proj) => f.project_name.to_lowercase().contains(&proj.to_lowercase()),
+            None => true,
+        })
+        .collect();
+
+    let cache = opts.cache.as_ref().map(SummaryCache::open);
+    let summaries = cache::summaries(&filtered, cache.as_ref());
+    if let Some(c) = &cache {
+        c.save()?;
+    }
+
+    let mut out: Vec<SessionSummary> = filtered
+        .iter()
+        .zip(summaries)
+        .filter(|(_, s)| time::in_range(s.first_timestamp.as_ref().and_then(|t| t.utc), after, before))
+        .map(|(f, s)| SessionSummary {
+            session_id: f.session_id.clone(),
+            project: f.project_name.clone(),
+            path: f.path.clone(),
+            size_bytes: f.size_bytes,
+            timestamp: s.first_timestamp,
+            last_timestamp: s.last_timestamp,
+            preview: s.preview,
+            last_prompt: s.last_prompt,
+            last_reply: s.last_reply,
+            msg_count: s.msg_count,
+        })
+        .collect();
+    out.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(out)
+}
+
+/// The session written to most recently, optionally within a project
+/// (substring, case-insensitive). Uses file mtime, so a resumed old session
+/// counts as active and no file has to be parsed.
+pub fn last_active<'a>(files: &'a [SessionFile], project: Option<&str>) -> Option<&'a SessionFile> {
+    let project = project.map(str::to_lowercase);
+    files
+        .iter()
+        .filter(|f| project.as_ref().map_or(true, |p| f.project_name.to_lowercase().contains(p)))
+        .max_by_key(|f| std::fs::metadata(&f.path).and_then(|m| m.modified()).ok())
+}
+
+// ── chain ──────────────────────────────────────────────────────────────────
+
+/// The sessions `file` belongs to a resume chain with, itself included,
+/// oldest first.
+///
+/// `claude --resume` starts a new file that repeats the earlier messages
+/// under their original uuids, so sessions of the same project that share a
+/// message uuid — directly or through another session — form one chain.
+pub fn chain<'a>(files: &'a [SessionFile], file: &SessionFile) -> Vec<&'a SessionFile> {
+    let candidates: Vec<&SessionFile> = files.iter().filter(|f| f.project_name == file.project_name).collect();
+    let scanned: Vec<(Option<DateTime<Utc>>, HashSet<String>)> = candidates.par_iter().map(|f| scan_ids(f)).collect();
+
+    let mut member: Vec<bool> = candidates.iter().map(|f| f.path == file.path).collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+    // Grow until no remaining candidate shares a uuid with the chain so far.
+    loop {
+        for (i, (_, ids)) in scanned.iter().enumerate() {
+            if member[i] {
+                seen.extend(ids.iter().map(String::as_str));
+            }
+        }
+        let joined: Vec<usize> = (0..candidates.len())
+            .filter(|&i| !member[i] && scanned[i].1.iter().any(|id| seen.contains(id.as_str())))
+            .collect();
+        if joined.is_empty() {
+            break;
+        }
+        for i in joined {
+            member[i] = true;
+        }
+    }
+
+    let mut out: Vec<(Option<DateTime<Utc>>, &SessionFile)> =
+        (0..candidates.len()).filter(|&i| member[i]).map(|i| (scanned[i].0, candidates[i])).collect();
+    out.sort_by_key(|(t, _)| *t);
+    out.into_iter().map(|(_, f)| f).collect()
+}
+
+/// First message time and every message uuid of `file`.
+fn scan_ids(file: &SessionFile) -> (Option<DateTime<Utc>>, HashSet<String>) {
+    #[derive(Deserialize)]
+    struct Ids {
+        uuid: Option<String>,
+        timestamp: Option<Timestamp>,
+    }
+
+    let mut first = None;
+    let mut ids = HashSet::new();
+    let Ok(f) = std::fs::File::open(&file.path) else { return (first, ids) };
+    for line in std::io::BufReader::new(f).lines() {
+        let Ok(line) = line else { continue };
+        let Ok(rec) = serde_json::from_str::<Ids>(&line) else { continue };
+        first = first.or_else(|| rec.timestamp.and_then(|t| t.utc));
+        ids.extend(rec.uuid);
+    }
+    (first, ids)
+}
+
+// ── load ───────────────────────────────────────────────────────────────────
+
+/// A whole conversation: its messages in order plus metadata.
+#[derive(Debug)]
+pub struct Session {
+    pub session_id: String,
+    pub project: String,
+    /// Working directory, from the first message that records one.
+    pub cwd: Option<String>,
+    /// Git branch, from the first message that records one.
+    pub git_branch: Option<String>,
+    /// Claude Code version, from the first message that records one.
+    pub version: Option<String>,
+    pub messages: Vec<Message>,
+}
+
+/// A user, assistant, or system message.
+#[derive(Debug)]
+pub struct Message {
+    /// Position among the session's messages (what `smc show --from` counts).
+    pub index: usize,
+    pub role: &'static str,
+    pub record: MessageRecord,
+}
+
+/// A `tool_use` block inside a message.
+#[derive(Debug, Clone, Copy)]
+pub struct ToolCall<'a> {
+    /// Index of the message it appears in.
+    pub message: usize,
+    pub role: &'static str,
+    pub timestamp: Option<&'a Timestamp>,
+    pub name: &'a str,
+    pub input: &'a Value,
+}
+
+/// Parse every message of `file`.
+pub fn load(file: &SessionFile) -> Result<Session> {
+    let mut session = Session {
+        session_id: file.session_id.clone(),
+        project: file.project_name.clone(),
+        cwd: None,
+        git_branch: None,
+        version: None,
+        messages: Vec::new(),
+    };
+
+    for record in crate::cmd::parse_records(file)? {
+        let role = record.role();
+        let (Record::User(m) | Record::Assistant(m) | Record::System(m)) = record else { continue };
+        session.cwd = session.cwd.or_else(|| m.cwd.clone());
+        session.git_branch = session.git_branch.or_else(|| m.git_branch.clone());
+        session.version = session.version.or_else(|| m.version.clone());
+        session.messages.push(Message { index: session.messages.len(), role, record: m });
+    }
+    Ok(session)
+}
+
+/// Tools whose call runs a sub-agent.
+const TASK_TOOLS: [&str; 2] = ["Task", "Agent"];
+
+impl Session {
+    pub fn first_timestamp(&self) -> Option<&Timestamp> {
+        self.messages.iter().find_map(|m| m.record.timestamp.as_ref())
+    }
+
+    pub fn last_timestamp(&self) -> Option<&Timestamp> {
+        self.messages.iter().rev().find_map(|m| m.record.timestamp.as_ref())
+    }
+
+    /// Every tool call, in order.
+    pub fn tool_calls(&self) -> impl Iterator<Item = ToolCall<'_>> {
+        self.messages.iter().flat_map(|m| {
+            let blocks = match &m.record.message.content {
+                MessageContent::Blocks(b) => b.as_slice(),
+                MessageContent::Text(_) => &[],
+            };
+            blocks.iter().filter_map(move |b| match b {
+                ContentBlock::ToolUse { name, input, .. } => Some(ToolCall {
+                    message: m.index,
+                    role: m.role,
+                    timestamp: m.record.timestamp.as_ref(),
+                    name,
+                    input,
+                }),
+                _ => None,
+            })
+        })
+    }
+
+    /// The `Task` call that spawned each sidechain (sub-agent) message: the
+    /// `tool_use` id, keyed by message index. A sub-agent's first message is
+    /// matched to the pending `Task` call whose prompt it repeats, else to the
+    /// oldest pending call without a sub-agent; its later messages follow
+    /// `parentUuid`. Sidechain messages with no pending call are left out.
+    pub fn spawned_by(&self) -> HashMap<usize, String> {
+        let mut out = HashMap::new();
+        // (tool_use id, prompt, sub-agent seen) of calls still waiting for a result.
+        let mut pending: Vec<(&str, &str, bool)> = Vec::new();
+        let mut chains: HashMap<&str, &str> = HashMap::new();
+        for m in &self.messages {
+            if m.record.is_sidechain {
+                let parent = m.record.parent_uuid.as_ref().and_then(Value::as_str);
+                let task = match parent.and_then(|p| chains.get(p)) {
+                    Some(task) => Some(*task),
+                    None => {
+                        let text = m.record.text_no_thinking();
+                        let slot = pending
+                            .iter()
+                            .position(|p| !p.2 && p.1 == text.trim())
+                            .or_else(|| pending.iter().position(|p| !p.2));
+                        slot.map(|i| {
+                            pending[i].2 = true;
+                            pending[i].0
+                        })
+                    }
+                };
+                if let Some(task) = task {
+                    if let Some(uuid) = &m.record.uuid {
+                        chains.insert(uuid, task);
+                    }
+                    out.insert(m.index, task.to_string());
+                }
+                continue;
+            }
+            let MessageContent::Blocks(blocks) = &m.record.message.content else { continue };
+            for block in blocks {
+                match block {
+                    ContentBlock::ToolUse { id: Some(id), name, input } if TASK_TOOLS.contains(&name.as_str()) => {
+                        pending.push((id, input.get("prompt").and_then(Value::as_str).unwrap_or("").trim(), false));
+                    }
+                    ContentBlock::ToolResult { tool_use_id: Some(id), .. } => pending.retain(|p| p.0 != id),
+                    _ => {}
+                }
+            }
+        }
+        out
+    }
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn loads_messages_metadata_and_tools() {
+        let dir = TempDir::new("session");
+        let file = dir.session(
+            "s1",
+            "alpha",
+            concat!(
+                r#"{"type":"summary"}"#, "\n",
+                r#"{"type":"user","cwd":"/w","gitBranch":"main","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"hi"}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:05:00Z","message":{"role":"assistant","content":[{"type":"text","text":"ok"},{"type":"tool_use","name":"Bash","input":{"command":"ls"}}]}}"#, "\n",
+            ),
+        );
+        let s = load(&file).unwrap();
+        assert_eq!(s.messages.len(), 2);
+        assert_eq!((s.messages[1].index, s.messages[1].role), (1, "assistant"));
+        assert_eq!((s.cwd.as_deref(), s.git_branch.as_deref()), (Some("/w"), Some("main")));
+        assert_eq!(s.last_timestamp().unwrap().as_str(), "2025-01-01T10:05:00Z");
+        let tools: Vec<_> = s.tool_calls().collect();
+        assert_eq!(tools.len(), 1);
+        assert_eq!((tools[0].name, tools[0].message), ("Bash", 1));
+        assert_eq!(tools[0].input["command"], "ls");
+    }
+
+    #[test]
+    fn summary_spans_first_to_last_message() {
+        let dir = TempDir::new("session");
+        let file = dir.session(
+            "long",
+            "alpha",
+            concat!(
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"start"}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:00:30Z","message":{"role":"assistant","content":[{"type":"text","text":"on it"}]}}"#, "\n",
+                r#"{"type":"user","timestamp":"2025-01-01T11:30:00Z","message":{"role":"user","content":"and now?"}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-01T11:32:05Z","message":{"role":"assistant","content":[{"type":"text","text":"done"}]}}"#, "\n",
+            ),
+        );
+        let s = &list(&[file], &ListOpts::default()).unwrap()[0];
+        assert_eq!(s.msg_count, 4);
+        assert_eq!(s.duration_secs(), Some(92 * 60 + 5));
+        assert_eq!((s.last_prompt.as_deref(), s.last_reply.as_deref()), (Some("and now?"), Some("done")));
+    }
+
+    #[test]
+    fn sidechains_follow_their_task_call() {
+        let task = |id: &str, prompt: &str| {
+            format!(
+                r#"{{"type":"assistant","uuid":"a-{0}","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"{0}","name":"Task","input":{{"prompt":"{1}"}}}}]}}}}"#,
+                id, prompt
+            ) + "\n"
+        };
+        let side = |uuid: &str, parent: &str, text: &str| {
+            format!(
+                r#"{{"type":"user","isSidechain":true,"uuid":"{}","parentUuid":{},"message":{{"role":"user","content":"{}"}}}}"#,
+                uuid, parent, text
+            ) + "\n"
+        };
+        let body = task("t1", "find the bug")
+            + &task("t2", "write docs")
+            + &side("s1", "null", "write docs")
+            + &side("s2", "null", "find the bug")
+            + &side("s3", r#""s1""#, "more docs")
+            + r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#
+            + "\n"
+            + &side("s4", "null", "stray");
+        let dir = TempDir::new("session");
+        let file = dir.session("side", "alpha", &body);
+        let s = load(&file).unwrap();
+        assert!(s.messages[2].record.is_sidechain);
+        let spawned = s.spawned_by();
+        assert_eq!((spawned[&2].as_str(), spawned[&3].as_str(), spawned[&4].as_str()), ("t2", "t1", "t2"));
+        assert!(!spawned.contains_key(&6));
+    }
+
+    #[test]
+    fn lists_with_filters() {
+        let dir = TempDir::new("session");
+        let old = dir.session("old", "alpha", r#"{"type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"role":"user","content":"a"}}"#);
+        let new = dir.session("new", "alpha", r#"{"type":"user","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"b"}}"#);
+        let files = vec![old, new];
+
+        let all = list(&files, &ListOpts::default()).unwrap();
+        assert_eq!(all.iter().map(|s| s.session_id.as_str()).collect::<Vec<_>>(), ["new", "old"]);
+        assert_eq!(all[0].preview.as_deref(), Some("b"));
+        assert_eq!(all[0].duration_secs(), Some(0));
+
+        let opts = ListOpts { after: Some("2024-06-01".into()), ..Default::default() };
+        assert_eq!(list(&files, &opts).unwrap().len(), 1);
+        let opts = ListOpts { project: Some("beta".into()), ..Default::default() };
+        assert!(list(&files, &opts).unwrap().is_empty());
+
+        // Date "old" after "new" so it becomes the most recently active.
+        let touch = |f: &SessionFile, secs| {
+            let at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+            std::fs::File::options().write(true).open(&f.path).unwrap().set_modified(at).unwrap();
+        };
+        touch(&files[1], 1_700_000_000);
+        touch(&files[0], 1_800_000_000);
+        assert_eq!(last_active(&files, None).unwrap().session_id, "old");
+        assert!(last_active(&files, Some("beta")).is_none());
+    }
+}
@@ -0,0 +1,242 @@
+//! Binary cache of per-session summaries (`~/.smc/cache/summaries.bin`).
+//!
+//! Parsing JSON dominates repeated `sessions` / `freq` runs, yet what those
+//! commands need from a file is small: first and last timestamps, first and
+//! last prompts, counts by role and tool, letter frequencies, token totals.
+//! A [`FileSummary`] holds exactly that, stored with postcard and keyed by
+//! path plus size and mtime, so only new or changed files are re-parsed. A
+//! missing, stale, or unreadable cache just means a full parse.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Record, Timestamp, UsageDedup, UserKind};
+use crate::util::discover::SessionFile;
+use crate::util::policy;
+use crate::util::state;
+
+/// Bump whenever [`FileSummary`] changes shape or meaning.
+const VERSION: u32 = 4;
+
+/// Max chars of the first-prompt preview.
+const PREVIEW_CHARS: usize = 120;
+
+// ── Summary ────────────────────────────────────────────────────────────────
+
+/// Everything the cached commands extract from one session file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FileSummary {
+    /// Timestamp of the first message.
+    pub first_timestamp: Option<Timestamp>,
+    /// Timestamp of the last message.
+    pub last_timestamp: Option<Timestamp>,
+    /// First user message, truncated.
+    pub preview: Option<String>,
+    /// Last prompt typed by the user (not a tool result or command), truncated.
+    pub last_prompt: Option<String>,
+    /// Last assistant text after `last_prompt`, truncated.
+    pub last_reply: Option<String>,
+    /// Messages (user, assistant, system).
+    pub msg_count: u32,
+    /// Messages by role; this and the counts below leave out smc's own
+    /// output (see [`policy`]).
+    pub roles: BTreeMap<String, u64>,
+    /// Tool calls by tool name.
+    pub tools: BTreeMap<String, u64>,
+    /// ASCII letter counts (case-folded) over message text, `a` first.
+    pub letters: Vec<u64>,
+    /// Chars of message text.
+    pub text_chars: u64,
+    /// API-reported tokens (input + output + cache).
+    pub tokens: u64,
+}
+
+/// Parse `file` in full and summarize it.
+pub fn summarize(file: &SessionFile) -> FileSummary {
+    let mut s = FileSummary { letters: vec![0; 26], ..Default::default() };
+    let Ok(f) = std::fs::File::open(&file.path) else { return s };
+    let reader = std::io::BufReader::with_capacity(256 * 1024, f);
+
+    let mut dedup = UsageDedup::default();
+    for line in reader.lines() {
+        let Ok(line) = line else { continue };
+        let Ok(record) = serde_json::from_str::<Record>(&line) else { continue };
+        let Some(msg) = record.as_message() else { continue };
+
+        s.msg_count += 1;
+        if msg.timestamp.is_some() {
+            if s.first_timestamp.is_none() {
+                s.first_timestamp = msg.timestamp.clone();
+            }
+            s.last_timestamp = msg.timestamp.clone();
+        }
+        if let Some(usage) = dedup.first(msg) {
+            s.tokens += usage.total();
+        }
+        // Role, tool, and text statistics follow the default self-output policy.
+        let own = policy::is_smc_output(&line);
+        if !own {
+            *s.roles.entry(record.role().to_string()).or_default() += 1;
+            for tool in msg.tool_names() {
+                *s.tools.entry(tool.to_string()).or_default() += 1;
+            }
+        }
+
+        let text = msg.text_content();
+        if s.preview.is_none() && matches!(record, Record::User(_)) {
+            s.preview = Some(text.chars().take(PREVIEW_CHARS).collect());
+        }
+        if msg.user_kind() == Some(UserKind::Human) {
+            s.last_prompt = Some(text.chars().take(PREVIEW_CHARS).collect());
+            s.last_reply = None;
+        } else if matches!(record, Record::Assistant(_)) && !text.trim().is_empty() {
+            s.last_reply = Some(text.chars().take(PREVIEW_CHARS).collect());
+        }
+        if own {
+            continue;
+        }
+        s.text_chars += text.chars().count() as u64;
+        for b in text.bytes() {
+            if b.is_ascii_alphabetic() {
+                s.letters[(b.to_ascii_lowercase() - b'a') as usize] += 1;
+            }
+        }
+    }
+    s
+}
+
+// ── Cache ──────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct CacheFile {
+    version: u32,
+    entries: HashMap<String, Entry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    size: u64,
+    mtime: u64,
+    summary: FileSummary,
+}
+
+/// Summaries keyed by file path, loaded once and written back by [`save`](Self::save).
+pub struct SummaryCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, Entry>>,
+    dirty: AtomicBool,
+}
+
+impl SummaryCache {
+    /// `~/.smc/cache/summaries.bin`.
+    pub fn default_path() -> PathBuf {
+        state::smc_home_path().join("cache").join("summaries.bin")
+    }
+
+    /// Load the cache at `path`; anything unreadable starts it empty.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| postcard::from_bytes::<CacheFile>(&bytes).ok())
+            .filter(|c| c.version == VERSION)
+            .map(|c| c.entries)
+            .unwrap_or_default();
+        Self { path, entries: Mutex::new(entries), dirty: AtomicBool::new(false) }
+    }
+
+    /// The cached summary if `file` is unchanged, else a fresh parse (which is cached).
+    pub fn get(&self, file: &SessionFile) -> FileSummary {
+        let key = file.path.display().to_string();
+        let mtime = state::mtime_secs(&file.path);
+        if let Some(e) = self.entries.lock().unwrap().get(&key) {
+            if e.size == file.size_bytes && e.mtime == mtime {
+                return e.summary.clone();
+            }
+        }
+        let summary = summarize(file);
+        let entry = Entry { size: file.size_bytes, mtime, summary: summary.clone() };
+        self.entries.lock().unwrap().insert(key, entry);
+        self.dirty.store(true, Ordering::Relaxed);
+        summary
+    }
+
+    /// Write the cache back if anything was re-parsed, dropping entries for
+    /// files that no longer exist.
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.retain(|path, _| Path::new(path).exists());
+        let bytes = postcard::to_stdvec(&CacheFile { version: VERSION, entries })?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+        }
+        // Write-then-rename so a concurrent reader never sees a torn file.
+        let tmp = self.path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, bytes).with_context(|| format!("cannot write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path).with_context(|| format!("cannot write {}", self.path.display()))
+    }
+}
+
+/// Summaries for `files` in parallel, through `cache` when given.
+pub fn summaries(files: &[&SessionFile], cache: Option<&SummaryCache>) -> Vec<FileSummary> {
+    files
+        .par_iter()
+        .map(|f| match cache {
+            Some(c) => c.get(f),
+            None => summarize(f),
+        })
+        .collect()
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn summarizes_and_round_trips() {
+        let dir = TempDir::new("cache");
+        let file = dir.session(
+            "s1",
+            "p",
+            concat!(
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"Hi AB"}}"#, "\n",
+                // One response written as two lines, each repeating its usage.
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:01:00Z","message":{"id":"msg_1","role":"assistant","content":[{"type":"text","text":""}],"usage":{"input_tokens":3,"output_tokens":4}}}"#, "\n",
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:01:00Z","message":{"id":"msg_1","role":"assistant","content":[{"type":"tool_use","name":"Bash","input":{}}],"usage":{"input_tokens":3,"output_tokens":4}}}"#, "\n",
+                "not json\n",
+            ),
+        );
+
+        let s = summarize(&file);
+        assert_eq!(s.msg_count, 3);
+        assert_eq!(s.preview.as_deref(), Some("Hi AB"));
+        assert_eq!(s.roles.get("assistant"), Some(&2));
+        assert_eq!(s.tools.get("Bash"), Some(&1));
+        assert_eq!((s.letters[0], s.letters[7]), (1, 1));
+        assert_eq!(s.tokens, 7);
+        assert_eq!(s.last_timestamp.as_ref().unwrap().as_str(), "2025-01-01T10:01:00Z");
+        assert_eq!((s.last_prompt.as_deref(), s.last_reply.as_deref()), (Some("Hi AB"), None));
+
+        let cache_path = dir.join("cache.bin");
+        let cache = SummaryCache::open(&cache_path);
+        assert_eq!(cache.get(&file), s);
+        cache.save().unwrap();
+
+        // Reloaded from disk.
+        let reopened = SummaryCache::open(&cache_path);
+        assert_eq!(reopened.entries.lock().unwrap().get(&file.path.display().to_string()).unwrap().summary, s);
+    }
+}
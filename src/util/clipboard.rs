@@ -0,0 +1,50 @@
+//! System clipboard via the platform's stock command-line tools.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+/// Candidate tools in preference order: `(program, args)`.
+fn candidates() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip", &[])]
+    } else {
+        let mut v: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            v.push(("wl-copy", &[]));
+        }
+        v.push(("xclip", &["-selection", "clipboard"]));
+        v.push(("xsel", &["--clipboard", "--input"]));
+        // WSL
+        v.push(("clip.exe", &[]));
+        v
+    }
+}
+
+/// Put `text` on the clipboard; returns the name of the tool that took it.
+pub fn copy(text: &str) -> Result<&'static str> {
+    for (program, args) in candidates() {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else { continue };
+
+        child
+            .stdin
+            .take()
+            .context("clipboard stdin unavailable")?
+            .write_all(text.as_bytes())
+            .with_context(|| format!("cannot write to {}", program))?;
+        let status = child.wait()?;
+        if status.success() {
+            return Ok(program);
+        }
+    }
+    bail!("no clipboard tool found — install pbcopy, wl-copy, xclip, or xsel")
+}
@@ -0,0 +1,165 @@
+//! User configuration from `~/.smc/config.toml` (missing file = defaults).
+//!
+//! ```toml
+//! [plugin]
+//! post_search = "my-filter --score"   # search hits, as JSONL
+//! post_export = "my-scrubber"         # session records before export renders them
+//!
+//! [limits]
+//! threads = 4                          # rayon worker threads (default: one per core)
+//! max_file_size = "500MB"              # skip larger sessions in multi-file scans
+//!
+//! [export]
+//! front_matter = true                  # YAML metadata block at the top
+//! stats_footer = true                  # JSON block of session numbers at the end
+//! template = "export.md"              # page layout with {{placeholders}}, relative to ~/.smc
+//! tags = ["claude"]                    # listed in the front matter
+//!
+//! [policy]
+//! include_smc = true                   # count smc's own output everywhere, like --include-smc
+//!
+//! [truncate]                           # default --truncate per command (0 = never truncate)
+//! search = 1000
+//! show = 500                           # tool input previews in show and thread
+//! context = 1000
+//! watch = 300
+//!
+//! [expand]                             # synonyms `search --expand` ORs into a term
+//! auth = ["authentication", "login", "oauth"]
+//! ```
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::util::state;
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub plugin: PluginConfig,
+    pub limits: LimitsConfig,
+    pub export: ExportConfig,
+    pub policy: PolicyConfig,
+    pub truncate: TruncateConfig,
+    /// Search synonyms: term → words it also matches under `--expand`.
+    pub expand: BTreeMap<String, Vec<String>>,
+}
+
+/// External filter commands; see [`crate::util::plugin`].
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginConfig {
+    pub post_search: Option<String>,
+    pub post_export: Option<String>,
+}
+
+/// Defaults for `--threads` / `--max-file-size`.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub threads: Option<usize>,
+    /// Size with optional K/M/G suffix, e.g. `"500MB"`.
+    pub max_file_size: Option<String>,
+}
+
+/// Defaults for `smc export`.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    pub front_matter: bool,
+    pub stats_footer: bool,
+    /// Template file, relative to the smc home directory unless absolute.
+    pub template: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Defaults for the self-output policy; see [`crate::util::policy`].
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyConfig {
+    pub include_smc: bool,
+}
+
+/// Defaults for `--truncate`; unset keys keep the built-in limits.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TruncateConfig {
+    pub search: Option<usize>,
+    /// `show` and `thread`.
+    pub show: Option<usize>,
+    pub context: Option<usize>,
+    pub watch: Option<usize>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let path = state::smc_home_path().join(CONFIG_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(s) => Self::parse(&s).with_context(|| format!("invalid {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plugins() {
+        let c = Config::parse("[plugin]\npost_search = \"jq -c .\"\n").unwrap();
+        assert_eq!(c.plugin.post_search.as_deref(), Some("jq -c ."));
+        assert!(c.plugin.post_export.is_none());
+    }
+
+    #[test]
+    fn parses_limits() {
+        let c = Config::parse("[limits]\nthreads = 2\nmax_file_size = \"1G\"\n").unwrap();
+        assert_eq!(c.limits.threads, Some(2));
+        assert_eq!(c.limits.max_file_size.as_deref(), Some("1G"));
+    }
+
+    #[test]
+    fn parses_export() {
+        let c = Config::parse("[export]\nfront_matter = true\ntags = [\"a\", \"b\"]\n").unwrap();
+        assert!(c.export.front_matter);
+        assert!(!c.export.stats_footer);
+        assert_eq!(c.export.tags, ["a", "b"]);
+        assert!(c.export.template.is_none());
+    }
+
+    #[test]
+    fn parses_policy() {
+        assert!(Config::parse("[policy]\ninclude_smc = true\n").unwrap().policy.include_smc);
+        assert!(!Config::parse("").unwrap().policy.include_smc);
+    }
+
+    #[test]
+    fn parses_truncate() {
+        let c = Config::parse("[truncate]\nsearch = 0\nshow = 800\n").unwrap();
+        assert_eq!((c.truncate.search, c.truncate.show, c.truncate.context), (Some(0), Some(800), None));
+    }
+
+    #[test]
+    fn parses_expand() {
+        let c = Config::parse("[expand]\nauth = [\"login\", \"oauth\"]\n").unwrap();
+        assert_eq!(c.expand["auth"], ["login", "oauth"]);
+        let c = Config::parse("expand.db = [\"postgres\"]\n").unwrap();
+        assert_eq!(c.expand["db"], ["postgres"]);
+    }
+
+    #[test]
+    fn empty_and_unknown() {
+        assert!(Config::parse("").unwrap().plugin.post_search.is_none());
+        assert!(Config::parse("[plugin]\npost_serach = \"x\"\n").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Line diffs for Edit / MultiEdit tool calls.
+
+use serde_json::Value;
+
+/// Above this many line pairs, skip the LCS and emit all-removed/all-added.
+const MAX_CELLS: usize = 1_000_000;
+
+/// Line-level diff of `old` → `new`, each line prefixed with ` `, `-`, or `+`.
+pub fn lines(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let mut out = Vec::new();
+
+    if a.len() * b.len() > MAX_CELLS {
+        out.extend(a.iter().map(|l| format!("-{}", l)));
+        out.extend(b.iter().map(|l| format!("+{}", l)));
+        return out.join("\n");
+    }
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push(format!(" {}", a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(format!("-{}", a[i]));
+            i += 1;
+        } else {
+            out.push(format!("+{}", b[j]));
+            j += 1;
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Unified-style diff for an `Edit` or `MultiEdit` tool input.
+/// Returns `None` for other tools or malformed input.
+pub fn edit_diff(tool: &str, input: &Value) -> Option<String> {
+    let path = input.get("file_path").and_then(Value::as_str).unwrap_or("");
+    let edits: Vec<&Value> = match tool {
+        "Edit" => vec![input],
+        "MultiEdit" => input.get("edits")?.as_array()?.iter().collect(),
+        _ => return None,
+    };
+
+    let mut out = format!("--- {}\n+++ {}", path, path);
+    for edit in edits {
+        let old = edit.get("old_string")?.as_str()?;
+        let new = edit.get("new_string")?.as_str()?;
+        out.push_str("\n@@\n");
+        out.push_str(&lines(old, new));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_common_lines() {
+        assert_eq!(lines("a\nb\nc", "a\nx\nc"), " a\n-b\n+x\n c");
+    }
+
+    #[test]
+    fn edit_input() {
+        let d = edit_diff("Edit", &json!({"file_path": "f.rs", "old_string": "x", "new_string": "y"}));
+        assert_eq!(d.as_deref(), Some("--- f.rs\n+++ f.rs\n@@\n-x\n+y"));
+    }
+
+    #[test]
+    fn multi_edit_input() {
+        let input = json!({"file_path": "f.rs", "edits": [
+            {"old_string": "a", "new_string": "b"},
+            {"old_string": "c", "new_string": "c\nd"}
+        ]});
+        let d = edit_diff("MultiEdit", &input).unwrap();
+        assert_eq!(d.matches("@@").count(), 2);
+        assert!(d.ends_with(" c\n+d"));
+    }
+
+    #[test]
+    fn other_tools_are_ignored() {
+        assert!(edit_diff("Read", &json!({"file_path": "f.rs"})).is_none());
+    }
+}
@@ -1,7 +1,8 @@
 /// Session file discovery — finds all JSONL conversation logs under ~/.claude/projects.
+use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 // ── SessionFile ────────────────────────────────────────────────────────────
 
@@ -26,18 +27,62 @@ impl SessionFile {
             format!("{:.2}GB", b as f64 / (1024.0 * 1024.0 * 1024.0))
         }
     }
+
+    /// `file://` URL of the JSONL, percent-encoded.
+    pub fn file_url(&self) -> String {
+        file_url(&self.path)
+    }
+}
+
+/// `file://` URL of `path`, made absolute and percent-encoded.
+pub fn file_url(path: &Path) -> String {
+    let abs = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut url = String::from("file://");
+    for b in abs.to_string_lossy().bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => url.push(b as char),
+            _ => url.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    url
+}
+
+/// Parse a byte size such as `1048576`, `500K`, `500MB`, or `1.5G`
+/// (binary units, case-insensitive, optional `B`/`iB`).
+pub fn parse_size(s: &str) -> Result<u64> {
+    let t = s.trim().to_ascii_uppercase();
+    let t = t.strip_suffix("IB").or_else(|| t.strip_suffix('B')).unwrap_or(&t);
+    let (num, mult) = match t.chars().last() {
+        Some('K') => (&t[..t.len() - 1], 1u64 << 10),
+        Some('M') => (&t[..t.len() - 1], 1 << 20),
+        Some('G') => (&t[..t.len() - 1], 1 << 30),
+        Some('T') => (&t[..t.len() - 1], 1 << 40),
+        _ => (t, 1),
+    };
+    let n: f64 = num
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite() && *n >= 0.0)
+        .ok_or_else(|| anyhow::anyhow!("invalid size '{}' — expected e.g. 500MB or 2G", s))?;
+    Ok((n * mult as f64) as u64)
 }
 
 // ── Discovery ──────────────────────────────────────────────────────────────
 
-/// Resolve the Claude projects directory.
-pub fn claude_dir(path_override: Option<&str>) -> Result<PathBuf> {
-    let dir = if let Some(p) = path_override {
+/// The Claude projects directory path, whether or not it exists yet.
+pub fn claude_dir_path(path_override: Option<&str>) -> PathBuf {
+    if let Some(p) = path_override {
         PathBuf::from(p)
     } else {
         let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
         Path::new(&home).join(".claude").join("projects")
-    };
+    }
+}
+
+/// Resolve the Claude projects directory.
+pub fn claude_dir(path_override: Option<&str>) -> Result<PathBuf> {
+    let dir = claude_dir_path(path_override);
     anyhow::ensure!(dir.exists(), "Claude projects directory not found at {}", dir.display());
     Ok(dir)
 }
@@ -81,10 +126,134 @@ pub fn discover_jsonl_files(base: &Path) -> Result<Vec<SessionFile>> {
         }
     }
 
-    files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    files.sort_by_key(|f| std::cmp::Reverse(f.size_bytes));
     Ok(files)
 }
 
+/// Describe a single `<project-dir>/<session>.jsonl` path the way discovery
+/// would, e.g. for a file that appeared after the initial scan.
+pub fn session_file(path: &Path) -> Option<SessionFile> {
+    if path.extension().map_or(true, |e| e != "jsonl") {
+        return None;
+    }
+    let session_id = path.file_stem()?.to_str()?.to_string();
+    let dir_name = path.parent()?.file_name()?.to_str()?;
+    Some(SessionFile {
+        path: path.to_path_buf(),
+        session_id,
+        project_name: extract_project_name(dir_name),
+        size_bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+/// Session files spooled from stdin into a private temp directory, which is
+/// removed on drop.
+pub struct StdinSpool {
+    dir: PathBuf,
+    pub files: Vec<SessionFile>,
+}
+
+impl Drop for StdinSpool {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Read JSONL records from `input` and split them into one session file per
+/// `sessionId` (records without one stay with the previous session). The
+/// project name comes from the last path component of the first `cwd` seen.
+/// Lines are written as they are read; only the current session's file is
+/// held open.
+pub fn spool_stdin(input: impl BufRead) -> Result<StdinSpool> {
+    let mut spool = StdinSpool { dir: private_temp_dir("smc-stdin")?, files: Vec::new() };
+
+    let mut current: Option<usize> = None;
+    let mut out: Option<(usize, std::io::BufWriter<std::fs::File>)> = None;
+    for line in input.lines() {
+        let line = line.context("cannot read stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap_or_default();
+        let project = v
+            .get("cwd")
+            .and_then(|c| c.as_str())
+            .and_then(|c| c.rsplit('/').find(|s| !s.is_empty()))
+            .unwrap_or("stdin");
+
+        let idx = match (v.get("sessionId").and_then(|s| s.as_str()), current) {
+            (None, Some(i)) => i,
+            (id, _) => {
+                let id = id.unwrap_or("stdin");
+                match spool.files.iter().position(|f| f.session_id == id) {
+                    Some(i) => i,
+                    None => {
+                        let path = spool.dir.join(format!("{}.jsonl", id.replace(['/', '\\'], "_")));
+                        spool.files.push(SessionFile {
+                            path,
+                            session_id: id.to_string(),
+                            project_name: project.to_string(),
+                            size_bytes: 0,
+                        });
+                        spool.files.len() - 1
+                    }
+                }
+            }
+        };
+        let file = &mut spool.files[idx];
+        if file.project_name == "stdin" && project != "stdin" {
+            file.project_name = project.to_string();
+        }
+
+        if out.as_ref().map_or(true, |(i, _)| *i != idx) {
+            if let Some((_, mut w)) = out.take() {
+                w.flush()?;
+            }
+            let f = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file.path)
+                .with_context(|| format!("cannot write {}", file.path.display()))?;
+            out = Some((idx, std::io::BufWriter::new(f)));
+        }
+        let (_, w) = out.as_mut().expect("opened above");
+        w.write_all(line.as_bytes())?;
+        w.write_all(b"\n")?;
+        file.size_bytes += line.len() as u64 + 1;
+        current = Some(idx);
+    }
+    if let Some((_, mut w)) = out {
+        w.flush()?;
+    }
+    spool.files.sort_by_key(|f| std::cmp::Reverse(f.size_bytes));
+    Ok(spool)
+}
+
+/// A new directory under the system temp dir, readable only by this user.
+/// The name ends in a random suffix and creation fails if it already
+/// exists, so nothing planted there beforehand is written through or
+/// removed.
+fn private_temp_dir(prefix: &str) -> Result<PathBuf> {
+    use std::hash::{BuildHasher, Hasher};
+    let mut last_err = None;
+    for attempt in 0..16u32 {
+        let mut h = std::collections::hash_map::RandomState::new().build_hasher();
+        h.write_u32(std::process::id());
+        h.write_u32(attempt);
+        h.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_nanos()));
+        let dir = std::env::temp_dir().join(format!("{}-{:016x}", prefix, h.finish()));
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => last_err = Some(e),
+            Err(e) => return Err(e).with_context(|| format!("cannot create {}", dir.display())),
+        }
+    }
+    Err(last_err.expect("loop ran")).context("cannot create a temp directory")
+}
+
 /// Find a session by exact ID or unique prefix.
 pub fn find_session<'a>(
     files: &'a [SessionFile],
@@ -133,6 +302,16 @@ fn extract_project_name(dir_name: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500MB").unwrap(), 500 << 20);
+        assert_eq!(parse_size("2g").unwrap(), 2 << 30);
+        assert_eq!(parse_size("1.5KiB").unwrap(), 1536);
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("-1M").is_err());
+    }
+
     #[test]
     fn extracts_github_project() {
         assert_eq!(extract_project_name("-Users-travis-GitHub-myapp"), "myapp");
@@ -146,8 +325,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn file_url_percent_encodes() {
+        let f = SessionFile {
+            path: PathBuf::from("/no/such dir/a b.jsonl"),
+            session_id: "a b".into(),
+            project_name: "x".into(),
+            size_bytes: 0,
+        };
+        assert_eq!(f.file_url(), "file:///no/such%20dir/a%20b.jsonl");
+    }
+
     #[test]
     fn fallback_last_segment() {
         assert_eq!(extract_project_name("-Users-travis-something"), "something");
     }
+
+    #[test]
+    fn spool_splits_by_session() {
+        let input = concat!(
+            "{\"sessionId\":\"s1\",\"cwd\":\"/w/alpha\"}\n",
+            "{\"sessionId\":\"s2\"}\n",
+            "\n",
+            "{\"type\":\"summary\"}\n",
+            "{\"sessionId\":\"s1\"}\n",
+        );
+        let spool = spool_stdin(input.as_bytes()).unwrap();
+        let mut ids: Vec<(&str, &str)> =
+            spool.files.iter().map(|f| (f.session_id.as_str(), f.project_name.as_str())).collect();
+        ids.sort();
+        assert_eq!(ids, [("s1", "alpha"), ("s2", "stdin")]);
+        let s2 = spool.files.iter().find(|f| f.session_id == "s2").unwrap();
+        assert_eq!(std::fs::read_to_string(&s2.path).unwrap().lines().count(), 2);
+
+        let dir = spool.dir.clone();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+        }
+        let other = spool_stdin("{\"sessionId\":\"s1\"}\n".as_bytes()).unwrap();
+        assert_ne!(other.dir, dir);
+        drop(spool);
+        assert!(!dir.exists());
+    }
 }
@@ -0,0 +1,105 @@
+//! Fuzzy project-name resolution for `--project`.
+//!
+//! A project filter matches every project whose name contains it. When none
+//! does, the filter is most likely a typo: a filter that is one or two edits
+//! away from a single project resolves to that project, and one that is
+//! close to several fails with "did you mean" suggestions instead of
+//! silently matching nothing.
+
+use anyhow::Result;
+
+/// Most suggestions listed when a filter is ambiguous.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A project near a filter. `distance` 0 means the name contains it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectMatch {
+    pub project: String,
+    pub distance: usize,
+}
+
+/// Edits allowed for a filter of this many characters to still count as near.
+fn max_distance(query_len: usize) -> usize {
+    (query_len / 3).max(1)
+}
+
+/// Fewest edits (insert, delete, substitute, swap two neighbours) that turn
+/// `query` into some substring of `name`, ignoring case.
+pub fn distance(query: &str, name: &str) -> usize {
+    let q: Vec<char> = query.to_lowercase().chars().collect();
+    let n: Vec<char> = name.to_lowercase().chars().collect();
+    // d[i][j]: `q[..i]` against a substring of `name` ending at `j`; the
+    // substring may start anywhere, so row 0 is free.
+    let mut d = vec![vec![0usize; n.len() + 1]; q.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=q.len() {
+        for j in 1..=n.len() {
+            let cost = usize::from(q[i - 1] != n[j - 1]);
+            let mut best = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && q[i - 1] == n[j - 2] && q[i - 2] == n[j - 1] {
+                best = best.min(d[i - 2][j - 2] + 1);
+            }
+            d[i][j] = best;
+        }
+    }
+    d[q.len()].iter().copied().min().unwrap_or(q.len())
+}
+
+/// Projects near `query`, closest first, then by name.
+pub fn rank<'a>(projects: impl IntoIterator<Item = &'a str>, query: &str) -> Vec<ProjectMatch> {
+    let limit = max_distance(query.chars().count());
+    let mut names: Vec<&str> = projects.into_iter().collect();
+    names.sort_unstable();
+    names.dedup();
+    let mut matches: Vec<ProjectMatch> = names
+        .into_iter()
+        .map(|p| ProjectMatch { project: p.to_string(), distance: distance(query, p) })
+        .filter(|m| m.distance <= limit)
+        .collect();
+    matches.sort_by(|a, b| a.distance.cmp(&b.distance).then_with(|| a.project.cmp(&b.project)));
+    matches
+}
+
+/// Resolve a project filter: `None` when some project name contains it as
+/// typed, `Some(name)` when it is a typo of exactly one project, an error
+/// with suggestions otherwise.
+pub fn resolve_project<'a>(projects: impl IntoIterator<Item = &'a str>, query: &str) -> Result<Option<String>> {
+    let matches = rank(projects, query);
+    match matches.as_slice() {
+        [] => anyhow::bail!("no project matches '{}' — run `smc projects` to list them", query),
+        [first, ..] if first.distance == 0 => Ok(None),
+        [only] => Ok(Some(only.project.clone())),
+        [first, second, ..] if first.distance < second.distance => Ok(Some(first.project.clone())),
+        _ => {
+            let names: Vec<&str> = matches.iter().take(MAX_SUGGESTIONS).map(|m| m.project.as_str()).collect();
+            anyhow::bail!("no project matches '{}' — did you mean: {}?", query, names.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_typos_and_suggests() {
+        assert_eq!(distance("alpha", "my-alpha-web"), 0);
+        assert_eq!(distance("alhpa", "alpha"), 1);
+        assert_eq!(distance("ALPAH", "alpha"), 1);
+        assert_eq!(distance("smc-cli", "smc_cli"), 1);
+
+        let projects = ["alpha", "beta", "smc_cli", "web-api", "web-app"];
+        assert_eq!(resolve_project(projects, "alp").unwrap(), None);
+        assert_eq!(resolve_project(projects, "btea").unwrap(), Some("beta".into()));
+        assert_eq!(resolve_project(projects, "smc-cli").unwrap(), Some("smc_cli".into()));
+        let err = resolve_project(projects, "web-apx").unwrap_err().to_string();
+        assert!(err.ends_with("did you mean: web-api, web-app?"), "{}", err);
+        assert!(resolve_project(projects, "zzzzzz").unwrap_err().to_string().contains("smc projects"));
+
+        let ranked = rank(projects, "web-ap");
+        assert_eq!(ranked[0], ProjectMatch { project: "web-api".into(), distance: 0 });
+        assert_eq!(ranked.len(), 2);
+    }
+}
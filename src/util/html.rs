@@ -0,0 +1,40 @@
+//! Minimal HTML helpers for the static-site and report writers.
+
+/// Escape text for use in HTML element content or attribute values.
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Shared stylesheet: readable on light and dark system themes.
+pub const STYLE: &str = r#"
+:root { color-scheme: light dark; --muted: #888; --border: #8884; }
+body { font: 15px/1.5 -apple-system, system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }
+a { color: #4a7bd0; text-decoration: none; } a:hover { text-decoration: underline; }
+pre { white-space: pre-wrap; word-break: break-word; background: #8881; padding: .6rem; border-radius: 4px; }
+.meta { color: var(--muted); font-size: .85em; }
+.msg { border-top: 1px solid var(--border); padding: .5rem 0; }
+.role-user h3 { color: #2e8b57; } .role-assistant h3 { color: #4a7bd0; } .role-system h3 { color: var(--muted); }
+input[type=search] { width: 100%; font-size: 1rem; padding: .4rem; margin: 1rem 0; }
+table { border-collapse: collapse; width: 100%; } td, th { text-align: left; padding: .2rem .5rem; border-bottom: 1px solid var(--border); }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(escape(r#"<a href="x">&</a>"#), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
@@ -1,2 +1,20 @@
 pub mod tokens;
 pub mod discover;
+pub mod time;
+pub mod text;
+pub mod diff;
+pub mod html;
+pub mod state;
+pub mod redact;
+pub mod clipboard;
+pub mod plugin;
+pub mod config;
+pub mod query;
+pub mod tail;
+pub mod progress;
+pub mod cache;
+pub mod policy;
+pub mod fuzzy;
+pub mod stem;
+#[cfg(test)]
+pub mod testutil;
@@ -0,0 +1,102 @@
+//! External filter commands: records go in as JSONL on stdin, the
+//! transformed stream comes back as JSONL on stdout. Records stay raw JSON
+//! both ways, so their fields keep the order they were written in.
+
+use std::io::{BufRead, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use serde_json::value::RawValue;
+
+use crate::util::text;
+
+/// What a plugin wrote back.
+#[derive(Debug, Default)]
+pub struct Piped {
+    pub records: Vec<Box<RawValue>>,
+    /// One message per non-blank output line that wasn't JSON and was dropped.
+    pub warnings: Vec<String>,
+}
+
+/// Run `cmd` through the shell and pipe `records` through it. Output lines
+/// that aren't JSON are dropped with a warning; a non-zero exit is an error.
+pub fn pipe(cmd: &str, records: &[Box<RawValue>]) -> Result<Piped> {
+    let mut child = shell(cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("cannot run plugin '{}'", cmd))?;
+
+    let mut stdin = child.stdin.take().context("plugin stdin unavailable")?;
+    let input: Vec<u8> = records
+        .iter()
+        .flat_map(|r| r.get().bytes().chain([b'\n']))
+        .collect();
+    // Write from a separate thread so a plugin that streams output before
+    // draining its input can't deadlock us.
+    let writer = std::thread::spawn(move || {
+        // A filter may legitimately exit without reading everything.
+        let _ = stdin.write_all(&input);
+    });
+
+    let stdout = child.stdout.take().context("plugin stdout unavailable")?;
+    let mut out = Piped::default();
+    for (line_num, line) in std::io::BufReader::new(stdout).lines().enumerate() {
+        let line = line.with_context(|| format!("cannot read output of plugin '{}'", cmd))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<&RawValue>(&line) {
+            Ok(_) => out.records.push(RawValue::from_string(line)?),
+            Err(e) => out.warnings.push(format!(
+                "plugin '{}' output line {} is not JSON ({}), dropped: {}",
+                cmd,
+                line_num + 1,
+                e,
+                text::oneline(&line, 80)
+            )),
+        }
+    }
+    let _ = writer.join();
+
+    let status = child.wait()?;
+    anyhow::ensure!(status.success(), "plugin '{}' exited with {}", cmd, status);
+    Ok(out)
+}
+
+fn shell(cmd: &str) -> Command {
+    if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", cmd]);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.args(["-c", cmd]);
+        c
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn raw(s: &str) -> Box<RawValue> {
+        RawValue::from_string(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn pipes_through_filter() {
+        let input = [raw(r#"{"n":1}"#), raw(r#"{"type":"hit","n":2}"#)];
+        let out = pipe("grep -v '\"n\":1'; echo; echo not-json", &input).unwrap();
+        assert_eq!(out.records.iter().map(|r| r.get()).collect::<Vec<_>>(), [r#"{"type":"hit","n":2}"#]);
+        assert_eq!(out.warnings.len(), 1);
+        assert!(out.warnings[0].contains("line 3 is not JSON"), "{}", out.warnings[0]);
+        assert!(out.warnings[0].ends_with("dropped: not-json"));
+    }
+
+    #[test]
+    fn failing_filter_is_an_error() {
+        assert!(pipe("exit 3", &[]).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Self-output policy: what counts as smc's own output, and when scans skip it.
+//!
+//! smc output pasted back into a conversation (by a hook, or by Claude
+//! running smc) is wrapped in `<smc-cc-cli>` tags. Left in, a search for
+//! "X" finds its own earlier results for "X", and word, character, and tool
+//! statistics count the same text again every time it is echoed. So search,
+//! watch, recent, and freq skip any message that contains the tag unless
+//! `--include-smc` (or `[policy] include_smc` in `config.toml`) says
+//! otherwise. The summary cache and alerts always skip it.
+
+use std::borrow::Cow;
+
+/// Marks a block of smc output inside a transcript.
+pub const SMC_TAG: &str = "<smc-cc-cli>";
+
+/// The text holds smc output.
+pub fn is_smc_output(text: &str) -> bool {
+    text.contains(SMC_TAG)
+}
+
+/// A message with this text is left out of a scan under `include_smc`.
+pub fn skips(include_smc: bool, text: &str) -> bool {
+    !include_smc && is_smc_output(text)
+}
+
+/// Wrap `text` in smc tags, for output likely to be pasted back into a
+/// conversation (the clipboard).
+pub fn tag(text: &str) -> String {
+    format!("{}\n{}\n</smc-cc-cli>\n", SMC_TAG, text.trim_end())
+}
+
+/// What `--copy` puts on the clipboard: `text` in smc tags, or as it is
+/// with `--no-tag` for pasting somewhere that isn't a conversation.
+pub fn clipboard_text(text: &str, no_tag: bool) -> Cow<'_, str> {
+    if no_tag {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(tag(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_tagged_text_unless_included() {
+        let echoed = "<smc-cc-cli>{\"type\":\"search_hit\"}</smc-cc-cli>";
+        assert!(skips(false, echoed));
+        assert!(!skips(true, echoed));
+        assert!(!skips(false, "plain text"));
+        assert!(skips(false, &tag("- hit\n")));
+        assert_eq!(tag("- hit\n"), "<smc-cc-cli>\n- hit\n</smc-cc-cli>\n");
+    }
+
+    #[test]
+    fn clipboard_text_is_tagged_unless_no_tag() {
+        assert_eq!(clipboard_text("- hit\n", false), "<smc-cc-cli>\n- hit\n</smc-cc-cli>\n");
+        assert_eq!(clipboard_text("- hit\n", true), "- hit\n");
+    }
+}
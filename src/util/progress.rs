@@ -0,0 +1,22 @@
+//! Progress reporting for long scans, supplied by the caller.
+//!
+//! Library code only calls the trait; how progress is shown (a terminal
+//! line, a GUI bar, a server event) is up to the implementation passed in.
+//! Callbacks arrive from rayon worker threads, hence the `Sync` bound.
+
+/// Scan progress callbacks. Every method defaults to a no-op.
+pub trait Progress: Sync {
+    /// A scan over `files` session files totalling `bytes` is starting.
+    fn start(&self, _files: usize, _bytes: u64) {}
+
+    /// One more file of `bytes` has been processed.
+    fn inc(&self, _bytes: u64) {}
+
+    /// The scan is over (also called when it stops early).
+    fn finish(&self) {}
+}
+
+/// Reports nothing; what the plain `run` entry points use.
+pub struct Silent;
+
+impl Progress for Silent {}
@@ -0,0 +1,179 @@
+//! Filter expressions shared across commands.
+//!
+//! `role:assistant tool:Bash project:myapp after:2025-01-01 "timeout"` —
+//! whitespace-separated `key:value` pairs plus free search terms. Double
+//! quotes group words, and a quoted token is always a term, so
+//! `"http://example.com"` doesn't parse as a key.
+
+use anyhow::{bail, Result};
+
+use crate::util::discover::SessionFile;
+
+/// Every key the syntax accepts.
+pub const KEYS: &[&str] = &["role", "tool", "project", "session", "branch", "file", "after", "before"];
+
+/// Keys that narrow the set of session files and so work for every command.
+pub const FILE_KEYS: &[&str] = &["project", "session"];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub terms: Vec<String>,
+    pub role: Option<String>,
+    pub tool: Option<String>,
+    pub project: Option<String>,
+    /// Session ID prefix.
+    pub session: Option<String>,
+    pub branch: Option<String>,
+    pub file: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let mut f = Filter::default();
+        for (token, quoted) in tokenize(expr)? {
+            let pair = if quoted { None } else { token.split_once(':') };
+            let Some((key, value)) = pair else {
+                f.terms.push(token);
+                continue;
+            };
+            if value.is_empty() {
+                bail!("filter key '{}' needs a value", key);
+            }
+            let slot = match key {
+                "role" => &mut f.role,
+                "tool" => &mut f.tool,
+                "project" => &mut f.project,
+                "session" => &mut f.session,
+                "branch" => &mut f.branch,
+                "file" => &mut f.file,
+                "after" => &mut f.after,
+                "before" => &mut f.before,
+                _ => bail!("unknown filter key '{}' — use: {} (quote terms containing ':')", key, KEYS.join(", ")),
+            };
+            if slot.is_some() {
+                bail!("filter key '{}' given twice", key);
+            }
+            *slot = Some(value.to_string());
+        }
+        Ok(f)
+    }
+
+    /// Keys present in this filter, terms reported as `terms`.
+    pub fn keys(&self) -> Vec<&'static str> {
+        let slots = [
+            ("role", &self.role),
+            ("tool", &self.tool),
+            ("project", &self.project),
+            ("session", &self.session),
+            ("branch", &self.branch),
+            ("file", &self.file),
+            ("after", &self.after),
+            ("before", &self.before),
+        ];
+        let mut keys: Vec<&'static str> = slots.iter().filter(|(_, v)| v.is_some()).map(|(k, _)| *k).collect();
+        if !self.terms.is_empty() {
+            keys.push("terms");
+        }
+        keys
+    }
+
+    /// Fail if the filter uses anything beyond [`FILE_KEYS`] and `supported`.
+    pub fn ensure_supported(&self, command: &str, supported: &[&str]) -> Result<()> {
+        for key in self.keys() {
+            if !FILE_KEYS.contains(&key) && !supported.contains(&key) {
+                bail!("{} doesn't support '{}' in --filter", command, key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a session file passes the file-level keys.
+    pub fn matches_file(&self, file: &SessionFile) -> bool {
+        let project_ok = self
+            .project
+            .as_ref()
+            .map_or(true, |p| file.project_name.to_lowercase().contains(&p.to_lowercase()));
+        let session_ok = self.session.as_ref().map_or(true, |s| file.session_id.starts_with(s.as_str()));
+        project_ok && session_ok
+    }
+}
+
+/// Split on whitespace, honouring double quotes anywhere in a token
+/// (`tool:"Bash Output"`). Returns each token and whether it started quoted.
+fn tokenize(expr: &str) -> Result<Vec<(String, bool)>> {
+    let mut tokens = Vec::new();
+    let mut cur = String::new();
+    let mut in_quotes = false;
+    let mut started_quoted = false;
+    let mut has_token = false;
+
+    for c in expr.chars() {
+        match c {
+            '"' => {
+                if !has_token {
+                    started_quoted = true;
+                }
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    tokens.push((std::mem::take(&mut cur), started_quoted));
+                }
+                has_token = false;
+                started_quoted = false;
+            }
+            c => {
+                cur.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if in_quotes {
+        bail!("unterminated quote in filter '{}'", expr);
+    }
+    if has_token {
+        tokens.push((cur, started_quoted));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys_and_terms() {
+        let f = Filter::parse(r#"role:assistant tool:Bash project:myapp after:2025-01-01 "timeout error" retry"#).unwrap();
+        assert_eq!(f.role.as_deref(), Some("assistant"));
+        assert_eq!(f.tool.as_deref(), Some("Bash"));
+        assert_eq!(f.project.as_deref(), Some("myapp"));
+        assert_eq!(f.after.as_deref(), Some("2025-01-01"));
+        assert_eq!(f.terms, ["timeout error", "retry"]);
+    }
+
+    #[test]
+    fn quoted_values_and_terms() {
+        let f = Filter::parse(r#"file:"src/my file.rs" "http://x""#).unwrap();
+        assert_eq!(f.file.as_deref(), Some("src/my file.rs"));
+        assert_eq!(f.terms, ["http://x"]);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(Filter::parse("colour:red").is_err());
+        assert!(Filter::parse("role:").is_err());
+        assert!(Filter::parse("role:user role:assistant").is_err());
+        assert!(Filter::parse("\"open").is_err());
+    }
+
+    #[test]
+    fn support_checks() {
+        let f = Filter::parse("project:x tool:Bash").unwrap();
+        assert!(f.ensure_supported("search", &["tool"]).is_ok());
+        assert!(f.ensure_supported("stats", &[]).is_err());
+        assert!(Filter::parse("project:x").unwrap().ensure_supported("stats", &[]).is_ok());
+    }
+}
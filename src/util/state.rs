@@ -0,0 +1,32 @@
+//! smc's own state directory (~/.smc) — databases, caches, cursors.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// The state directory path, without creating it. `SMC_HOME` overrides `~/.smc`.
+pub fn smc_home_path() -> PathBuf {
+    match std::env::var_os("SMC_HOME") {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+            Path::new(&home).join(".smc")
+        }
+    }
+}
+
+/// Resolve and create the state directory.
+pub fn smc_home() -> Result<PathBuf> {
+    let dir = smc_home_path();
+    std::fs::create_dir_all(&dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    Ok(dir)
+}
+
+/// File modification time as Unix seconds (0 if unavailable).
+pub fn mtime_secs(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
@@ -0,0 +1,68 @@
+//! English stemming for `search --stem`: "deploying", "deployed", and
+//! "deploys" all reduce to "deploy", so one plain term finds every form.
+//!
+//! Text is split into words (runs of letters and digits), lowercased, and
+//! stemmed with the Snowball English algorithm. A term matches where its
+//! stemmed words appear in the same order, one after another.
+
+use std::ops::Range;
+
+use rust_stemmers::{Algorithm, Stemmer};
+
+/// A stemmed word and where the original sits in the text (bytes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub stem: String,
+    pub span: Range<usize>,
+}
+
+/// Every word of `text`, stemmed.
+pub fn words(text: &str) -> Vec<Word> {
+    let stemmer = Stemmer::create(Algorithm::English);
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                let stem = stemmer.stem(&text[s..i].to_lowercase()).into_owned();
+                out.push(Word { stem, span: s..i });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// The stems of a search term, in order.
+pub fn term(term: &str) -> Vec<String> {
+    words(term).into_iter().map(|w| w.stem).collect()
+}
+
+/// Byte spans in the text of each place `term` (from [`term`]) occurs in
+/// `words`.
+pub fn find_all<'a>(words: &'a [Word], term: &'a [String]) -> impl Iterator<Item = Range<usize>> + 'a {
+    let n = term.len().max(1);
+    words
+        .windows(n)
+        .filter(move |w| !term.is_empty() && w.iter().map(|w| &w.stem).eq(term.iter()))
+        .map(|w| w[0].span.start..w[w.len() - 1].span.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_word_forms() {
+        let text = "Deployed it; deploying again after the deployment.";
+        let words = words(text);
+        assert_eq!(words.len(), 7);
+        let spans: Vec<&str> = find_all(&words, &term("deploys")).map(|r| &text[r]).collect();
+        assert_eq!(spans, ["Deployed", "deploying", "deployment"]);
+        let spans: Vec<&str> = find_all(&words, &term("deploying again")).map(|r| &text[r]).collect();
+        assert_eq!(spans, ["deploying again"]);
+        assert_eq!(find_all(&words, &term("!!")).count(), 0);
+    }
+}
@@ -0,0 +1,189 @@
+//! Incremental reads of append-only JSONL files, from the end forwards
+//! ([`read_appended`]) or backwards ([`ReverseLines`]).
+
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// How far a reader has consumed a file: byte offset plus lines seen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cursor {
+    pub offset: u64,
+    pub line: usize,
+}
+
+impl Cursor {
+    /// A cursor at the current end of `path` (start if unreadable).
+    pub fn at_end(path: &Path) -> Self {
+        let Ok(mut f) = std::fs::File::open(path) else { return Self::default() };
+        let mut buf = [0u8; 64 * 1024];
+        let mut cursor = Self::default();
+        while let Ok(n) = f.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            cursor.offset += n as u64;
+            cursor.line += buf[..n].iter().filter(|&&b| b == b'\n').count();
+        }
+        // Don't count a trailing partial line as consumed.
+        let partial = tail_partial_len(path, cursor.offset);
+        cursor.offset -= partial;
+        cursor
+    }
+}
+
+/// Call `f(line_number, line)` (1-based) for every complete line after
+/// `cursor`, advancing it. A trailing line without `\n` is still being
+/// written and is left for the next call. A file that shrank below the
+/// cursor was rewritten, so reading restarts from the top.
+pub fn read_appended(path: &Path, cursor: &mut Cursor, mut f: impl FnMut(usize, &str)) -> std::io::Result<u64> {
+    let mut file = std::fs::File::open(path)?;
+    if file.metadata()?.len() < cursor.offset {
+        *cursor = Cursor::default();
+    }
+    file.seek(SeekFrom::Start(cursor.offset))?;
+
+    let start = cursor.offset;
+    let mut reader = std::io::BufReader::new(file);
+    let mut buf = String::new();
+    loop {
+        buf.clear();
+        let n = reader.read_line(&mut buf)?;
+        if n == 0 || !buf.ends_with('\n') {
+            break;
+        }
+        cursor.offset += n as u64;
+        cursor.line += 1;
+        f(cursor.line, buf.trim_end_matches(['\n', '\r']));
+    }
+    Ok(cursor.offset - start)
+}
+
+/// The lines of a file, last first, read backwards in blocks so the newest
+/// records of a large session cost one small read. Yields lines without
+/// their `\n` (an empty one for a file ending in `\n`).
+pub struct ReverseLines {
+    file: std::fs::File,
+    /// Bytes before `buf` not read yet.
+    pos: u64,
+    /// Bytes from `pos` up to the last line yielded.
+    buf: Vec<u8>,
+    block: usize,
+}
+
+impl ReverseLines {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        Self::with_block(path, 64 * 1024)
+    }
+
+    fn with_block(path: &Path, block: usize) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let pos = file.metadata()?.len();
+        Ok(Self { file, pos, buf: Vec::new(), block })
+    }
+
+    /// Prepend the block before `buf`. Grows with `buf` so a huge line is
+    /// read in a few passes rather than one copy per block.
+    fn read_back(&mut self) -> std::io::Result<()> {
+        let n = (self.block.max(self.buf.len()) as u64).min(self.pos);
+        self.pos -= n;
+        self.file.seek(SeekFrom::Start(self.pos))?;
+        let mut chunk = vec![0u8; n as usize];
+        self.file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&self.buf);
+        self.buf = chunk;
+        Ok(())
+    }
+}
+
+impl Iterator for ReverseLines {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = match self.buf.iter().rposition(|&b| b == b'\n') {
+                Some(i) => {
+                    let line = self.buf.split_off(i + 1);
+                    self.buf.truncate(i);
+                    line
+                }
+                None if self.pos == 0 && !self.buf.is_empty() => std::mem::take(&mut self.buf),
+                None if self.pos == 0 => return None,
+                None => {
+                    if let Err(e) = self.read_back() {
+                        self.pos = 0;
+                        self.buf.clear();
+                        return Some(Err(e));
+                    }
+                    continue;
+                }
+            };
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            return Some(Ok(line));
+        }
+    }
+}
+
+/// Bytes after the last `\n` in the first `len` bytes of `path`.
+fn tail_partial_len(path: &Path, len: u64) -> u64 {
+    let Ok(mut f) = std::fs::File::open(path) else { return 0 };
+    let back = len.min(64 * 1024);
+    if f.seek(SeekFrom::Start(len - back)).is_err() {
+        return 0;
+    }
+    let mut buf = vec![0u8; back as usize];
+    if f.read_exact(&mut buf).is_err() {
+        return 0;
+    }
+    match buf.iter().rposition(|&b| b == b'\n') {
+        Some(i) => (buf.len() - i - 1) as u64,
+        None if back == len => len,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testutil::TempDir;
+
+    #[test]
+    fn reads_only_complete_appended_lines() {
+        let dir = TempDir::new("tail");
+        let path = dir.join("s.jsonl");
+        std::fs::write(&path, "a\nb\npart").unwrap();
+
+        let mut cursor = Cursor::at_end(&path);
+        assert_eq!(cursor, Cursor { offset: 4, line: 2 });
+
+        let mut seen = Vec::new();
+        std::fs::write(&path, "a\nb\npartial\nc\nd").unwrap();
+        read_appended(&path, &mut cursor, |n, l| seen.push((n, l.to_string()))).unwrap();
+        assert_eq!(seen, [(3, "partial".to_string()), (4, "c".to_string())]);
+
+        std::fs::write(&path, "x\n").unwrap();
+        seen.clear();
+        read_appended(&path, &mut cursor, |n, l| seen.push((n, l.to_string()))).unwrap();
+        assert_eq!(seen, [(1, "x".to_string())]);
+    }
+
+    #[test]
+    fn reads_lines_backwards_across_blocks() {
+        let dir = TempDir::new("tail-rev");
+        let path = dir.join("s.jsonl");
+        let long = "x".repeat(40);
+        std::fs::write(&path, format!("one\r\ntwo\n{}\n\nlast", long)).unwrap();
+        let lines: Vec<String> = ReverseLines::with_block(&path, 3)
+            .unwrap()
+            .map(|l| String::from_utf8(l.unwrap()).unwrap())
+            .collect();
+        assert_eq!(lines, ["last", "", long.as_str(), "two", "one"]);
+
+        std::fs::write(&path, "a\n").unwrap();
+        let lines: Vec<Vec<u8>> = ReverseLines::open(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(lines, [b"".to_vec(), b"a".to_vec()]);
+    }
+}
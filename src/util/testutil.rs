@@ -0,0 +1,50 @@
+//! Test fixtures: session files in a scratch directory of their own.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::util::discover::SessionFile;
+
+/// A fresh directory under the system temp dir, removed on drop.
+///
+/// The name carries the process id and a per-process counter, so tests
+/// running in parallel, or in several test binaries at once, never share one.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        static N: AtomicUsize = AtomicUsize::new(0);
+        let n = N.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("smc-{}-{}-{}", name, std::process::id(), n));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    /// `body` written to `<id>.jsonl` as a session of `project`.
+    pub fn session(&self, id: &str, project: &str, body: &str) -> SessionFile {
+        let path = self.0.join(format!("{}.jsonl", id));
+        std::fs::write(&path, body).unwrap();
+        SessionFile { path, session_id: id.into(), project_name: project.into(), size_bytes: body.len() as u64 }
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
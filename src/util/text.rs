@@ -0,0 +1,35 @@
+//! Text preview helpers shared by record builders.
+
+/// First `max` chars of `s`; `max == 0` means no truncation.
+pub fn preview(s: &str, max: usize) -> String {
+    if max == 0 {
+        s.to_string()
+    } else {
+        s.chars().take(max).collect()
+    }
+}
+
+/// Single-line preview: newlines collapsed to spaces, then truncated.
+pub fn oneline(s: &str, max: usize) -> String {
+    preview(&s.replace(['\n', '\r'], " "), max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_on_char_boundary() {
+        assert_eq!(preview("héllo", 2), "hé");
+    }
+
+    #[test]
+    fn zero_is_unlimited() {
+        assert_eq!(preview("hello", 0), "hello");
+    }
+
+    #[test]
+    fn oneline_collapses_newlines() {
+        assert_eq!(oneline("a\nb\r\nc", 0), "a b  c");
+    }
+}
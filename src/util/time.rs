@@ -0,0 +1,203 @@
+//! Timestamp parsing and date-range filtering.
+//!
+//! Claude Code writes RFC 3339 timestamps; CLI date bounds accept a bare
+//! `YYYY-MM-DD` (midnight UTC), a full RFC 3339 string, or a span back from
+//! now such as `48h` or `7d`.
+
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, Utc};
+
+use crate::models::Timestamp;
+
+/// Inclusive `after`, exclusive `before`.
+pub type DateRange = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Parse an RFC 3339 timestamp into UTC.
+pub fn parse_rfc3339(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|d| d.with_timezone(&Utc))
+}
+
+/// Parse a CLI date bound (`YYYY-MM-DD`, RFC 3339, or a [`parse_span`]
+/// back from now).
+pub fn parse_bound(s: &str) -> Result<DateTime<Utc>> {
+    if let Some(dt) = parse_rfc3339(s) {
+        return Ok(dt);
+    }
+    if let Some(span) = parse_span(s) {
+        return Ok(Utc::now() - span);
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
+        anyhow::anyhow!("invalid date '{}' — expected YYYY-MM-DD, RFC 3339, or a span like 48h or 7d", s)
+    })?;
+    Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc())
+}
+
+/// A span like `90s`, `30m`, `48h`, `7d`, or `2w`.
+pub fn parse_span(s: &str) -> Option<chrono::Duration> {
+    let unit = s.find(|c: char| !c.is_ascii_digit()).filter(|&i| i > 0)?;
+    let n: i64 = s[..unit].parse().ok()?;
+    let secs = match &s[unit..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return None,
+    };
+    chrono::Duration::try_seconds(n.checked_mul(secs)?)
+}
+
+/// Parse optional `--after` / `--before` flags.
+pub fn parse_range(
+    after: Option<&str>,
+    before: Option<&str>,
+) -> Result<DateRange> {
+    Ok((after.map(parse_bound).transpose()?, before.map(parse_bound).transpose()?))
+}
+
+/// `after` is inclusive, `before` is exclusive.
+/// Records without a parseable timestamp always pass.
+pub fn in_range(
+    ts: Option<DateTime<Utc>>,
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+) -> bool {
+    let Some(ts) = ts else { return true };
+    if after.is_some_and(|a| ts < a) {
+        return false;
+    }
+    if before.is_some_and(|b| ts >= b) {
+        return false;
+    }
+    true
+}
+
+// ── Display ────────────────────────────────────────────────────────────────
+
+/// How timestamps are rendered in emitted records.
+///
+/// `local` rewrites the timestamp into the machine's timezone (still
+/// RFC 3339); `relative` adds an `age` field such as `"2h ago"`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeFormat {
+    pub local: bool,
+    pub relative: bool,
+}
+
+impl TimeFormat {
+    /// Rewrite `ts` in place per `local`; returns the `age` field value.
+    pub fn render(&self, ts: &mut Option<Timestamp>) -> Option<String> {
+        let t = ts.as_mut()?;
+        let utc = t.utc?;
+        if self.local {
+            t.raw = utc.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false);
+        }
+        self.relative.then(|| relative(utc, Utc::now()))
+    }
+}
+
+/// Human-friendly age of `ts` relative to `now`, e.g. `"3d ago"`.
+pub fn relative(ts: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - ts).num_seconds();
+    let (future, secs) = (secs < 0, secs.unsigned_abs());
+    let span = match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 => format!("{}h", secs / 3_600),
+        86_400..=5_183_999 => format!("{}d", secs / 86_400),
+        5_184_000..=31_535_999 => format!("{}mo", secs / 2_592_000),
+        _ => format!("{}y", secs / 31_536_000),
+    };
+    if future { format!("in {}", span) } else { format!("{} ago", span) }
+}
+
+/// Compact length of a span, e.g. `"45s"`, `"12m"`, `"1h 5m"`, `"2d 3h"`.
+pub fn duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 => format!("{}h {}m", secs / 3_600, secs % 3_600 / 60),
+        _ => format!("{}d {}h", secs / 86_400, secs % 86_400 / 3_600),
+    }
+}
+
+/// `template` with strftime tokens (`%Y-%m-%d`, ...) filled in from `at`,
+/// e.g. for dated output paths. Fails on an unknown token instead of
+/// panicking mid-format.
+pub fn strftime(template: &str, at: &DateTime<Local>) -> Result<String> {
+    let items: Vec<Item> = StrftimeItems::new(template).collect();
+    anyhow::ensure!(!items.contains(&Item::Error), "invalid date token in '{}'", template);
+    Ok(at.format_with_items(items.into_iter()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_date_is_midnight_utc() {
+        let dt = parse_bound("2025-03-01").unwrap();
+        assert_eq!(dt.to_rfc3339(), "2025-03-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn offsets_are_normalised() {
+        let a = parse_rfc3339("2025-03-01T01:00:00+02:00").unwrap();
+        let b = parse_rfc3339("2025-02-28T23:30:00Z").unwrap();
+        assert!(a < b);
+    }
+
+    #[test]
+    fn fills_date_tokens() {
+        use chrono::TimeZone;
+        let at = Local.with_ymd_and_hms(2025, 3, 9, 14, 5, 0).unwrap();
+        assert_eq!(strftime("notes/smc-%Y-%m-%d.md", &at).unwrap(), "notes/smc-2025-03-09.md");
+        assert_eq!(strftime("plain.md", &at).unwrap(), "plain.md");
+        assert!(strftime("bad-%Q.md", &at).is_err());
+    }
+
+    #[test]
+    fn spans_count_back_from_now() {
+        assert_eq!(parse_span("48h"), Some(chrono::Duration::hours(48)));
+        assert_eq!(parse_span("2w"), Some(chrono::Duration::days(14)));
+        assert_eq!(parse_span("h"), None);
+        assert_eq!(parse_span("3y"), None);
+        let week_ago = parse_bound("7d").unwrap();
+        assert!((Utc::now() - week_ago - chrono::Duration::days(7)).num_seconds().abs() < 5);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(parse_bound("yesterday").is_err());
+    }
+
+    #[test]
+    fn relative_spans() {
+        let now = parse_rfc3339("2025-03-10T12:00:00Z").unwrap();
+        let at = |s| parse_rfc3339(s).unwrap();
+        assert_eq!(relative(at("2025-03-10T11:59:30Z"), now), "30s ago");
+        assert_eq!(relative(at("2025-03-10T10:00:00Z"), now), "2h ago");
+        assert_eq!(relative(at("2025-03-07T12:00:00Z"), now), "3d ago");
+        assert_eq!(relative(at("2024-11-10T12:00:00Z"), now), "4mo ago");
+        assert_eq!(relative(at("2025-03-10T12:05:00Z"), now), "in 5m");
+    }
+
+    #[test]
+    fn duration_spans() {
+        assert_eq!(duration(45), "45s");
+        assert_eq!(duration(12 * 60 + 5), "12m");
+        assert_eq!(duration(3_600 + 5 * 60), "1h 5m");
+        assert_eq!(duration(2 * 86_400 + 3 * 3_600), "2d 3h");
+    }
+
+    #[test]
+    fn range_bounds() {
+        let (after, before) = parse_range(Some("2025-03-01"), Some("2025-03-02")).unwrap();
+        let inside = parse_rfc3339("2025-03-01T12:00:00Z");
+        let outside = parse_rfc3339("2025-03-02T00:00:00Z");
+        assert!(in_range(inside, after, before));
+        assert!(!in_range(outside, after, before));
+        assert!(in_range(None, after, before));
+    }
+}